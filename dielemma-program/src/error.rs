@@ -0,0 +1,285 @@
+//! Error types for the Dielemma program
+//!
+//! Every variant is returned to the runtime as `ProgramError::Custom(code)`.
+//! The numeric codes are part of the program's public interface: never reorder
+//! or reuse them, only append new variants at the end.
+
+use solana_program::{msg, program_error::ProgramError};
+use std::fmt;
+
+/// Errors that may be returned by the Dielemma program
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DielemmaError {
+    /// Deposit amount is zero or exceeds the maximum allowed
+    InvalidAmount = 0,
+    /// Timeout is outside the allowed range
+    InvalidTimeout = 1,
+    /// Deposit seed is empty, too long or not valid UTF-8
+    InvalidDepositSeed = 2,
+    /// Deposit account does not match the PDA derived from the seed
+    InvalidDepositAccount = 3,
+    /// Deposit token account does not match the vault PDA
+    InvalidVaultAccount = 4,
+    /// Signer is not the depositor of this deposit
+    NotDepositor = 5,
+    /// Signer is not the receiver of this deposit
+    NotReceiver = 6,
+    /// Signer is neither the depositor nor the receiver
+    Unauthorized = 7,
+    /// Proof-of-life has not expired yet
+    NotExpired = 8,
+    /// Deposit has already been withdrawn or claimed
+    AlreadyClosed = 9,
+    /// Deposit still holds tokens and cannot be closed
+    DepositStillActive = 10,
+    /// Token account is not owned by the expected wallet
+    InvalidTokenAccountOwner = 11,
+    /// Token account mint does not match the deposit mint
+    MintMismatch = 12,
+    /// DLM mint account is not the configured DLM mint
+    InvalidDlmMint = 13,
+    /// DLM token account is not the depositor's associated token account
+    InvalidDlmTokenAccount = 14,
+    /// Burn token account is not the burn address's associated token account
+    InvalidBurnTokenAccount = 15,
+    /// Stored proof-of-life timestamp is in the future or before genesis
+    InvalidTimestamp = 16,
+    /// Program is paused by the admin
+    ProgramPaused = 17,
+    /// Config account is not the config PDA or is not initialized
+    InvalidConfigAccount = 18,
+    /// Signer is not the config admin
+    NotAdmin = 19,
+    /// Signer is not the pending admin
+    NotPendingAdmin = 20,
+    /// Deposit does not hold native SOL (or wSOL, for claims), or holds native SOL where
+    /// only token deposits are supported
+    NotNativeDeposit = 21,
+    /// Beneficiary list is empty, too long, has duplicates or shares not adding up to 100%
+    InvalidBeneficiaries = 22,
+    /// Receiver's share has already been claimed, or a share was claimed before the change
+    ShareAlreadyClaimed = 23,
+    /// Other beneficiaries still have unclaimed shares
+    SharesOutstanding = 24,
+    /// No claim has been initiated on the deposit
+    ClaimNotInitiated = 25,
+    /// A claim has already been initiated on the deposit
+    ClaimAlreadyInitiated = 26,
+    /// The claim's challenge period has not passed yet
+    ChallengePeriodActive = 27,
+    /// Challenge period exceeds `MAX_CLAIM_CHALLENGE_SECONDS`
+    InvalidChallengePeriod = 28,
+    /// Delegate is unknown, already expired, the depositor, or the delegate list is full
+    InvalidDelegate = 29,
+    /// Delegate's expiry has passed
+    DelegateExpired = 30,
+    /// Missing or mismatched ed25519 signature for a signed heartbeat
+    InvalidHeartbeat = 31,
+    /// Signed heartbeat's expiry has passed
+    HeartbeatExpired = 32,
+    /// Signed heartbeat's nonce is not greater than the last one used
+    HeartbeatNonceUsed = 33,
+    /// Batch is empty, too large, or lists a deposit account twice
+    InvalidBatch = 34,
+    /// Liveness profile account does not match the depositor's profile PDA
+    InvalidProfileAccount = 35,
+    /// Guardian list or threshold is invalid
+    InvalidGuardians = 36,
+    /// Signer is not a guardian of this deposit
+    NotGuardian = 37,
+    /// Guardian has already attested the depositor's death
+    AlreadyAttested = 38,
+    /// Vesting duration is out of bounds or the deposit is still vesting
+    InvalidVesting = 39,
+    /// Nothing has vested since the last claim
+    NothingVested = 40,
+    /// Release tranches are invalid or the deposit is not fully unlocked
+    InvalidTranches = 41,
+    /// No further tranche has unlocked since the last claim
+    NothingUnlocked = 42,
+    /// Release date is in the past or missing
+    InvalidReleaseDate = 43,
+    /// Deposit cannot be withdrawn before its withdrawal lock ends
+    WithdrawLocked = 44,
+    /// Fallback receivers are invalid
+    InvalidFallbackChain = 45,
+    /// Receiver's claim window is not open
+    OutsideClaimWindow = 46,
+    /// New depositor is the current depositor, a receiver, a fallback receiver, a guardian or a delegate
+    InvalidNewDepositor = 47,
+    /// Deposit account uses an older layout and must be converted with MigrateDeposit
+    OutdatedDepositLayout = 48,
+    /// Proof of life has expired or a claim is pending, so the deposit must be proven alive first
+    DepositExpired = 49,
+}
+
+impl DielemmaError {
+    /// Numeric code carried by `ProgramError::Custom`
+    pub fn code(self) -> u32 {
+        self as u32
+    }
+}
+
+impl From<DielemmaError> for ProgramError {
+    fn from(e: DielemmaError) -> Self {
+        msg!("Dielemma error: {}", e);
+        ProgramError::Custom(e as u32)
+    }
+}
+
+impl TryFrom<u32> for DielemmaError {
+    type Error = u32;
+
+    /// Decode a `ProgramError::Custom` code returned by the program
+    fn try_from(code: u32) -> Result<Self, Self::Error> {
+        use DielemmaError::*;
+        Ok(match code {
+            0 => InvalidAmount,
+            1 => InvalidTimeout,
+            2 => InvalidDepositSeed,
+            3 => InvalidDepositAccount,
+            4 => InvalidVaultAccount,
+            5 => NotDepositor,
+            6 => NotReceiver,
+            7 => Unauthorized,
+            8 => NotExpired,
+            9 => AlreadyClosed,
+            10 => DepositStillActive,
+            11 => InvalidTokenAccountOwner,
+            12 => MintMismatch,
+            13 => InvalidDlmMint,
+            14 => InvalidDlmTokenAccount,
+            15 => InvalidBurnTokenAccount,
+            16 => InvalidTimestamp,
+            17 => ProgramPaused,
+            18 => InvalidConfigAccount,
+            19 => NotAdmin,
+            20 => NotPendingAdmin,
+            21 => NotNativeDeposit,
+            22 => InvalidBeneficiaries,
+            23 => ShareAlreadyClaimed,
+            24 => SharesOutstanding,
+            25 => ClaimNotInitiated,
+            26 => ClaimAlreadyInitiated,
+            27 => ChallengePeriodActive,
+            28 => InvalidChallengePeriod,
+            29 => InvalidDelegate,
+            30 => DelegateExpired,
+            31 => InvalidHeartbeat,
+            32 => HeartbeatExpired,
+            33 => HeartbeatNonceUsed,
+            34 => InvalidBatch,
+            35 => InvalidProfileAccount,
+            36 => InvalidGuardians,
+            37 => NotGuardian,
+            38 => AlreadyAttested,
+            39 => InvalidVesting,
+            40 => NothingVested,
+            41 => InvalidTranches,
+            42 => NothingUnlocked,
+            43 => InvalidReleaseDate,
+            44 => WithdrawLocked,
+            45 => InvalidFallbackChain,
+            46 => OutsideClaimWindow,
+            47 => InvalidNewDepositor,
+            48 => OutdatedDepositLayout,
+            49 => DepositExpired,
+            _ => return Err(code),
+        })
+    }
+}
+
+impl TryFrom<&ProgramError> for DielemmaError {
+    type Error = ();
+
+    /// Decode a `ProgramError` if it carries a Dielemma error code
+    fn try_from(e: &ProgramError) -> Result<Self, Self::Error> {
+        match e {
+            ProgramError::Custom(code) => DielemmaError::try_from(*code).map_err(|_| ()),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for DielemmaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            DielemmaError::InvalidAmount => "Invalid deposit amount",
+            DielemmaError::InvalidTimeout => "Invalid timeout",
+            DielemmaError::InvalidDepositSeed => "Invalid deposit seed",
+            DielemmaError::InvalidDepositAccount => "Invalid deposit account PDA",
+            DielemmaError::InvalidVaultAccount => "Invalid deposit token account PDA",
+            DielemmaError::NotDepositor => "Signer is not the depositor",
+            DielemmaError::NotReceiver => "Signer is not the receiver",
+            DielemmaError::Unauthorized => "Signer is neither depositor nor receiver",
+            DielemmaError::NotExpired => "Proof of life has not expired yet",
+            DielemmaError::AlreadyClosed => "Deposit already withdrawn or claimed",
+            DielemmaError::DepositStillActive => "Deposit still holds tokens",
+            DielemmaError::InvalidTokenAccountOwner => "Token account has the wrong owner",
+            DielemmaError::MintMismatch => "Token account mint does not match deposit mint",
+            DielemmaError::InvalidDlmMint => "Invalid DLM mint account",
+            DielemmaError::InvalidDlmTokenAccount => "Invalid depositor DLM token account",
+            DielemmaError::InvalidBurnTokenAccount => "Invalid burn DLM token account",
+            DielemmaError::InvalidTimestamp => "Invalid last proof-of-life timestamp",
+            DielemmaError::ProgramPaused => "Program is paused",
+            DielemmaError::InvalidConfigAccount => "Invalid config account",
+            DielemmaError::NotAdmin => "Signer is not the admin",
+            DielemmaError::NotPendingAdmin => "Signer is not the pending admin",
+            DielemmaError::NotNativeDeposit => "Deposit does not hold the expected native SOL or tokens",
+            DielemmaError::InvalidBeneficiaries => "Invalid beneficiary shares",
+            DielemmaError::ShareAlreadyClaimed => "Share already claimed",
+            DielemmaError::SharesOutstanding => "Other beneficiaries have unclaimed shares",
+            DielemmaError::ClaimNotInitiated => "No claim has been initiated",
+            DielemmaError::ClaimAlreadyInitiated => "A claim has already been initiated",
+            DielemmaError::ChallengePeriodActive => "Claim challenge period has not passed yet",
+            DielemmaError::InvalidChallengePeriod => "Invalid claim challenge period",
+            DielemmaError::InvalidDelegate => "Invalid delegate",
+            DielemmaError::DelegateExpired => "Delegate has expired",
+            DielemmaError::InvalidHeartbeat => "Invalid signed heartbeat",
+            DielemmaError::HeartbeatExpired => "Signed heartbeat has expired",
+            DielemmaError::HeartbeatNonceUsed => "Signed heartbeat nonce already used",
+            DielemmaError::InvalidBatch => "Invalid proof-of-life batch",
+            DielemmaError::InvalidProfileAccount => "Invalid liveness profile account",
+            DielemmaError::InvalidGuardians => "Invalid guardians",
+            DielemmaError::NotGuardian => "Signer is not a guardian",
+            DielemmaError::AlreadyAttested => "Guardian has already attested",
+            DielemmaError::InvalidVesting => "Invalid vesting",
+            DielemmaError::NothingVested => "Nothing has vested yet",
+            DielemmaError::InvalidTranches => "Invalid release tranches",
+            DielemmaError::NothingUnlocked => "No tranche has unlocked yet",
+            DielemmaError::InvalidReleaseDate => "Invalid release date",
+            DielemmaError::WithdrawLocked => "Deposit is locked against withdrawal",
+            DielemmaError::InvalidFallbackChain => "Invalid fallback receivers",
+            DielemmaError::OutsideClaimWindow => "Receiver's claim window is not open",
+            DielemmaError::InvalidNewDepositor => "Invalid new depositor",
+            DielemmaError::OutdatedDepositLayout => "Deposit account must be migrated",
+            DielemmaError::DepositExpired => "Proof of life has expired",
+        };
+        f.write_str(s)
+    }
+}
+
+impl std::error::Error for DielemmaError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes_round_trip() {
+        let mut code = 0;
+        let mut messages = Vec::new();
+        while let Ok(err) = DielemmaError::try_from(code) {
+            assert_eq!(err.code(), code);
+            assert_eq!(ProgramError::from(err), ProgramError::Custom(code));
+            assert_eq!(DielemmaError::try_from(&ProgramError::Custom(code)), Ok(err));
+            let message = err.to_string();
+            assert!(!message.is_empty() && !messages.contains(&message), "{:?}", err);
+            messages.push(message);
+            code += 1;
+        }
+        // Every variant up to the last one decodes; update when appending variants
+        assert_eq!(code, DielemmaError::DepositExpired.code() + 1);
+    }
+}
@@ -0,0 +1,1655 @@
+//! Instruction builders for the Dielemma program
+//!
+//! `process_instruction` parses instructions manually rather than through Borsh:
+//! a little-endian `u32` discriminant, then (for deposit instructions) the deposit
+//! seed as a little-endian `u32` length followed by its UTF-8 bytes, then any
+//! variant-specific fields. The helpers here produce that layout together with the
+//! expected account list.
+
+use crate::{
+    DielemmaInstruction, BURN_ADDRESS, CONFIG_SEED_PREFIX, DEPOSIT_SEED_PREFIX, HEARTBEAT_MESSAGE_PREFIX,
+    PROFILE_SEED_PREFIX, SPL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID, TOKEN_ACCOUNT_SEED_PREFIX,
+};
+use solana_program::{
+    bpf_loader_upgradeable, ed25519_program,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+    sysvar::{self, rent::Rent, SysvarId},
+};
+
+impl DielemmaInstruction {
+    /// Pack the instruction into the wire format expected by `process_instruction`
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        match self {
+            DielemmaInstruction::Deposit {
+                deposit_seed,
+                receiver,
+                amount,
+                timeout_seconds,
+                withdraw_locked_until,
+            } => {
+                buf.extend_from_slice(&0u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+                buf.extend_from_slice(receiver.as_ref());
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&timeout_seconds.to_le_bytes());
+                buf.extend_from_slice(&withdraw_locked_until.to_le_bytes());
+            }
+            DielemmaInstruction::ProofOfLife { deposit_seed } => {
+                buf.extend_from_slice(&1u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+            }
+            DielemmaInstruction::Withdraw { deposit_seed } => {
+                buf.extend_from_slice(&2u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+            }
+            DielemmaInstruction::Claim { deposit_seed } => {
+                buf.extend_from_slice(&3u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+            }
+            DielemmaInstruction::CloseAccount { deposit_seed } => {
+                buf.extend_from_slice(&4u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+            }
+            DielemmaInstruction::InitializeConfig {
+                dlm_mint,
+                burn_address,
+                proof_of_life_cost,
+            } => {
+                buf.extend_from_slice(&5u32.to_le_bytes());
+                buf.extend_from_slice(dlm_mint.as_ref());
+                buf.extend_from_slice(burn_address.as_ref());
+                buf.extend_from_slice(&proof_of_life_cost.to_le_bytes());
+            }
+            DielemmaInstruction::SetPaused { paused } => {
+                buf.extend_from_slice(&6u32.to_le_bytes());
+                buf.push(*paused as u8);
+            }
+            DielemmaInstruction::UpdateConfig {
+                dlm_mint,
+                burn_address,
+                proof_of_life_cost,
+            } => {
+                buf.extend_from_slice(&7u32.to_le_bytes());
+                buf.extend_from_slice(dlm_mint.as_ref());
+                buf.extend_from_slice(burn_address.as_ref());
+                buf.extend_from_slice(&proof_of_life_cost.to_le_bytes());
+            }
+            DielemmaInstruction::ProposeAdmin { new_admin } => {
+                buf.extend_from_slice(&8u32.to_le_bytes());
+                buf.extend_from_slice(new_admin.as_ref());
+            }
+            DielemmaInstruction::AcceptAdmin => {
+                buf.extend_from_slice(&9u32.to_le_bytes());
+            }
+            DielemmaInstruction::DepositSol {
+                deposit_seed,
+                receiver,
+                amount,
+                timeout_seconds,
+                withdraw_locked_until,
+            } => {
+                buf.extend_from_slice(&10u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+                buf.extend_from_slice(receiver.as_ref());
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&timeout_seconds.to_le_bytes());
+                buf.extend_from_slice(&withdraw_locked_until.to_le_bytes());
+            }
+            DielemmaInstruction::WithdrawSol { deposit_seed } => {
+                buf.extend_from_slice(&11u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+            }
+            DielemmaInstruction::ClaimSol { deposit_seed } => {
+                buf.extend_from_slice(&12u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+            }
+            DielemmaInstruction::SetBeneficiaries {
+                deposit_seed,
+                beneficiaries,
+            } => {
+                buf.extend_from_slice(&13u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+                buf.push(beneficiaries.len() as u8);
+                for (receiver, share_bps) in beneficiaries {
+                    buf.extend_from_slice(receiver.as_ref());
+                    buf.extend_from_slice(&share_bps.to_le_bytes());
+                }
+            }
+            DielemmaInstruction::UpdateReceiver {
+                deposit_seed,
+                old_receiver,
+                new_receiver,
+                reset_timer,
+            } => {
+                buf.extend_from_slice(&14u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+                buf.extend_from_slice(old_receiver.as_ref());
+                buf.extend_from_slice(new_receiver.as_ref());
+                buf.push(*reset_timer as u8);
+            }
+            DielemmaInstruction::UpdateTimeout {
+                deposit_seed,
+                timeout_seconds,
+            } => {
+                buf.extend_from_slice(&15u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+                buf.extend_from_slice(&timeout_seconds.to_le_bytes());
+            }
+            DielemmaInstruction::TopUp { deposit_seed, amount } => {
+                buf.extend_from_slice(&16u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            DielemmaInstruction::PartialWithdraw { deposit_seed, amount } => {
+                buf.extend_from_slice(&17u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            DielemmaInstruction::InitiateClaim { deposit_seed } => {
+                buf.extend_from_slice(&18u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+            }
+            DielemmaInstruction::CancelClaim { deposit_seed } => {
+                buf.extend_from_slice(&19u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+            }
+            DielemmaInstruction::SetClaimChallengePeriod { challenge_seconds } => {
+                buf.extend_from_slice(&20u32.to_le_bytes());
+                buf.extend_from_slice(&challenge_seconds.to_le_bytes());
+            }
+            DielemmaInstruction::AddDelegate {
+                deposit_seed,
+                delegate,
+                expires_at,
+            } => {
+                buf.extend_from_slice(&21u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+                buf.extend_from_slice(delegate.as_ref());
+                buf.extend_from_slice(&expires_at.to_le_bytes());
+            }
+            DielemmaInstruction::RevokeDelegate { deposit_seed, delegate } => {
+                buf.extend_from_slice(&22u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+                buf.extend_from_slice(delegate.as_ref());
+            }
+            DielemmaInstruction::SignedProofOfLife {
+                deposit_seed,
+                nonce,
+                expires_at,
+            } => {
+                buf.extend_from_slice(&23u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+                buf.extend_from_slice(&nonce.to_le_bytes());
+                buf.extend_from_slice(&expires_at.to_le_bytes());
+            }
+            DielemmaInstruction::BatchProofOfLife { deposit_seeds } => {
+                buf.extend_from_slice(&24u32.to_le_bytes());
+                buf.push(deposit_seeds.len() as u8);
+                for deposit_seed in deposit_seeds {
+                    pack_seed(&mut buf, deposit_seed);
+                }
+            }
+            DielemmaInstruction::CreateProfile => {
+                buf.extend_from_slice(&25u32.to_le_bytes());
+            }
+            DielemmaInstruction::SetProfileLink { deposit_seed, linked } => {
+                buf.extend_from_slice(&26u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+                buf.push(*linked as u8);
+            }
+            DielemmaInstruction::ProfileProofOfLife => {
+                buf.extend_from_slice(&27u32.to_le_bytes());
+            }
+            DielemmaInstruction::SetGuardians {
+                deposit_seed,
+                threshold,
+                guardians,
+            } => {
+                buf.extend_from_slice(&28u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+                buf.push(*threshold);
+                buf.push(guardians.len() as u8);
+                for guardian in guardians {
+                    buf.extend_from_slice(guardian.as_ref());
+                }
+            }
+            DielemmaInstruction::AttestDeath { deposit_seed } => {
+                buf.extend_from_slice(&29u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+            }
+            DielemmaInstruction::SetVesting {
+                deposit_seed,
+                vesting_seconds,
+            } => {
+                buf.extend_from_slice(&30u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+                buf.extend_from_slice(&vesting_seconds.to_le_bytes());
+            }
+            DielemmaInstruction::SetReleaseTranches { deposit_seed, tranches } => {
+                buf.extend_from_slice(&31u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+                buf.push(tranches.len() as u8);
+                for (timeout_multiple, unlocked_bps) in tranches {
+                    buf.push(*timeout_multiple);
+                    buf.extend_from_slice(&unlocked_bps.to_le_bytes());
+                }
+            }
+            DielemmaInstruction::SetReleaseDate {
+                deposit_seed,
+                release_at,
+                release_at_only,
+            } => {
+                buf.extend_from_slice(&32u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+                buf.extend_from_slice(&release_at.to_le_bytes());
+                buf.push(*release_at_only as u8);
+            }
+            DielemmaInstruction::SetFallbackReceiver {
+                deposit_seed,
+                fallback_receiver,
+            } => {
+                buf.extend_from_slice(&33u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+                buf.extend_from_slice(fallback_receiver.as_ref());
+            }
+            DielemmaInstruction::Renounce { deposit_seed } => {
+                buf.extend_from_slice(&34u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+            }
+            DielemmaInstruction::SetFallbackChain { deposit_seed, fallbacks } => {
+                buf.extend_from_slice(&35u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+                buf.push(fallbacks.len() as u8);
+                for (receiver, grace_seconds) in fallbacks {
+                    buf.extend_from_slice(receiver.as_ref());
+                    buf.extend_from_slice(&grace_seconds.to_le_bytes());
+                }
+            }
+            DielemmaInstruction::RotateDepositor { deposit_seed } => {
+                buf.extend_from_slice(&36u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+            }
+            DielemmaInstruction::MigrateDeposit { deposit_seed } => {
+                buf.extend_from_slice(&37u32.to_le_bytes());
+                pack_seed(&mut buf, deposit_seed);
+            }
+        }
+        buf
+    }
+}
+
+/// Append a length-prefixed deposit seed
+fn pack_seed(buf: &mut Vec<u8>, deposit_seed: &str) {
+    buf.extend_from_slice(&(deposit_seed.len() as u32).to_le_bytes());
+    buf.extend_from_slice(deposit_seed.as_bytes());
+}
+
+/// Derive the deposit account PDA for a depositor and seed
+pub fn find_deposit_address(program_id: &Pubkey, depositor: &Pubkey, deposit_seed: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[DEPOSIT_SEED_PREFIX, depositor.as_ref(), deposit_seed.as_bytes()],
+        program_id,
+    )
+}
+
+/// Derive the token vault PDA that holds the tokens of a deposit
+pub fn find_vault_address(program_id: &Pubkey, deposit: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TOKEN_ACCOUNT_SEED_PREFIX, deposit.as_ref()], program_id)
+}
+
+/// Derive the global config PDA
+pub fn find_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED_PREFIX], program_id)
+}
+
+/// Derive the liveness profile PDA of a depositor
+pub fn find_profile_address(program_id: &Pubkey, depositor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROFILE_SEED_PREFIX, depositor.as_ref()], program_id)
+}
+
+/// Derive the program data account holding this program's upgrade authority
+pub fn find_program_data_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0
+}
+
+/// Derive the associated token account of a wallet for the given mint and token program
+pub fn get_associated_token_address(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    let associated_token_program_id = SPL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID
+        .parse::<Pubkey>()
+        .expect("valid associated token program id");
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &associated_token_program_id,
+    )
+    .0
+}
+
+/// Default burn address (the incinerator) used when initializing the config
+pub fn default_burn_address() -> Pubkey {
+    BURN_ADDRESS.parse::<Pubkey>().expect("valid burn address")
+}
+
+/// Derive the burn address's DLM token account (Token-2022 ATA)
+pub fn get_burn_token_address(burn_address: &Pubkey, dlm_mint: &Pubkey) -> Pubkey {
+    get_associated_token_address(burn_address, dlm_mint, &spl_token_2022::id())
+}
+
+/// Create a `Deposit` instruction
+///
+/// Creates the deposit PDA and its token vault, then moves `amount` tokens
+/// from `depositor_token_account` into the vault. Works with legacy Token and
+/// Token-2022 mints; extra accounts required by mint extensions (e.g. transfer
+/// hooks) can be appended to the returned instruction's account list.
+/// `withdraw_locked_until` bars the depositor from withdrawing before that date.
+#[allow(clippy::too_many_arguments)]
+pub fn deposit(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    depositor_token_account: &Pubkey,
+    token_mint: &Pubkey,
+    token_program: &Pubkey,
+    deposit_seed: &str,
+    receiver: &Pubkey,
+    amount: u64,
+    timeout_seconds: u64,
+    withdraw_locked_until: i64,
+) -> Instruction {
+    let (deposit_account, _) = find_deposit_address(program_id, depositor, deposit_seed);
+    let (vault, _) = find_vault_address(program_id, &deposit_account);
+    let data = DielemmaInstruction::Deposit {
+        deposit_seed: deposit_seed.to_string(),
+        receiver: *receiver,
+        amount,
+        timeout_seconds,
+        withdraw_locked_until,
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*depositor, true),
+            AccountMeta::new(deposit_account, false),
+            AccountMeta::new(*depositor_token_account, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(*token_mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(Rent::id(), false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+        data,
+    }
+}
+
+/// Create a `TopUp` instruction adding `amount` tokens to an existing deposit
+///
+/// Extra accounts required by mint extensions can be appended to the returned
+/// instruction's account list.
+pub fn top_up(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    depositor_token_account: &Pubkey,
+    token_mint: &Pubkey,
+    token_program: &Pubkey,
+    deposit_seed: &str,
+    amount: u64,
+) -> Instruction {
+    let (deposit_account, _) = find_deposit_address(program_id, depositor, deposit_seed);
+    let (vault, _) = find_vault_address(program_id, &deposit_account);
+    let data = DielemmaInstruction::TopUp {
+        deposit_seed: deposit_seed.to_string(),
+        amount,
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*depositor, true),
+            AccountMeta::new(deposit_account, false),
+            AccountMeta::new(*depositor_token_account, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(*token_mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+        data,
+    }
+}
+
+/// Create a `ProofOfLife` instruction
+///
+/// Burns DLM from the depositor's Token-2022 ATA into the burn address's ATA.
+/// `dlm_mint` and `burn_address` must match the program config. Extra accounts
+/// required by DLM mint extensions (e.g. transfer hooks) can be appended to the
+/// returned instruction's account list.
+pub fn proof_of_life(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    deposit_seed: &str,
+    dlm_mint: &Pubkey,
+    burn_address: &Pubkey,
+) -> Instruction {
+    delegated_proof_of_life(program_id, depositor, depositor, deposit_seed, dlm_mint, burn_address)
+}
+
+/// Create a `ProofOfLife` instruction signed by a delegate of `depositor`
+///
+/// The DLM cost is burned from the delegate's Token-2022 ATA.
+pub fn delegated_proof_of_life(
+    program_id: &Pubkey,
+    delegate: &Pubkey,
+    depositor: &Pubkey,
+    deposit_seed: &str,
+    dlm_mint: &Pubkey,
+    burn_address: &Pubkey,
+) -> Instruction {
+    let (deposit_account, _) = find_deposit_address(program_id, depositor, deposit_seed);
+    let token_program = spl_token_2022::id();
+    let data = DielemmaInstruction::ProofOfLife {
+        deposit_seed: deposit_seed.to_string(),
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*delegate, true),
+            AccountMeta::new(deposit_account, false),
+            AccountMeta::new(get_associated_token_address(delegate, dlm_mint, &token_program), false),
+            AccountMeta::new(get_burn_token_address(burn_address, dlm_mint), false),
+            AccountMeta::new_readonly(*dlm_mint, false),
+            AccountMeta::new_readonly(token_program, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+        data,
+    }
+}
+
+/// Create a `BatchProofOfLife` instruction refreshing all of `depositor`'s deposits in `deposit_seeds`
+pub fn batch_proof_of_life(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    deposit_seeds: &[&str],
+    dlm_mint: &Pubkey,
+    burn_address: &Pubkey,
+) -> Instruction {
+    let token_program = spl_token_2022::id();
+    let data = DielemmaInstruction::BatchProofOfLife {
+        deposit_seeds: deposit_seeds.iter().map(|seed| seed.to_string()).collect(),
+    }
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*depositor, true),
+        AccountMeta::new(get_associated_token_address(depositor, dlm_mint, &token_program), false),
+        AccountMeta::new(get_burn_token_address(burn_address, dlm_mint), false),
+        AccountMeta::new_readonly(*dlm_mint, false),
+        AccountMeta::new_readonly(token_program, false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+    accounts.extend(
+        deposit_seeds
+            .iter()
+            .map(|seed| AccountMeta::new(find_deposit_address(program_id, depositor, seed).0, false)),
+    );
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Create a `CreateProfile` instruction creating the depositor's liveness profile
+pub fn create_profile(program_id: &Pubkey, depositor: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*depositor, true),
+            AccountMeta::new(find_profile_address(program_id, depositor).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+        data: DielemmaInstruction::CreateProfile.pack(),
+    }
+}
+
+/// Create a `SetProfileLink` instruction linking a deposit to the depositor's liveness profile,
+/// or unlinking it
+pub fn set_profile_link(program_id: &Pubkey, depositor: &Pubkey, deposit_seed: &str, linked: bool) -> Instruction {
+    let mut instruction = depositor_instruction(
+        program_id,
+        depositor,
+        deposit_seed,
+        DielemmaInstruction::SetProfileLink {
+            deposit_seed: deposit_seed.to_string(),
+            linked,
+        },
+    );
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(find_profile_address(program_id, depositor).0, false));
+    instruction
+}
+
+/// Create a `ProfileProofOfLife` instruction refreshing every deposit linked to the depositor's profile
+///
+/// As with `proof_of_life`, extra accounts required by DLM mint extensions can be appended.
+pub fn profile_proof_of_life(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    dlm_mint: &Pubkey,
+    burn_address: &Pubkey,
+) -> Instruction {
+    let token_program = spl_token_2022::id();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*depositor, true),
+            AccountMeta::new(find_profile_address(program_id, depositor).0, false),
+            AccountMeta::new(get_associated_token_address(depositor, dlm_mint, &token_program), false),
+            AccountMeta::new(get_burn_token_address(burn_address, dlm_mint), false),
+            AccountMeta::new_readonly(*dlm_mint, false),
+            AccountMeta::new_readonly(token_program, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+        data: DielemmaInstruction::ProfileProofOfLife.pack(),
+    }
+}
+
+/// Message the depositor signs off-chain to authorize a `SignedProofOfLife`:
+/// `HEARTBEAT_MESSAGE_PREFIX`, the program ID, the deposit PDA, then the nonce and expiry as
+/// little-endian integers
+pub fn heartbeat_message(program_id: &Pubkey, deposit_account: &Pubkey, nonce: u64, expires_at: i64) -> Vec<u8> {
+    let mut message = Vec::with_capacity(HEARTBEAT_MESSAGE_PREFIX.len() + 32 + 32 + 8 + 8);
+    message.extend_from_slice(HEARTBEAT_MESSAGE_PREFIX);
+    message.extend_from_slice(program_id.as_ref());
+    message.extend_from_slice(deposit_account.as_ref());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message.extend_from_slice(&expires_at.to_le_bytes());
+    message
+}
+
+/// Create the ed25519 program instruction that must directly precede `signed_proof_of_life`
+///
+/// `signature` is the depositor's ed25519 signature over `message`; the public key,
+/// signature and message are all stored inline in the instruction data.
+pub fn ed25519_signature_instruction(signer: &Pubkey, signature: &[u8; 64], message: &[u8]) -> Instruction {
+    // 2 header bytes, then seven u16 offsets
+    const DATA_START: usize = 2 + 7 * 2;
+    let public_key_offset = DATA_START;
+    let signature_offset = public_key_offset + 32;
+    let message_offset = signature_offset + 64;
+
+    let mut data = Vec::with_capacity(message_offset + message.len());
+    data.extend_from_slice(&[1, 0]);
+    for value in [
+        signature_offset as u16,
+        u16::MAX,
+        public_key_offset as u16,
+        u16::MAX,
+        message_offset as u16,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(signer.as_ref());
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+
+    Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data,
+    }
+}
+
+/// Create a `SignedProofOfLife` instruction relaying the depositor's signed heartbeat
+///
+/// The DLM cost is burned from the relayer's Token-2022 ATA.
+#[allow(clippy::too_many_arguments)]
+pub fn signed_proof_of_life(
+    program_id: &Pubkey,
+    relayer: &Pubkey,
+    depositor: &Pubkey,
+    deposit_seed: &str,
+    nonce: u64,
+    expires_at: i64,
+    dlm_mint: &Pubkey,
+    burn_address: &Pubkey,
+) -> Instruction {
+    let (deposit_account, _) = find_deposit_address(program_id, depositor, deposit_seed);
+    let token_program = spl_token_2022::id();
+    let data = DielemmaInstruction::SignedProofOfLife {
+        deposit_seed: deposit_seed.to_string(),
+        nonce,
+        expires_at,
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*relayer, true),
+            AccountMeta::new(deposit_account, false),
+            AccountMeta::new(get_associated_token_address(relayer, dlm_mint, &token_program), false),
+            AccountMeta::new(get_burn_token_address(burn_address, dlm_mint), false),
+            AccountMeta::new_readonly(*dlm_mint, false),
+            AccountMeta::new_readonly(token_program, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Create a `Withdraw` instruction returning the vault balance to the depositor
+///
+/// As with `deposit`, transfer hook accounts can be appended to the account list.
+pub fn withdraw(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    depositor_token_account: &Pubkey,
+    token_mint: &Pubkey,
+    token_program: &Pubkey,
+    deposit_seed: &str,
+) -> Instruction {
+    let mut instruction = withdraw_instruction(
+        program_id,
+        depositor,
+        depositor_token_account,
+        token_mint,
+        token_program,
+        deposit_seed,
+        DielemmaInstruction::Withdraw {
+            deposit_seed: deposit_seed.to_string(),
+        },
+    );
+    // Writable so fees withheld on the emptied vault can be swept to the mint
+    instruction.accounts[6].is_writable = true;
+    instruction
+}
+
+/// Create a `PartialWithdraw` instruction returning `amount` tokens to the depositor
+pub fn partial_withdraw(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    depositor_token_account: &Pubkey,
+    token_mint: &Pubkey,
+    token_program: &Pubkey,
+    deposit_seed: &str,
+    amount: u64,
+) -> Instruction {
+    withdraw_instruction(
+        program_id,
+        depositor,
+        depositor_token_account,
+        token_mint,
+        token_program,
+        deposit_seed,
+        DielemmaInstruction::PartialWithdraw {
+            deposit_seed: deposit_seed.to_string(),
+            amount,
+        },
+    )
+}
+
+/// `Withdraw` and `PartialWithdraw` share the same account list
+fn withdraw_instruction(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    depositor_token_account: &Pubkey,
+    token_mint: &Pubkey,
+    token_program: &Pubkey,
+    deposit_seed: &str,
+    instruction: DielemmaInstruction,
+) -> Instruction {
+    let (deposit_account, _) = find_deposit_address(program_id, depositor, deposit_seed);
+    let (vault, _) = find_vault_address(program_id, &deposit_account);
+    let data = instruction.pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*depositor, true),
+            AccountMeta::new(deposit_account, false),
+            AccountMeta::new(*depositor_token_account, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(*token_mint, false),
+        ],
+        data,
+    }
+}
+
+/// Create a `Claim` instruction paying the vault balance to the receiver
+///
+/// Set `profile_linked` if the deposit is linked to the depositor's liveness profile.
+/// As with `deposit`, transfer hook accounts can be appended to the account list.
+#[allow(clippy::too_many_arguments)]
+pub fn claim(
+    program_id: &Pubkey,
+    receiver: &Pubkey,
+    depositor: &Pubkey,
+    receiver_token_account: &Pubkey,
+    token_mint: &Pubkey,
+    token_program: &Pubkey,
+    deposit_seed: &str,
+    profile_linked: bool,
+) -> Instruction {
+    let (deposit_account, _) = find_deposit_address(program_id, depositor, deposit_seed);
+    let (vault, _) = find_vault_address(program_id, &deposit_account);
+    let data = DielemmaInstruction::Claim {
+        deposit_seed: deposit_seed.to_string(),
+    }
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*receiver, true),
+        AccountMeta::new(deposit_account, false),
+        AccountMeta::new(*receiver_token_account, false),
+        AccountMeta::new(vault, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        AccountMeta::new(*token_mint, false),
+    ];
+    if profile_linked {
+        accounts.push(AccountMeta::new_readonly(find_profile_address(program_id, depositor).0, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Create a `CloseAccount` instruction
+///
+/// `authority` must be the depositor or the receiver; the deposit account's
+/// lamports are sent to `refund_recipient`.
+pub fn close_account(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    depositor: &Pubkey,
+    refund_recipient: &Pubkey,
+    deposit_seed: &str,
+) -> Instruction {
+    let (deposit_account, _) = find_deposit_address(program_id, depositor, deposit_seed);
+    let data = DielemmaInstruction::CloseAccount {
+        deposit_seed: deposit_seed.to_string(),
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(deposit_account, false),
+            AccountMeta::new(*refund_recipient, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Create a `DepositSol` instruction
+///
+/// The deposit account is created holding `amount` lamports on top of its rent.
+/// `withdraw_locked_until` bars the depositor from withdrawing before that date.
+pub fn deposit_sol(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    deposit_seed: &str,
+    receiver: &Pubkey,
+    amount: u64,
+    timeout_seconds: u64,
+    withdraw_locked_until: i64,
+) -> Instruction {
+    let (deposit_account, _) = find_deposit_address(program_id, depositor, deposit_seed);
+    let data = DielemmaInstruction::DepositSol {
+        deposit_seed: deposit_seed.to_string(),
+        receiver: *receiver,
+        amount,
+        timeout_seconds,
+        withdraw_locked_until,
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*depositor, true),
+            AccountMeta::new(deposit_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+        data,
+    }
+}
+
+/// Create a `WithdrawSol` instruction returning deposited lamports to the depositor
+pub fn withdraw_sol(program_id: &Pubkey, depositor: &Pubkey, deposit_seed: &str) -> Instruction {
+    let (deposit_account, _) = find_deposit_address(program_id, depositor, deposit_seed);
+    let data = DielemmaInstruction::WithdrawSol {
+        deposit_seed: deposit_seed.to_string(),
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*depositor, true),
+            AccountMeta::new(deposit_account, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+        data,
+    }
+}
+
+/// Create an `InitiateClaim` instruction starting the challenge period of a claim
+///
+/// Set `profile_linked` if the deposit is linked to the depositor's liveness profile.
+pub fn initiate_claim(
+    program_id: &Pubkey,
+    receiver: &Pubkey,
+    depositor: &Pubkey,
+    deposit_seed: &str,
+    profile_linked: bool,
+) -> Instruction {
+    let (deposit_account, _) = find_deposit_address(program_id, depositor, deposit_seed);
+    let data = DielemmaInstruction::InitiateClaim {
+        deposit_seed: deposit_seed.to_string(),
+    }
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*receiver, true),
+        AccountMeta::new(deposit_account, false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+    if profile_linked {
+        accounts.push(AccountMeta::new_readonly(find_profile_address(program_id, depositor).0, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Create a `CancelClaim` instruction voiding a pending claim
+pub fn cancel_claim(program_id: &Pubkey, depositor: &Pubkey, deposit_seed: &str) -> Instruction {
+    let (deposit_account, _) = find_deposit_address(program_id, depositor, deposit_seed);
+    let data = DielemmaInstruction::CancelClaim {
+        deposit_seed: deposit_seed.to_string(),
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*depositor, true),
+            AccountMeta::new(deposit_account, false),
+        ],
+        data,
+    }
+}
+
+/// Create a `ClaimSol` instruction paying deposited lamports to the receiver
+///
+/// Pass the token program of a wSOL deposit to unwrap its vault into SOL instead;
+/// leave it `None` for native SOL deposits. Set `profile_linked` if the deposit is
+/// linked to the depositor's liveness profile.
+pub fn claim_sol(
+    program_id: &Pubkey,
+    receiver: &Pubkey,
+    depositor: &Pubkey,
+    wsol_token_program: Option<&Pubkey>,
+    deposit_seed: &str,
+    profile_linked: bool,
+) -> Instruction {
+    let (deposit_account, _) = find_deposit_address(program_id, depositor, deposit_seed);
+    let data = DielemmaInstruction::ClaimSol {
+        deposit_seed: deposit_seed.to_string(),
+    }
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*receiver, true),
+        AccountMeta::new(deposit_account, false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+    if profile_linked {
+        accounts.push(AccountMeta::new_readonly(find_profile_address(program_id, depositor).0, false));
+    }
+    if let Some(token_program) = wsol_token_program {
+        let (vault, _) = find_vault_address(program_id, &deposit_account);
+        accounts.push(AccountMeta::new(vault, false));
+        accounts.push(AccountMeta::new_readonly(*token_program, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Create a `SetBeneficiaries` instruction splitting a deposit between receivers
+///
+/// `beneficiaries` pairs each receiver with a share in basis points; the shares must
+/// add up to `TOTAL_SHARE_BPS`.
+pub fn set_beneficiaries(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    deposit_seed: &str,
+    beneficiaries: &[(Pubkey, u16)],
+) -> Instruction {
+    depositor_instruction(
+        program_id,
+        depositor,
+        deposit_seed,
+        DielemmaInstruction::SetBeneficiaries {
+            deposit_seed: deposit_seed.to_string(),
+            beneficiaries: beneficiaries.to_vec(),
+        },
+    )
+}
+
+/// Create an `UpdateReceiver` instruction handing `old_receiver`'s share to `new_receiver`
+pub fn update_receiver(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    deposit_seed: &str,
+    old_receiver: &Pubkey,
+    new_receiver: &Pubkey,
+    reset_timer: bool,
+) -> Instruction {
+    depositor_instruction(
+        program_id,
+        depositor,
+        deposit_seed,
+        DielemmaInstruction::UpdateReceiver {
+            deposit_seed: deposit_seed.to_string(),
+            old_receiver: *old_receiver,
+            new_receiver: *new_receiver,
+            reset_timer,
+        },
+    )
+}
+
+/// Create an `AddDelegate` instruction (`expires_at` of 0 never expires)
+pub fn add_delegate(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    deposit_seed: &str,
+    delegate: &Pubkey,
+    expires_at: i64,
+) -> Instruction {
+    depositor_instruction(
+        program_id,
+        depositor,
+        deposit_seed,
+        DielemmaInstruction::AddDelegate {
+            deposit_seed: deposit_seed.to_string(),
+            delegate: *delegate,
+            expires_at,
+        },
+    )
+}
+
+/// Create a `RevokeDelegate` instruction
+pub fn revoke_delegate(program_id: &Pubkey, depositor: &Pubkey, deposit_seed: &str, delegate: &Pubkey) -> Instruction {
+    depositor_instruction(
+        program_id,
+        depositor,
+        deposit_seed,
+        DielemmaInstruction::RevokeDelegate {
+            deposit_seed: deposit_seed.to_string(),
+            delegate: *delegate,
+        },
+    )
+}
+
+/// Create an `UpdateTimeout` instruction
+///
+/// `profile_linked` must reflect whether the deposit is linked to the depositor's liveness profile.
+pub fn update_timeout(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    deposit_seed: &str,
+    timeout_seconds: u64,
+    profile_linked: bool,
+) -> Instruction {
+    let mut instruction = depositor_instruction(
+        program_id,
+        depositor,
+        deposit_seed,
+        DielemmaInstruction::UpdateTimeout {
+            deposit_seed: deposit_seed.to_string(),
+            timeout_seconds,
+        },
+    );
+    if profile_linked {
+        let profile = find_profile_address(program_id, depositor).0;
+        instruction.accounts.push(AccountMeta::new_readonly(profile, false));
+    }
+    instruction
+}
+
+/// Create a `SetGuardians` instruction; `threshold` of `guardians` can confirm the depositor's death
+pub fn set_guardians(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    deposit_seed: &str,
+    threshold: u8,
+    guardians: &[Pubkey],
+) -> Instruction {
+    depositor_instruction(
+        program_id,
+        depositor,
+        deposit_seed,
+        DielemmaInstruction::SetGuardians {
+            deposit_seed: deposit_seed.to_string(),
+            threshold,
+            guardians: guardians.to_vec(),
+        },
+    )
+}
+
+/// Create an `AttestDeath` instruction signed by a guardian of `depositor`'s deposit
+///
+/// Set `profile_linked` if the deposit is linked to the depositor's liveness profile.
+pub fn attest_death(
+    program_id: &Pubkey,
+    guardian: &Pubkey,
+    depositor: &Pubkey,
+    deposit_seed: &str,
+    profile_linked: bool,
+) -> Instruction {
+    let (deposit_account, _) = find_deposit_address(program_id, depositor, deposit_seed);
+    let data = DielemmaInstruction::AttestDeath {
+        deposit_seed: deposit_seed.to_string(),
+    }
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*guardian, true),
+        AccountMeta::new(deposit_account, false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+    if profile_linked {
+        accounts.push(AccountMeta::new_readonly(find_profile_address(program_id, depositor).0, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Create a `SetVesting` instruction; shares vest over `vesting_seconds` once claims start
+pub fn set_vesting(program_id: &Pubkey, depositor: &Pubkey, deposit_seed: &str, vesting_seconds: u64) -> Instruction {
+    depositor_instruction(
+        program_id,
+        depositor,
+        deposit_seed,
+        DielemmaInstruction::SetVesting {
+            deposit_seed: deposit_seed.to_string(),
+            vesting_seconds,
+        },
+    )
+}
+
+/// Create a `SetReleaseTranches` instruction from `(timeout_multiple, cumulative unlocked_bps)` pairs
+pub fn set_release_tranches(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    deposit_seed: &str,
+    tranches: &[(u8, u16)],
+) -> Instruction {
+    depositor_instruction(
+        program_id,
+        depositor,
+        deposit_seed,
+        DielemmaInstruction::SetReleaseTranches {
+            deposit_seed: deposit_seed.to_string(),
+            tranches: tranches.to_vec(),
+        },
+    )
+}
+
+/// Create a `SetReleaseDate` instruction; receivers may claim from `release_at` regardless of liveness
+pub fn set_release_date(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    deposit_seed: &str,
+    release_at: i64,
+    release_at_only: bool,
+) -> Instruction {
+    depositor_instruction(
+        program_id,
+        depositor,
+        deposit_seed,
+        DielemmaInstruction::SetReleaseDate {
+            deposit_seed: deposit_seed.to_string(),
+            release_at,
+            release_at_only,
+        },
+    )
+}
+
+/// Create a `SetFallbackReceiver` instruction; the default pubkey removes the fallback
+pub fn set_fallback_receiver(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    deposit_seed: &str,
+    fallback_receiver: &Pubkey,
+) -> Instruction {
+    depositor_instruction(
+        program_id,
+        depositor,
+        deposit_seed,
+        DielemmaInstruction::SetFallbackReceiver {
+            deposit_seed: deposit_seed.to_string(),
+            fallback_receiver: *fallback_receiver,
+        },
+    )
+}
+
+/// Create a `SetFallbackChain` instruction from ordered `(receiver, grace_seconds)` pairs
+pub fn set_fallback_chain(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    deposit_seed: &str,
+    fallbacks: &[(Pubkey, u64)],
+) -> Instruction {
+    depositor_instruction(
+        program_id,
+        depositor,
+        deposit_seed,
+        DielemmaInstruction::SetFallbackChain {
+            deposit_seed: deposit_seed.to_string(),
+            fallbacks: fallbacks.to_vec(),
+        },
+    )
+}
+
+/// Create a `Renounce` instruction giving up `receiver`'s claim on `depositor`'s deposit
+pub fn renounce(program_id: &Pubkey, receiver: &Pubkey, depositor: &Pubkey, deposit_seed: &str) -> Instruction {
+    let (deposit_account, _) = find_deposit_address(program_id, depositor, deposit_seed);
+    let data = DielemmaInstruction::Renounce {
+        deposit_seed: deposit_seed.to_string(),
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*receiver, true),
+            AccountMeta::new(deposit_account, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+        data,
+    }
+}
+
+/// Create a `RotateDepositor` instruction moving `depositor`'s deposit to `new_depositor`
+///
+/// `token_accounts` is the `(mint, token_program)` of a token deposit and `None` for a
+/// native SOL deposit. `profile_linked` must reflect whether the deposit is linked to the
+/// current depositor's liveness profile. Extra accounts required by mint extensions can
+/// be appended to the returned instruction's account list.
+pub fn rotate_depositor(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    new_depositor: &Pubkey,
+    deposit_seed: &str,
+    token_accounts: Option<(&Pubkey, &Pubkey)>,
+    profile_linked: bool,
+) -> Instruction {
+    let (deposit_account, _) = find_deposit_address(program_id, depositor, deposit_seed);
+    let (new_deposit_account, _) = find_deposit_address(program_id, new_depositor, deposit_seed);
+    let data = DielemmaInstruction::RotateDepositor {
+        deposit_seed: deposit_seed.to_string(),
+    }
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*depositor, true),
+        AccountMeta::new(*new_depositor, true),
+        AccountMeta::new(deposit_account, false),
+        AccountMeta::new(new_deposit_account, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+    if profile_linked {
+        accounts.push(AccountMeta::new_readonly(find_profile_address(program_id, depositor).0, false));
+    }
+    if let Some((token_mint, token_program)) = token_accounts {
+        accounts.push(AccountMeta::new(find_vault_address(program_id, &deposit_account).0, false));
+        accounts.push(AccountMeta::new(find_vault_address(program_id, &new_deposit_account).0, false));
+        accounts.push(AccountMeta::new(*token_mint, false));
+        accounts.push(AccountMeta::new_readonly(*token_program, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Create a `MigrateDeposit` instruction converting `depositor`'s legacy deposit, paid for by `payer`
+pub fn migrate_deposit(program_id: &Pubkey, payer: &Pubkey, depositor: &Pubkey, deposit_seed: &str) -> Instruction {
+    let (deposit_account, _) = find_deposit_address(program_id, depositor, deposit_seed);
+    let data = DielemmaInstruction::MigrateDeposit {
+        deposit_seed: deposit_seed.to_string(),
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(deposit_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Deposit settings instructions share the `[signer] depositor, [writable] deposit, [] config`
+/// account list
+fn depositor_instruction(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    deposit_seed: &str,
+    instruction: DielemmaInstruction,
+) -> Instruction {
+    let (deposit_account, _) = find_deposit_address(program_id, depositor, deposit_seed);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*depositor, true),
+            AccountMeta::new(deposit_account, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+        data: instruction.pack(),
+    }
+}
+
+/// Create an `InitializeConfig` instruction
+///
+/// `admin` must be the program's upgrade authority and pays for the config account.
+pub fn initialize_config(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    dlm_mint: &Pubkey,
+    burn_address: &Pubkey,
+    proof_of_life_cost: u64,
+) -> Instruction {
+    let data = DielemmaInstruction::InitializeConfig {
+        dlm_mint: *dlm_mint,
+        burn_address: *burn_address,
+        proof_of_life_cost,
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(find_program_data_address(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Create a `SetPaused` instruction
+pub fn set_paused(program_id: &Pubkey, admin: &Pubkey, paused: bool) -> Instruction {
+    admin_instruction(program_id, admin, DielemmaInstruction::SetPaused { paused })
+}
+
+/// Create a `SetClaimChallengePeriod` instruction
+pub fn set_claim_challenge_period(program_id: &Pubkey, admin: &Pubkey, challenge_seconds: u64) -> Instruction {
+    admin_instruction(
+        program_id,
+        admin,
+        DielemmaInstruction::SetClaimChallengePeriod { challenge_seconds },
+    )
+}
+
+/// Create an `UpdateConfig` instruction
+pub fn update_config(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    dlm_mint: &Pubkey,
+    burn_address: &Pubkey,
+    proof_of_life_cost: u64,
+) -> Instruction {
+    admin_instruction(
+        program_id,
+        admin,
+        DielemmaInstruction::UpdateConfig {
+            dlm_mint: *dlm_mint,
+            burn_address: *burn_address,
+            proof_of_life_cost,
+        },
+    )
+}
+
+/// Create a `ProposeAdmin` instruction
+pub fn propose_admin(program_id: &Pubkey, admin: &Pubkey, new_admin: &Pubkey) -> Instruction {
+    admin_instruction(program_id, admin, DielemmaInstruction::ProposeAdmin { new_admin: *new_admin })
+}
+
+/// Create an `AcceptAdmin` instruction, signed by the pending admin
+pub fn accept_admin(program_id: &Pubkey, new_admin: &Pubkey) -> Instruction {
+    admin_instruction(program_id, new_admin, DielemmaInstruction::AcceptAdmin)
+}
+
+/// Config instructions share the `[signer] admin, [writable] config` account list
+fn admin_instruction(program_id: &Pubkey, signer: &Pubkey, instruction: DielemmaInstruction) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new(find_config_address(program_id).0, false),
+        ],
+        data: instruction.pack(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_deposit_layout() {
+        let receiver = Pubkey::new_unique();
+        let data = DielemmaInstruction::Deposit {
+            deposit_seed: "seed".to_string(),
+            receiver,
+            amount: 1000,
+            timeout_seconds: 86400,
+            withdraw_locked_until: -1,
+        }
+        .pack();
+
+        assert_eq!(&data[0..4], &0u32.to_le_bytes());
+        assert_eq!(&data[4..8], &4u32.to_le_bytes());
+        assert_eq!(&data[8..12], b"seed");
+        assert_eq!(&data[12..44], receiver.as_ref());
+        assert_eq!(&data[44..52], &1000u64.to_le_bytes());
+        assert_eq!(&data[52..60], &86400u64.to_le_bytes());
+        assert_eq!(&data[60..68], &(-1i64).to_le_bytes());
+        assert_eq!(data.len(), 68);
+    }
+
+    #[test]
+    fn test_pack_seed_only_variants() {
+        let seed = "abc".to_string();
+        let cases = [
+            (1u32, DielemmaInstruction::ProofOfLife { deposit_seed: seed.clone() }),
+            (2, DielemmaInstruction::Withdraw { deposit_seed: seed.clone() }),
+            (3, DielemmaInstruction::Claim { deposit_seed: seed.clone() }),
+            (4, DielemmaInstruction::CloseAccount { deposit_seed: seed.clone() }),
+            (11, DielemmaInstruction::WithdrawSol { deposit_seed: seed.clone() }),
+            (12, DielemmaInstruction::ClaimSol { deposit_seed: seed.clone() }),
+            (18, DielemmaInstruction::InitiateClaim { deposit_seed: seed.clone() }),
+            (19, DielemmaInstruction::CancelClaim { deposit_seed: seed.clone() }),
+            (29, DielemmaInstruction::AttestDeath { deposit_seed: seed.clone() }),
+            (34, DielemmaInstruction::Renounce { deposit_seed: seed.clone() }),
+            (36, DielemmaInstruction::RotateDepositor { deposit_seed: seed.clone() }),
+            (37, DielemmaInstruction::MigrateDeposit { deposit_seed: seed.clone() }),
+        ];
+        for (discriminant, instruction) in cases {
+            let data = instruction.pack();
+            assert_eq!(&data[0..4], &discriminant.to_le_bytes());
+            assert_eq!(&data[4..8], &3u32.to_le_bytes());
+            assert_eq!(&data[8..], b"abc");
+        }
+    }
+
+    #[test]
+    fn test_pack_set_beneficiaries_layout() {
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let data = DielemmaInstruction::SetBeneficiaries {
+            deposit_seed: "s".to_string(),
+            beneficiaries: vec![(first, 7_500), (second, 2_500)],
+        }
+        .pack();
+
+        assert_eq!(&data[0..4], &13u32.to_le_bytes());
+        assert_eq!(data[9], 2);
+        assert_eq!(&data[10..42], first.as_ref());
+        assert_eq!(&data[42..44], &7_500u16.to_le_bytes());
+        assert_eq!(&data[44..76], second.as_ref());
+        assert_eq!(&data[76..78], &2_500u16.to_le_bytes());
+        assert_eq!(data.len(), 78);
+    }
+
+    #[test]
+    fn test_pack_update_receiver_layout() {
+        let (old_receiver, new_receiver) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let data = DielemmaInstruction::UpdateReceiver {
+            deposit_seed: "s".to_string(),
+            old_receiver,
+            new_receiver,
+            reset_timer: true,
+        }
+        .pack();
+
+        assert_eq!(&data[0..4], &14u32.to_le_bytes());
+        assert_eq!(&data[9..41], old_receiver.as_ref());
+        assert_eq!(&data[41..73], new_receiver.as_ref());
+        assert_eq!(data[73], 1);
+        assert_eq!(data.len(), 74);
+    }
+
+    #[test]
+    fn test_pack_update_timeout_layout() {
+        let data = DielemmaInstruction::UpdateTimeout {
+            deposit_seed: "s".to_string(),
+            timeout_seconds: 3_600,
+        }
+        .pack();
+
+        assert_eq!(&data[0..4], &15u32.to_le_bytes());
+        assert_eq!(&data[9..17], &3_600u64.to_le_bytes());
+        assert_eq!(data.len(), 17);
+    }
+
+    #[test]
+    fn test_pack_set_vesting_layout() {
+        let data = DielemmaInstruction::SetVesting {
+            deposit_seed: "s".to_string(),
+            vesting_seconds: 86_400,
+        }
+        .pack();
+
+        assert_eq!(&data[0..4], &30u32.to_le_bytes());
+        assert_eq!(&data[9..17], &86_400u64.to_le_bytes());
+        assert_eq!(data.len(), 17);
+    }
+
+    #[test]
+    fn test_pack_set_release_tranches_layout() {
+        let data = DielemmaInstruction::SetReleaseTranches {
+            deposit_seed: "s".to_string(),
+            tranches: vec![(1, 2_500), (2, 10_000)],
+        }
+        .pack();
+
+        assert_eq!(&data[0..4], &31u32.to_le_bytes());
+        assert_eq!(data[9], 2);
+        assert_eq!(&data[10..13], &[1, 0xc4, 0x09]);
+        assert_eq!(&data[13..16], &[2, 0x10, 0x27]);
+        assert_eq!(data.len(), 16);
+    }
+
+    #[test]
+    fn test_pack_set_release_date_layout() {
+        let data = DielemmaInstruction::SetReleaseDate {
+            deposit_seed: "s".to_string(),
+            release_at: 1_700_000_000,
+            release_at_only: true,
+        }
+        .pack();
+
+        assert_eq!(&data[0..4], &32u32.to_le_bytes());
+        assert_eq!(&data[9..17], &1_700_000_000i64.to_le_bytes());
+        assert_eq!(data[17], 1);
+        assert_eq!(data.len(), 18);
+    }
+
+    #[test]
+    fn test_pack_set_fallback_chain_layout() {
+        let fallback = Pubkey::new_unique();
+        let data = DielemmaInstruction::SetFallbackChain {
+            deposit_seed: "s".to_string(),
+            fallbacks: vec![(fallback, 86_400)],
+        }
+        .pack();
+
+        assert_eq!(&data[0..4], &35u32.to_le_bytes());
+        assert_eq!(data[9], 1);
+        assert_eq!(&data[10..42], fallback.as_ref());
+        assert_eq!(&data[42..50], &86_400u64.to_le_bytes());
+        assert_eq!(data.len(), 50);
+    }
+
+    #[test]
+    fn test_pack_top_up_layout() {
+        let data = DielemmaInstruction::TopUp {
+            deposit_seed: "s".to_string(),
+            amount: 42,
+        }
+        .pack();
+
+        assert_eq!(&data[0..4], &16u32.to_le_bytes());
+        assert_eq!(&data[9..17], &42u64.to_le_bytes());
+        assert_eq!(data.len(), 17);
+    }
+
+    #[test]
+    fn test_pack_partial_withdraw_layout() {
+        let data = DielemmaInstruction::PartialWithdraw {
+            deposit_seed: "s".to_string(),
+            amount: 42,
+        }
+        .pack();
+
+        assert_eq!(&data[0..4], &17u32.to_le_bytes());
+        assert_eq!(&data[9..17], &42u64.to_le_bytes());
+        assert_eq!(data.len(), 17);
+    }
+
+    #[test]
+    fn test_pack_add_delegate_layout() {
+        let delegate = Pubkey::new_unique();
+        let data = DielemmaInstruction::AddDelegate {
+            deposit_seed: "s".to_string(),
+            delegate,
+            expires_at: -1,
+        }
+        .pack();
+
+        assert_eq!(&data[0..4], &21u32.to_le_bytes());
+        assert_eq!(&data[9..41], delegate.as_ref());
+        assert_eq!(&data[41..49], &(-1i64).to_le_bytes());
+        assert_eq!(data.len(), 49);
+    }
+
+    #[test]
+    fn test_pack_batch_proof_of_life_layout() {
+        let data = DielemmaInstruction::BatchProofOfLife {
+            deposit_seeds: vec!["a".to_string(), "bc".to_string()],
+        }
+        .pack();
+
+        assert_eq!(&data[0..4], &24u32.to_le_bytes());
+        assert_eq!(data[4], 2);
+        assert_eq!(&data[5..9], &1u32.to_le_bytes());
+        assert_eq!(&data[9..10], b"a");
+        assert_eq!(&data[10..14], &2u32.to_le_bytes());
+        assert_eq!(&data[14..16], b"bc");
+        assert_eq!(data.len(), 16);
+    }
+
+    #[test]
+    fn test_pack_set_guardians_layout() {
+        let guardian = Pubkey::new_unique();
+        let data = DielemmaInstruction::SetGuardians {
+            deposit_seed: "s".to_string(),
+            threshold: 1,
+            guardians: vec![guardian],
+        }
+        .pack();
+
+        assert_eq!(&data[0..4], &28u32.to_le_bytes());
+        assert_eq!(data[9], 1);
+        assert_eq!(data[10], 1);
+        assert_eq!(&data[11..43], guardian.as_ref());
+        assert_eq!(data.len(), 43);
+    }
+
+    #[test]
+    fn test_pack_set_profile_link_layout() {
+        let data = DielemmaInstruction::SetProfileLink {
+            deposit_seed: "s".to_string(),
+            linked: true,
+        }
+        .pack();
+
+        assert_eq!(&data[0..4], &26u32.to_le_bytes());
+        assert_eq!(&data[4..9], &[1, 0, 0, 0, b's']);
+        assert_eq!(data[9], 1);
+        assert_eq!(data.len(), 10);
+        assert_eq!(DielemmaInstruction::CreateProfile.pack(), 25u32.to_le_bytes());
+        assert_eq!(DielemmaInstruction::ProfileProofOfLife.pack(), 27u32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_heartbeat_message_layout() {
+        let program_id = crate::id();
+        let deposit = Pubkey::new_unique();
+        let message = heartbeat_message(&program_id, &deposit, 7, -1);
+        let prefix_len = HEARTBEAT_MESSAGE_PREFIX.len();
+
+        assert_eq!(&message[..prefix_len], HEARTBEAT_MESSAGE_PREFIX);
+        assert_eq!(&message[prefix_len..prefix_len + 32], program_id.as_ref());
+        assert_eq!(&message[prefix_len + 32..prefix_len + 64], deposit.as_ref());
+        assert_eq!(&message[prefix_len + 64..prefix_len + 72], &7u64.to_le_bytes());
+        assert_eq!(&message[prefix_len + 72..], &(-1i64).to_le_bytes());
+    }
+
+    #[test]
+    fn test_vault_derived_from_deposit() {
+        let program_id = crate::id();
+        let depositor = Pubkey::new_unique();
+        let (deposit, _) = find_deposit_address(&program_id, &depositor, "seed");
+        let (vault, _) = find_vault_address(&program_id, &deposit);
+        let mint = Pubkey::new_unique();
+        let ix = withdraw(&program_id, &depositor, &Pubkey::new_unique(), &mint, &spl_token::id(), "seed");
+
+        assert_eq!(ix.accounts[1].pubkey, deposit);
+        assert_eq!(ix.accounts[3].pubkey, vault);
+        assert!(ix.accounts[0].is_signer);
+        assert_eq!(ix.accounts[6].pubkey, mint);
+    }
+}
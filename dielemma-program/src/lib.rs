@@ -6,32 +6,51 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
-    entrypoint,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    ed25519_program, entrypoint,
     entrypoint::ProgramResult,
+    instruction::AccountMeta,
     msg,
-    program::{invoke, invoke_signed},
+    program::{get_return_data, invoke, invoke_signed},
     program_error::ProgramError,
-    program_pack::Pack,
+    program_utils::limited_deserialize,
     pubkey::Pubkey,
     system_instruction,
     system_program,
-    sysvar::{clock::Clock, rent::Rent, Sysvar, SysvarId},
+    sysvar::{
+        clock::Clock,
+        instructions::{load_current_index_checked, load_instruction_at_checked},
+        rent::Rent,
+        Sysvar, SysvarId,
+    },
 };
-use spl_token::{
-    instruction::{initialize_account, transfer},
-    state::Account as TokenAccount,
+use spl_token_2022::{
+    extension::{
+        transfer_fee::{instruction::harvest_withheld_tokens_to_mint, TransferFeeAmount},
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    instruction::{close_account, get_account_data_size, initialize_account3, transfer_checked},
+    state::{Account as TokenAccount, Mint},
 };
-use spl_token_2022::instruction::transfer_checked;
+
+pub mod error;
+pub mod instruction;
+
+pub use error::DielemmaError;
 
 // Declare program ID
 solana_program::declare_id!("E7Qo7Hwp6dW9Ebc7LgdpzGJtzxLFNQCb6FmaKf3qnSRv");
 
-/// Burn address for official tokens (system program is used as burn target)
+/// Default burn address for official tokens (the incinerator)
+/// The active burn address is stored in the program config
 pub const BURN_ADDRESS: &str = "1nc1nerator11111111111111111111111111111111";
 
-/// Official DLM token mint address (hardcoded)
+/// Mainnet DLM token mint address (Token-2022)
+/// The active DLM mint is stored in the program config, so devnet can use its own mint
 pub const OFFICIAL_DLM_TOKEN_MINT: &str = "dVA6zfXBRieUCPS8GR4hve5ugmp5naPvKGFquUDpump";
-// pub const OFFICIAL_DLM_TOKEN_MINT: &str = "EvU5rAr3oSuvaekL3Y1vhGs5iQwwrxUjaZhzupn2RY4F"; //devnet Token-2022
+
+/// Default proof-of-life cost: 1 DLM token (6 decimals)
+pub const DEFAULT_PROOF_OF_LIFE_COST: u64 = 1_000_000;
 
 /// Associated Token Program ID for deriving ATAs
 pub const SPL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
@@ -49,49 +68,64 @@ pub enum DielemmaInstruction {
     /// 5. [] Token program
     /// 6. [] System program
     /// 7. [] Rent sysvar
+    /// 8. [] Config account (PDA)
+    /// 9. [] Extra accounts required by token mint extensions, if any (transfer hooks)
     Deposit {
         /// Unique deposit seed (client-generated)
         deposit_seed: String,
         /// Receiver who can claim if proof-of-life expires
         receiver: Pubkey,
-        /// Amount of tokens to deposit (in smallest unit)
+        /// Amount of tokens to deposit (in smallest unit, before transfer fees)
         amount: u64,
         /// Timeout period in seconds (e.g., 86400 = 1 day)
         timeout_seconds: u64,
+        /// Unix timestamp until which the depositor cannot withdraw (0 = never locked,
+        /// `WITHDRAW_LOCKED_FOREVER` = irrevocable); optional, omitted means 0
+        withdraw_locked_until: i64,
     },
 
-    /// Proof of life by burning 1 DLM token to reset timeout
+    /// Proof of life by burning DLM tokens (the configured cost) to reset timeout
     /// Accounts:
-    /// 0. [signer] Depositor
+    /// 0. [signer] Depositor, or an active delegate registered with AddDelegate
     /// 1. [writable] Deposit account (PDA)
-    /// 2. [writable] Depositor's DLM token account (ATA)
+    /// 2. [writable] Signer's DLM token account (ATA)
     /// 3. [writable] Burn address's DLM token account (ATA)
     /// 4. [] DLM Token mint
     /// 5. [] Token-2022 program
+    /// 6. [] Config account (PDA)
+    /// 7. [] Extra accounts required by DLM mint extensions, if any (transfer hooks)
     ProofOfLife {
         /// Deposit account seed (unique identifier)
         deposit_seed: String,
     },
 
-    /// Withdraw deposited tokens (depositor can always withdraw)
+    /// Withdraw deposited tokens (depositor only, once any withdrawal lock has passed)
     /// Accounts:
     /// 0. [signer] Depositor
     /// 1. [writable] Deposit account (PDA)
     /// 2. [writable] Depositor's token account
     /// 3. [writable] Deposit token account (PDA)
     /// 4. [] Token program
+    /// 5. [] Config account (PDA)
+    /// 6. [writable] Token mint; may be read-only, but then fees withheld on the vault stay there
+    /// 7. [] Extra accounts required by token mint extensions, if any (transfer hooks)
     Withdraw {
         /// Deposit account seed (unique identifier)
         deposit_seed: String,
     },
 
-    /// Claim tokens if proof-of-life has expired (receiver only)
+    /// Claim tokens once proof-of-life has expired and an initiated claim has passed its
+    /// challenge period, i.e. finalize the claim (receiver only)
     /// Accounts:
     /// 0. [signer] Receiver
     /// 1. [writable] Deposit account (PDA)
     /// 2. [writable] Receiver's token account
     /// 3. [writable] Deposit token account (PDA)
     /// 4. [] Token program
+    /// 5. [] Config account (PDA)
+    /// 6. [writable] Token mint; may be read-only, but then fees withheld on the vault stay there
+    /// 7. [] Depositor's liveness profile (PDA), only if the deposit is linked to it
+    /// 8. [] Extra accounts required by token mint extensions, if any (transfer hooks)
     Claim {
         /// Deposit account seed (unique identifier)
         deposit_seed: String,
@@ -107,336 +141,3580 @@ pub enum DielemmaInstruction {
         /// Deposit account seed (unique identifier)
         deposit_seed: String,
     },
-}
 
-/// Maximum length of deposit seed string
-pub const MAX_DEPOSIT_SEED_LENGTH: usize = 32;
+    /// Create the global config account (once, by the program upgrade authority)
+    /// Accounts:
+    /// 0. [signer, writable] Admin/Payer (program upgrade authority)
+    /// 1. [writable] Config account (PDA)
+    /// 2. [] Program data account of this program
+    /// 3. [] System program
+    InitializeConfig {
+        /// DLM mint burned by proof-of-life
+        dlm_mint: Pubkey,
+        /// Wallet whose DLM ATA receives the burned tokens
+        burn_address: Pubkey,
+        /// Amount of DLM (in smallest unit) burned per proof-of-life
+        proof_of_life_cost: u64,
+    },
 
-/// Deposit account state stored on-chain
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
-pub struct DepositAccount {
-    /// Depositor's public key
-    pub depositor: Pubkey,
-    /// Receiver who can claim if proof-of-life expires
-    pub receiver: Pubkey,
-    /// Token mint address
-    pub token_mint: Pubkey,
-    /// Amount of tokens deposited
-    pub amount: u64,
-    /// Last proof-of-life timestamp (unix timestamp)
-    pub last_proof_timestamp: i64,
-    /// Timeout period in seconds
-    pub timeout_seconds: u64,
-    /// Bump seed for PDA
-    pub bump: u8,
-    /// Whether tokens have been withdrawn/claimed
-    pub is_closed: bool,
-    /// Length of deposit_seed
-    pub deposit_seed_len: u32,
-    /// Deposit seed used to derive this account's PDA (fixed-size array)
-    pub deposit_seed: [u8; MAX_DEPOSIT_SEED_LENGTH],
-}
+    /// Pause or unpause Deposit, ProofOfLife, Withdraw and Claim (admin only)
+    /// Accounts:
+    /// 0. [signer] Admin
+    /// 1. [writable] Config account (PDA)
+    SetPaused {
+        /// New paused state
+        paused: bool,
+    },
 
-/// Calculate the size needed for a DepositAccount
-/// 32 (depositor) + 32 (receiver) + 32 (token_mint) + 8 (amount) + 8 (last_proof_timestamp) +
-/// 8 (timeout_seconds) + 1 (bump) + 1 (is_closed) + 4 (seed length) + 32 (seed data)
-/// = 158 bytes
-pub const DEPOSIT_ACCOUNT_SIZE: usize = 32 + 32 + 32 + 8 + 8 + 8 + 1 + 1 + 4 + MAX_DEPOSIT_SEED_LENGTH;
+    /// Update the DLM mint, burn address and proof-of-life cost (admin only)
+    /// Accounts:
+    /// 0. [signer] Admin
+    /// 1. [writable] Config account (PDA)
+    UpdateConfig {
+        /// DLM mint burned by proof-of-life
+        dlm_mint: Pubkey,
+        /// Wallet whose DLM ATA receives the burned tokens
+        burn_address: Pubkey,
+        /// Amount of DLM (in smallest unit) burned per proof-of-life
+        proof_of_life_cost: u64,
+    },
 
-// Derive PDA seeds
-pub const DEPOSIT_SEED_PREFIX: &[u8] = b"deposit";
-pub const TOKEN_ACCOUNT_SEED_PREFIX: &[u8] = b"token_account";
+    /// Propose a new admin; takes effect once the new admin accepts (admin only)
+    /// Accounts:
+    /// 0. [signer] Admin
+    /// 1. [writable] Config account (PDA)
+    ProposeAdmin {
+        /// Proposed admin
+        new_admin: Pubkey,
+    },
 
-// Entry point for the Dielemma program
-entrypoint!(process_instruction);
+    /// Accept a pending admin handover
+    /// Accounts:
+    /// 0. [signer] Pending admin
+    /// 1. [writable] Config account (PDA)
+    AcceptAdmin,
 
-pub fn process_instruction(
-    program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    instruction_data: &[u8],
-) -> ProgramResult {
-    // Manually parse instruction to avoid stack overflow with Borsh + String
-    // Instruction format: discriminant (4 bytes) + data
-    if instruction_data.len() < 4 {
-        return Err(ProgramError::InvalidInstructionData);
-    }
+    /// Deposit native SOL, held as lamports by the deposit account itself
+    /// Accounts:
+    /// 0. [signer, writable] Depositor/Payer
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [] System program
+    /// 3. [] Config account (PDA)
+    DepositSol {
+        /// Unique deposit seed (client-generated)
+        deposit_seed: String,
+        /// Receiver who can claim if proof-of-life expires
+        receiver: Pubkey,
+        /// Amount of lamports to deposit
+        amount: u64,
+        /// Timeout period in seconds (e.g., 86400 = 1 day)
+        timeout_seconds: u64,
+        /// Unix timestamp until which the depositor cannot withdraw (0 = never locked,
+        /// `WITHDRAW_LOCKED_FOREVER` = irrevocable); optional, omitted means 0
+        withdraw_locked_until: i64,
+    },
 
-    let discriminant = u32::from_le_bytes(
-        instruction_data[0..4]
-            .try_into()
-            .map_err(|_| ProgramError::InvalidInstructionData)?
-    );
+    /// Withdraw a native SOL deposit back to the depositor, once any withdrawal lock has passed
+    /// Accounts:
+    /// 0. [signer, writable] Depositor
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [] Config account (PDA)
+    WithdrawSol {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+    },
 
-    match discriminant {
-        0 => {
-            // Deposit instruction
-            let data = &instruction_data[4..];
-            let offset = &mut 0;
+    /// Claim a native SOL deposit, or a wSOL deposit unwrapped to SOL, once proof-of-life
+    /// has expired and an initiated claim has passed its challenge period (receiver only)
+    /// Accounts:
+    /// 0. [signer, writable] Receiver
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [] Config account (PDA)
+    /// 3. [] Depositor's liveness profile (PDA), only if the deposit is linked to it
+    /// 4. [writable] Deposit token account (PDA), wSOL deposits only
+    /// 5. [] Token program, wSOL deposits only
+    ClaimSol {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+    },
 
-            // Parse deposit_seed (length-prefixed string) with bounds checking
-            if data.len() < 4 {
-                return Err(ProgramError::InvalidInstructionData);
-            }
-            let seed_len = u32::from_le_bytes(data[*offset..*offset + 4]
-                .try_into().unwrap()) as usize;
-            *offset += 4;
-            if seed_len > MAX_DEPOSIT_SEED_LENGTH || *offset + seed_len > data.len() {
-                msg!("Invalid deposit seed length");
-                return Err(ProgramError::InvalidInstructionData);
-            }
-            let deposit_seed_bytes = &data[*offset..*offset + seed_len];
-            let deposit_seed = std::str::from_utf8(deposit_seed_bytes)
-                .map_err(|_| ProgramError::InvalidInstructionData)?;
-
-            // Additional validation: ensure byte length is within bounds after UTF-8 conversion
-            if deposit_seed_bytes.len() > MAX_DEPOSIT_SEED_LENGTH {
-                msg!("Deposit seed bytes exceed maximum length");
-                return Err(ProgramError::InvalidInstructionData);
-            }
-            *offset += seed_len;
+    /// Split a deposit between several receivers (depositor only, before any share is claimed)
+    /// Accounts:
+    /// 0. [signer] Depositor
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [] Config account (PDA)
+    SetBeneficiaries {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+        /// Receivers and their shares in basis points, adding up to `TOTAL_SHARE_BPS`
+        beneficiaries: Vec<(Pubkey, u16)>,
+    },
 
-            // Verify remaining data has enough bytes for receiver (32) + amount (8) + timeout (8) = 48
-            if *offset + 48 > data.len() {
-                msg!("Invalid instruction data: insufficient bytes");
-                return Err(ProgramError::InvalidInstructionData);
-            }
+    /// Replace a receiver of an active deposit, keeping their share (depositor only)
+    /// Accounts:
+    /// 0. [signer] Depositor
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [] Config account (PDA)
+    UpdateReceiver {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+        /// Receiver to replace
+        old_receiver: Pubkey,
+        /// Receiver taking over the share
+        new_receiver: Pubkey,
+        /// Also restart the proof-of-life timer
+        reset_timer: bool,
+    },
 
-            // Parse receiver (32 bytes)
-            let receiver_bytes = &data[*offset..*offset + 32];
-            *offset += 32;
-            let receiver = Pubkey::try_from(receiver_bytes)
-                .map_err(|_| ProgramError::InvalidInstructionData)?;
+    /// Change the proof-of-life timeout (depositor only)
+    /// Increases apply immediately; reductions apply once the current timeout has elapsed.
+    /// Rejected once proof of life has expired or a claim is pending.
+    /// Accounts:
+    /// 0. [signer] Depositor
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [] Config account (PDA)
+    /// 3. [] Depositor's liveness profile (PDA), only if the deposit is linked to it
+    UpdateTimeout {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+        /// New timeout period in seconds
+        timeout_seconds: u64,
+    },
 
-            // Parse amount (u64)
-            let amount = u64::from_le_bytes(data[*offset..*offset + 8]
-                .try_into().unwrap());
-            *offset += 8;
+    /// Add tokens of the deposit's mint to an active deposit (depositor only)
+    /// Accounts:
+    /// 0. [signer] Depositor
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [writable] Token account (owned by depositor)
+    /// 3. [writable] Deposit token account (PDA, holds deposited tokens)
+    /// 4. [] Token mint
+    /// 5. [] Token program
+    /// 6. [] Config account (PDA)
+    /// 7. [] Extra accounts required by token mint extensions, if any (transfer hooks)
+    TopUp {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+        /// Amount of tokens to add
+        amount: u64,
+    },
 
-            // Parse timeout_seconds (u64)
-            let timeout_seconds = u64::from_le_bytes(data[*offset..*offset + 8]
-                .try_into().unwrap());
+    /// Withdraw part of a deposit, keeping it active (depositor only, once any withdrawal lock has passed)
+    /// Accounts:
+    /// 0. [signer] Depositor
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [writable] Token account (owned by depositor)
+    /// 3. [writable] Deposit token account (PDA, holds deposited tokens)
+    /// 4. [] Token program
+    /// 5. [] Config account (PDA)
+    /// 6. [] Token mint
+    /// 7. [] Extra accounts required by token mint extensions, if any (transfer hooks)
+    PartialWithdraw {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+        /// Amount of tokens to withdraw, less than the deposit's balance
+        amount: u64,
+    },
 
-            process_deposit(program_id, accounts, deposit_seed, &receiver, amount, timeout_seconds)
-        }
-        1 => {
-            // ProofOfLife instruction
-            let data = &instruction_data[4..];
-            let offset = &mut 0;
+    /// Start a claim on an expired deposit, opening the challenge period (receiver only)
+    /// Accounts:
+    /// 0. [signer] Receiver
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [] Config account (PDA)
+    /// 3. [] Depositor's liveness profile (PDA), only if the deposit is linked to it
+    InitiateClaim {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+    },
 
-            if data.len() < 4 {
-                return Err(ProgramError::InvalidInstructionData);
-            }
-            let seed_len = u32::from_le_bytes(data[*offset..*offset + 4]
-                .try_into().unwrap()) as usize;
-            *offset += 4;
-            if seed_len > MAX_DEPOSIT_SEED_LENGTH || *offset + seed_len > data.len() {
-                msg!("Invalid deposit seed length");
-                return Err(ProgramError::InvalidInstructionData);
-            }
-            let deposit_seed_bytes = &data[*offset..*offset + seed_len];
-            let deposit_seed = std::str::from_utf8(deposit_seed_bytes)
-                .map_err(|_| ProgramError::InvalidInstructionData)?;
-
-            // Additional validation: ensure byte length is within bounds after UTF-8 conversion
-            if deposit_seed_bytes.len() > MAX_DEPOSIT_SEED_LENGTH {
-                msg!("Deposit seed bytes exceed maximum length");
-                return Err(ProgramError::InvalidInstructionData);
-            }
+    /// Void an initiated claim (depositor only, allowed while paused, not while withdraw-locked)
+    /// Accounts:
+    /// 0. [signer] Depositor
+    /// 1. [writable] Deposit account (PDA)
+    CancelClaim {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+    },
 
-            process_proof_of_life(program_id, accounts, deposit_seed)
-        }
-        2 => {
-            // Withdraw instruction
-            let data = &instruction_data[4..];
-            let offset = &mut 0;
+    /// Set the challenge period of newly initiated claims (admin only)
+    /// Accounts:
+    /// 0. [signer] Admin
+    /// 1. [writable] Config account (PDA)
+    SetClaimChallengePeriod {
+        /// Challenge period in seconds, at most `MAX_CLAIM_CHALLENGE_SECONDS`
+        challenge_seconds: u64,
+    },
 
-            if data.len() < 4 {
-                return Err(ProgramError::InvalidInstructionData);
-            }
-            let seed_len = u32::from_le_bytes(data[*offset..*offset + 4]
-                .try_into().unwrap()) as usize;
-            *offset += 4;
-            if seed_len > MAX_DEPOSIT_SEED_LENGTH || *offset + seed_len > data.len() {
-                msg!("Invalid deposit seed length");
-                return Err(ProgramError::InvalidInstructionData);
-            }
-            let deposit_seed_bytes = &data[*offset..*offset + seed_len];
-            let deposit_seed = std::str::from_utf8(deposit_seed_bytes)
-                .map_err(|_| ProgramError::InvalidInstructionData)?;
-
-            // Additional validation: ensure byte length is within bounds after UTF-8 conversion
-            if deposit_seed_bytes.len() > MAX_DEPOSIT_SEED_LENGTH {
-                msg!("Deposit seed bytes exceed maximum length");
-                return Err(ProgramError::InvalidInstructionData);
-            }
+    /// Register a delegate key allowed to submit ProofOfLife, or change its expiry (depositor only)
+    /// Accounts:
+    /// 0. [signer] Depositor
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [] Config account (PDA)
+    AddDelegate {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+        /// Heartbeat key
+        delegate: Pubkey,
+        /// Unix timestamp after which the delegate can no longer prove life (0 = never)
+        expires_at: i64,
+    },
 
-            process_withdraw(program_id, accounts, deposit_seed)
-        }
-        3 => {
-            // Claim instruction
-            let data = &instruction_data[4..];
-            let offset = &mut 0;
+    /// Remove a delegate key (depositor only)
+    /// Accounts:
+    /// 0. [signer] Depositor
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [] Config account (PDA)
+    RevokeDelegate {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+        /// Heartbeat key to remove
+        delegate: Pubkey,
+    },
 
-            if data.len() < 4 {
-                return Err(ProgramError::InvalidInstructionData);
-            }
-            let seed_len = u32::from_le_bytes(data[*offset..*offset + 4]
-                .try_into().unwrap()) as usize;
-            *offset += 4;
-            if seed_len > MAX_DEPOSIT_SEED_LENGTH || *offset + seed_len > data.len() {
-                msg!("Invalid deposit seed length");
-                return Err(ProgramError::InvalidInstructionData);
-            }
-            let deposit_seed_bytes = &data[*offset..*offset + seed_len];
-            let deposit_seed = std::str::from_utf8(deposit_seed_bytes)
-                .map_err(|_| ProgramError::InvalidInstructionData)?;
-
-            // Additional validation: ensure byte length is within bounds after UTF-8 conversion
-            if deposit_seed_bytes.len() > MAX_DEPOSIT_SEED_LENGTH {
-                msg!("Deposit seed bytes exceed maximum length");
-                return Err(ProgramError::InvalidInstructionData);
-            }
+    /// Proof of life from a heartbeat message signed off-chain by the depositor and
+    /// submitted by any relayer, who pays the DLM cost
+    ///
+    /// Must directly follow an ed25519 program instruction verifying the depositor's
+    /// signature over `instruction::heartbeat_message(program_id, deposit, nonce, expires_at)`.
+    /// Accounts:
+    /// 0. [signer] Relayer
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [writable] Relayer's DLM token account (ATA)
+    /// 3. [writable] Burn address's DLM token account (ATA)
+    /// 4. [] DLM Token mint
+    /// 5. [] Token-2022 program
+    /// 6. [] Config account (PDA)
+    /// 7. [] Instructions sysvar
+    /// 8. [] Extra accounts required by DLM mint extensions, if any (transfer hooks)
+    SignedProofOfLife {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+        /// Must be greater than the deposit's last used heartbeat nonce
+        nonce: u64,
+        /// Unix timestamp after which the heartbeat is rejected
+        expires_at: i64,
+    },
 
-            process_claim(program_id, accounts, deposit_seed)
-        }
-        4 => {
-            // CloseAccount instruction
-            let data = &instruction_data[4..];
-            let offset = &mut 0;
+    /// Record proof of life on several deposits of the same depositor at once,
+    /// paying the DLM cost a single time
+    /// Accounts:
+    /// 0. [signer] Depositor
+    /// 1. [writable] Depositor's DLM token account (ATA)
+    /// 2. [writable] Burn address's DLM token account (ATA)
+    /// 3. [] DLM Token mint
+    /// 4. [] Token-2022 program
+    /// 5. [] Config account (PDA)
+    /// 6. [writable] One deposit account (PDA) per seed, in the same order
+    /// 7. [] Extra accounts required by DLM mint extensions, if any (transfer hooks)
+    BatchProofOfLife {
+        /// Seeds of the deposits to refresh
+        deposit_seeds: Vec<String>,
+    },
 
-            if data.len() < 4 {
-                return Err(ProgramError::InvalidInstructionData);
-            }
-            let seed_len = u32::from_le_bytes(data[*offset..*offset + 4]
-                .try_into().unwrap()) as usize;
-            *offset += 4;
-            if seed_len > MAX_DEPOSIT_SEED_LENGTH || *offset + seed_len > data.len() {
-                msg!("Invalid deposit seed length");
-                return Err(ProgramError::InvalidInstructionData);
-            }
-            let deposit_seed_bytes = &data[*offset..*offset + seed_len];
-            let deposit_seed = std::str::from_utf8(deposit_seed_bytes)
-                .map_err(|_| ProgramError::InvalidInstructionData)?;
-
-            // Additional validation: ensure byte length is within bounds after UTF-8 conversion
-            if deposit_seed_bytes.len() > MAX_DEPOSIT_SEED_LENGTH {
-                msg!("Deposit seed bytes exceed maximum length");
-                return Err(ProgramError::InvalidInstructionData);
-            }
+    /// Create the depositor's liveness profile, whose proof of life keeps every linked deposit alive
+    /// Accounts:
+    /// 0. [signer, writable] Depositor/Payer
+    /// 1. [writable] Liveness profile account (PDA)
+    /// 2. [] System program
+    /// 3. [] Config account (PDA)
+    CreateProfile,
 
-            process_close_account(program_id, accounts, deposit_seed)
-        }
-        _ => Err(ProgramError::InvalidInstructionData),
-    }
-}
+    /// Link a deposit to the depositor's liveness profile, or unlink it (depositor only)
+    /// Accounts:
+    /// 0. [signer] Depositor
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [] Config account (PDA)
+    /// 3. [] Liveness profile account (PDA)
+    SetProfileLink {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+        /// Whether the deposit follows the profile's proof of life
+        linked: bool,
+    },
 
-/// Process deposit instruction
-fn process_deposit(
-    program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    deposit_seed: &str,  // Use reference to avoid copying
-    receiver: &Pubkey,   // Use reference to avoid copying
-    amount: u64,
-    timeout_seconds: u64,
-) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
+    /// Proof of life on the depositor's liveness profile, burning the configured DLM cost once
+    /// for all linked deposits
+    /// Accounts:
+    /// 0. [signer] Depositor
+    /// 1. [writable] Liveness profile account (PDA)
+    /// 2. [writable] Depositor's DLM token account (ATA)
+    /// 3. [writable] Burn address's DLM token account (ATA)
+    /// 4. [] DLM Token mint
+    /// 5. [] Token-2022 program
+    /// 6. [] Config account (PDA)
+    /// 7. [] Extra accounts required by DLM mint extensions, if any (transfer hooks)
+    ProfileProofOfLife,
 
-    let depositor = next_account_info(account_info_iter)?;
-    let deposit_account = next_account_info(account_info_iter)?;
-    let depositor_token_account = next_account_info(account_info_iter)?;
-    let deposit_token_account = next_account_info(account_info_iter)?;
-    let token_mint = next_account_info(account_info_iter)?;
-    let token_program = next_account_info(account_info_iter)?;
-    let system_program = next_account_info(account_info_iter)?;
-    let rent_account = next_account_info(account_info_iter)?;
+    /// Register the guardians who can together confirm the depositor's death (depositor only)
+    /// An empty guardian list with a threshold of 0 removes them.
+    /// Accounts:
+    /// 0. [signer] Depositor
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [] Config account (PDA)
+    SetGuardians {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+        /// Number of guardian attestations confirming death
+        threshold: u8,
+        /// Guardian keys, at most `MAX_GUARDIANS`
+        guardians: Vec<Pubkey>,
+    },
 
-    // Verify depositor is signer
-    if !depositor.is_signer {
-        msg!("Depositor must sign the transaction");
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    /// Attest the depositor's death (guardian only)
+    /// Once `threshold` guardians have attested, the deposit becomes claimable after
+    /// `GUARDIAN_GRACE_SECONDS` unless the depositor proves life first.
+    /// Accounts:
+    /// 0. [signer] Guardian
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [] Config account (PDA)
+    /// 3. [] Depositor's liveness profile (PDA), only if the deposit is linked to it
+    AttestDeath {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+    },
 
-    // Verify system program
-    if system_program.key != &system_program::id() {
-        msg!("Invalid system program");
-        return Err(ProgramError::IncorrectProgramId);
-    }
+    /// Pay shares out linearly over time once claims start (depositor only)
+    /// Receivers may claim repeatedly, each time receiving what vested since expiry.
+    /// A duration of 0 pays shares out at once. Fixed once a claim has been made.
+    /// Accounts:
+    /// 0. [signer] Depositor
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [] Config account (PDA)
+    SetVesting {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+        /// Seconds over which shares vest, at most `MAX_VESTING_SECONDS`
+        vesting_seconds: u64,
+    },
+
+    /// Release shares in tranches as the deposit goes unproven for several timeouts (depositor only)
+    /// An empty list releases everything at expiry. Fixed once a claim has been made.
+    /// Accounts:
+    /// 0. [signer] Depositor
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [] Config account (PDA)
+    SetReleaseTranches {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+        /// `(timeout_multiple, cumulative unlocked_bps)` pairs, at most `MAX_RELEASE_TRANCHES`
+        tranches: Vec<(u8, u16)>,
+    },
+
+    /// Let receivers claim from a fixed date regardless of liveness (depositor only)
+    /// Unless `release_at_only` is set, proof-of-life expiry also unlocks claims, whichever
+    /// comes first. A date of 0 removes it. Fixed once a claim has been made.
+    /// Accounts:
+    /// 0. [signer] Depositor
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [] Config account (PDA)
+    SetReleaseDate {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+        /// Unix timestamp from which receivers may claim (0 = none)
+        release_at: i64,
+        /// Whether proof-of-life expiry no longer unlocks claims
+        release_at_only: bool,
+    },
+
+    /// Designate who takes over the share of a beneficiary who renounces (depositor only)
+    /// The default pubkey removes the fallback, leaving renounced shares to the depositor.
+    /// Accounts:
+    /// 0. [signer] Depositor
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [] Config account (PDA)
+    SetFallbackReceiver {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+        /// Fallback receiver (default pubkey for none)
+        fallback_receiver: Pubkey,
+    },
+
+    /// Give up the signer's claim on a deposit (receiver only)
+    /// The share passes to the fallback receiver if one is set and not already a
+    /// beneficiary, otherwise it stays with the depositor. While the deposit is
+    /// withdraw-locked, it is instead split among the receivers not yet paid in full.
+    /// Accounts:
+    /// 0. [signer] Receiver
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [] Config account (PDA)
+    Renounce {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+    },
+
+    /// Set the ordered fallback receivers who may claim in turn if nobody earlier has (depositor only)
+    /// Each may claim the whole deposit once their grace period after it became claimable has
+    /// passed without a claim, closing the windows of everyone before them. Fixed once a
+    /// claim has been made.
+    /// Accounts:
+    /// 0. [signer] Depositor
+    /// 1. [writable] Deposit account (PDA)
+    /// 2. [] Config account (PDA)
+    SetFallbackChain {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+        /// `(receiver, grace_seconds)` pairs, at most `MAX_FALLBACK_RECEIVERS`
+        fallbacks: Vec<(Pubkey, u64)>,
+    },
+
+    /// Move a deposit to the same seed under a new depositor key (both keys sign)
+    /// Receivers, timeout, proof-of-life history and every other setting carry over;
+    /// the deposited tokens or lamports move to the new PDAs and the old accounts are
+    /// closed, refunding their rent to the current depositor.
+    /// Accounts:
+    /// 0. [signer, writable] Current depositor
+    /// 1. [signer, writable] New depositor (pays for the new accounts)
+    /// 2. [writable] Current deposit account (PDA)
+    /// 3. [writable] New deposit account (PDA)
+    /// 4. [] System program
+    /// 5. [] Config account (PDA)
+    /// 6. [] Current depositor's liveness profile (PDA), only if the deposit is linked to it
+    /// 7. [writable] Current deposit token account (PDA), token deposits only
+    /// 8. [writable] New deposit token account (PDA), token deposits only
+    /// 9. [writable] Token mint, token deposits only
+    /// 10. [] Token program (Token or Token-2022), token deposits only
+    /// 11. [] Extra accounts required by token mint extensions, if any (transfer hooks)
+    RotateDepositor {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+    },
+
+    /// Convert a deposit created before beneficiaries were introduced to the current layout
+    /// Anyone may migrate a deposit: the legacy receiver becomes its sole beneficiary and every
+    /// setting added since then starts out unset. The payer covers the extra rent.
+    /// Accounts:
+    /// 0. [signer, writable] Payer
+    /// 1. [writable] Deposit account (PDA) in the legacy layout
+    /// 2. [] System program
+    MigrateDeposit {
+        /// Deposit account seed (unique identifier)
+        deposit_seed: String,
+    },
+}
+
+/// Maximum length of deposit seed string
+pub const MAX_DEPOSIT_SEED_LENGTH: usize = 32;
+
+/// `withdraw_locked_until` of an irrevocable deposit
+pub const WITHDRAW_LOCKED_FOREVER: i64 = i64::MAX;
+
+/// Largest amount a deposit can hold (100 million DLM at 9 decimals)
+pub const MAX_DEPOSIT_AMOUNT: u64 = 100_000_000_000_000_000;
+
+/// Default challenge period of an initiated claim (3 days)
+pub const DEFAULT_CLAIM_CHALLENGE_SECONDS: u64 = 259_200;
+
+/// Longest allowed claim challenge period (90 days)
+pub const MAX_CLAIM_CHALLENGE_SECONDS: u64 = 7_776_000;
+
+/// Shortest allowed proof-of-life timeout (1 minute)
+pub const MIN_TIMEOUT_SECONDS: u64 = 60;
+
+/// Longest allowed proof-of-life timeout (10 years)
+pub const MAX_TIMEOUT_SECONDS: u64 = 315360000;
+
+/// Maximum number of beneficiaries sharing one deposit
+pub const MAX_BENEFICIARIES: usize = 10;
+
+/// Basis points making up a whole deposit
+pub const TOTAL_SHARE_BPS: u16 = 10_000;
+
+/// A receiver entitled to a share of a deposit once proof-of-life expires
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Beneficiary {
+    /// Receiver who can claim this share
+    pub receiver: Pubkey,
+    /// Share of the deposit in basis points
+    pub share_bps: u16,
+    /// Whether this share has been claimed in full
+    pub claimed: bool,
+    /// Amount of this share paid out so far
+    pub claimed_amount: u64,
+}
+
+/// Size of a serialized Beneficiary: 32 (receiver) + 2 (share_bps) + 1 (claimed) + 8 (claimed_amount)
+pub const BENEFICIARY_SIZE: usize = 32 + 2 + 1 + 8;
+
+/// Domain tag at the start of every signed heartbeat message, versioned with its layout
+pub const HEARTBEAT_MESSAGE_PREFIX: &[u8] = b"dielemma-heartbeat-v1";
+
+/// Maximum number of deposits refreshed by one BatchProofOfLife
+pub const MAX_BATCH_DEPOSITS: usize = 10;
+
+/// Maximum number of guardians per deposit
+pub const MAX_GUARDIANS: usize = 5;
+
+/// Time the depositor has to prove life once guardians confirm their death (7 days)
+pub const GUARDIAN_GRACE_SECONDS: u64 = 604_800;
+
+/// Longest allowed vesting duration (10 years)
+pub const MAX_VESTING_SECONDS: u64 = 315_360_000;
+
+/// Maximum number of delegate keys per deposit
+pub const MAX_DELEGATES: usize = 3;
+
+/// A key allowed to submit ProofOfLife for a deposit, but not to move its funds
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Delegate {
+    /// Heartbeat key
+    pub key: Pubkey,
+    /// Unix timestamp after which the delegate is no longer accepted (0 = never)
+    pub expires_at: i64,
+}
+
+impl Delegate {
+    /// Whether the delegate may prove life at `now`
+    pub fn is_active(&self, now: i64) -> bool {
+        self.expires_at == 0 || now < self.expires_at
+    }
+}
+
+/// Size of a serialized Delegate: 32 (key) + 8 (expires_at)
+pub const DELEGATE_SIZE: usize = 32 + 8;
+
+/// Maximum number of receivers in a deposit's fallback chain
+pub const MAX_FALLBACK_RECEIVERS: usize = 4;
+
+/// A receiver who may claim the whole deposit once the beneficiaries and earlier
+/// fallbacks have let their claim windows pass
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct FallbackReceiver {
+    /// Receiver who can claim once their window opens
+    pub receiver: Pubkey,
+    /// Seconds after the deposit became claimable at which the window opens
+    pub grace_seconds: u64,
+}
+
+/// Size of a serialized FallbackReceiver: 32 (receiver) + 8 (grace_seconds)
+pub const FALLBACK_RECEIVER_SIZE: usize = 32 + 8;
+
+/// Maximum number of release tranches per deposit
+pub const MAX_RELEASE_TRANCHES: usize = 4;
+
+/// Portion of every share unlocked once the deposit has gone unproven for a multiple of its timeout
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct ReleaseTranche {
+    /// Number of timeouts since the last proof of life after which the tranche unlocks
+    pub timeout_multiple: u8,
+    /// Cumulative basis points of each share unlocked by this tranche
+    pub unlocked_bps: u16,
+}
+
+/// Size of a serialized ReleaseTranche: 1 (timeout_multiple) + 2 (unlocked_bps)
+pub const RELEASE_TRANCHE_SIZE: usize = 1 + 2;
+
+/// Deposit account state stored on-chain
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct DepositAccount {
+    /// Depositor's public key
+    pub depositor: Pubkey,
+    /// Number of used entries in `beneficiaries`
+    pub beneficiary_count: u8,
+    /// Receivers who can claim their share if proof-of-life expires
+    pub beneficiaries: [Beneficiary; MAX_BENEFICIARIES],
+    /// Token mint address (`NATIVE_SOL_MINT` for native SOL deposits)
+    pub token_mint: Pubkey,
+    /// Amount of tokens deposited, as received by the vault (net of transfer fees)
+    pub amount: u64,
+    /// Amount already paid out to beneficiaries
+    pub claimed_amount: u64,
+    /// Last proof-of-life timestamp (unix timestamp)
+    pub last_proof_timestamp: i64,
+    /// Timeout period in seconds
+    pub timeout_seconds: u64,
+    /// Shorter timeout scheduled by `UpdateTimeout` (0 if none)
+    pub pending_timeout_seconds: u64,
+    /// When `pending_timeout_seconds` replaces `timeout_seconds` (unix timestamp)
+    pub pending_timeout_effective_at: i64,
+    /// Beneficiary who initiated the pending claim (default pubkey if none)
+    pub claim_initiator: Pubkey,
+    /// When the pending claim's challenge period ends (0 if no claim is pending)
+    pub claim_finalizable_at: i64,
+    /// Number of used entries in `delegates`
+    pub delegate_count: u8,
+    /// Keys allowed to submit ProofOfLife on the depositor's behalf
+    pub delegates: [Delegate; MAX_DELEGATES],
+    /// Nonce of the last signed heartbeat accepted by SignedProofOfLife
+    pub heartbeat_nonce: u64,
+    /// Whether proof of life recorded on the depositor's liveness profile also counts for this deposit
+    pub profile_linked: bool,
+    /// Number of used entries in `guardians`
+    pub guardian_count: u8,
+    /// Keys that can together confirm the depositor's death
+    pub guardians: [Pubkey; MAX_GUARDIANS],
+    /// Number of guardian attestations confirming death (0 if no guardians)
+    pub guardian_threshold: u8,
+    /// Bit `i` is set once `guardians[i]` has attested the depositor's death
+    pub death_attestations: u8,
+    /// When the guardian quorum was reached (0 if not reached)
+    pub death_confirmed_at: i64,
+    /// Seconds over which shares vest once claims start (0 = paid out at once)
+    pub vesting_seconds: u64,
+    /// When vesting started (0 until the first claim)
+    pub vesting_started_at: i64,
+    /// Number of used entries in `tranches` (0 = everything unlocks at expiry)
+    pub tranche_count: u8,
+    /// Release tranches, ordered by `timeout_multiple`
+    pub tranches: [ReleaseTranche; MAX_RELEASE_TRANCHES],
+    /// Date from which receivers may claim regardless of liveness (0 = none)
+    pub release_at: i64,
+    /// Whether only `release_at` unlocks claims, ignoring proof-of-life expiry
+    pub release_at_only: bool,
+    /// Unix timestamp until which the depositor cannot withdraw (0 = never locked)
+    pub withdraw_locked_until: i64,
+    /// Receiver taking over the share of a beneficiary who renounces (default pubkey if none)
+    pub fallback_receiver: Pubkey,
+    /// Number of used entries in `fallback_chain`
+    pub fallback_chain_len: u8,
+    /// Receivers who may claim in turn if nobody earlier in line has, ordered by `grace_seconds`
+    pub fallback_chain: [FallbackReceiver; MAX_FALLBACK_RECEIVERS],
+    /// Bump seed for PDA
+    pub bump: u8,
+    /// Whether tokens have been withdrawn/claimed
+    pub is_closed: bool,
+    /// Length of deposit_seed
+    pub deposit_seed_len: u32,
+    /// Deposit seed used to derive this account's PDA (fixed-size array)
+    pub deposit_seed: [u8; MAX_DEPOSIT_SEED_LENGTH],
+    /// Layout version of this account (`DEPOSIT_LAYOUT_VERSION`)
+    pub layout_version: u8,
+}
+
+impl DepositAccount {
+    /// Whether the deposit holds native SOL in the deposit account rather than tokens in a vault
+    pub fn is_native(&self) -> bool {
+        self.token_mint == NATIVE_SOL_MINT
+    }
+
+    /// The beneficiaries in use
+    pub fn beneficiaries(&self) -> &[Beneficiary] {
+        &self.beneficiaries[..self.beneficiary_count as usize]
+    }
+
+    /// Position of `receiver` in the beneficiary list
+    pub fn beneficiary_index(&self, receiver: &Pubkey) -> Option<usize> {
+        self.beneficiaries().iter().position(|b| b.receiver == *receiver)
+    }
+
+    /// Timeout in force at `now`, taking a scheduled reduction into account once it is due
+    pub fn timeout_at(&self, now: i64) -> u64 {
+        if self.pending_timeout_seconds != 0 && now >= self.pending_timeout_effective_at {
+            self.pending_timeout_seconds
+        } else {
+            self.timeout_seconds
+        }
+    }
+
+    /// Position of `key` in the delegate list
+    pub fn delegate_index(&self, key: &Pubkey) -> Option<usize> {
+        self.delegates[..self.delegate_count as usize]
+            .iter()
+            .position(|d| d.key == *key)
+    }
+
+    /// Whether proof of life has expired at `now`
+    pub fn expired(&self, now: i64) -> bool {
+        !self.release_at_only && now.saturating_sub(self.last_proof_timestamp) >= self.timeout_at(now) as i64
+    }
+
+    /// Whether the depositor is barred from withdrawing at `now`
+    pub fn withdraw_locked(&self, now: i64) -> bool {
+        now < self.withdraw_locked_until
+    }
+
+    /// Whether the release date has been reached at `now`
+    pub fn released(&self, now: i64) -> bool {
+        self.release_at != 0 && now >= self.release_at
+    }
+
+    /// Whether a beneficiary has initiated a claim that has not been cancelled
+    pub fn has_pending_claim(&self) -> bool {
+        self.claim_finalizable_at != 0
+    }
+
+    /// Restart the proof-of-life timer, voiding any pending claim and death attestations
+    ///
+    /// Payouts made so far stay counted against each share, and vesting starts over the
+    /// next time the deposit becomes claimable.
+    pub fn record_proof_of_life(&mut self, now: i64) {
+        self.last_proof_timestamp = now;
+        self.clear_pending_claim();
+        self.clear_death_attestations();
+        self.vesting_started_at = 0;
+    }
+
+    /// Position of `key` in the guardian list
+    pub fn guardian_index(&self, key: &Pubkey) -> Option<usize> {
+        self.guardians[..self.guardian_count as usize]
+            .iter()
+            .position(|guardian| guardian == key)
+    }
+
+    /// Number of guardians who have attested the depositor's death
+    pub fn attestation_count(&self) -> u8 {
+        self.death_attestations.count_ones() as u8
+    }
+
+    /// Whether the guardian quorum confirmed the depositor's death and the grace period
+    /// for proving life has passed at `now`
+    pub fn death_confirmed(&self, now: i64) -> bool {
+        self.death_confirmed_at != 0 && now >= self.death_confirmed_at.saturating_add(GUARDIAN_GRACE_SECONDS as i64)
+    }
+
+    /// Forget all death attestations
+    pub fn clear_death_attestations(&mut self) {
+        self.death_attestations = 0;
+        self.death_confirmed_at = 0;
+    }
+
+    /// Catch up with a newer proof of life recorded on the depositor's liveness profile,
+    /// voiding any claim initiated and any death attested before it
+    ///
+    /// Must run before recording a claim or attestation, which would otherwise be voided
+    /// by an older profile proof.
+    pub fn sync_profile(&mut self, profile: &ProfileAccount) {
+        if profile.last_proof_timestamp > self.last_proof_timestamp {
+            self.record_proof_of_life(profile.last_proof_timestamp);
+        }
+    }
+
+    /// Forget the pending claim, if any
+    pub fn clear_pending_claim(&mut self) {
+        self.claim_initiator = Pubkey::default();
+        self.claim_finalizable_at = 0;
+    }
+
+    /// Amount not yet paid out to beneficiaries
+    pub fn remaining_amount(&self) -> u64 {
+        self.amount.saturating_sub(self.claimed_amount)
+    }
+
+    /// Number of beneficiaries whose share has not been claimed yet
+    pub fn unclaimed_count(&self) -> usize {
+        self.beneficiaries().iter().filter(|b| !b.claimed).count()
+    }
+
+    /// Whether the beneficiaries' shares still cover the whole deposit, i.e. none was renounced
+    pub fn fully_allocated(&self) -> bool {
+        self.beneficiaries().iter().map(|b| b.share_bps as u32).sum::<u32>() == TOTAL_SHARE_BPS as u32
+    }
+
+    /// Drop the beneficiary at `index`, leaving their share to the depositor
+    pub fn remove_beneficiary(&mut self, index: usize) {
+        let count = self.beneficiary_count as usize;
+        self.beneficiaries.copy_within(index + 1..count, index);
+        self.beneficiaries[count - 1] = Beneficiary::default();
+        self.beneficiary_count -= 1;
+    }
+
+    /// Add `share_bps` to the shares of the beneficiaries not yet paid in full, in proportion
+    /// to those shares, the last of them taking the rounding remainder
+    ///
+    /// Returns false, leaving the shares unchanged, if every beneficiary has been paid.
+    pub fn spread_share(&mut self, share_bps: u16) -> bool {
+        let unclaimed = |b: &Beneficiary| !b.claimed;
+        let unclaimed_bps: u32 = self.beneficiaries().iter().filter(|b| unclaimed(b)).map(|b| b.share_bps as u32).sum();
+        let Some(last) = self.beneficiaries().iter().rposition(unclaimed) else {
+            return false;
+        };
+
+        let mut left = share_bps;
+        for (i, beneficiary) in self.beneficiaries[..=last].iter_mut().enumerate() {
+            if beneficiary.claimed {
+                continue;
+            }
+            let extra = if i == last {
+                left
+            } else {
+                (share_bps as u32 * beneficiary.share_bps as u32 / unclaimed_bps) as u16
+            };
+            beneficiary.share_bps += extra;
+            left -= extra;
+        }
+        true
+    }
+
+    /// Amount owed to the beneficiary at `index` at `now`, given the balance left in the deposit
+    ///
+    /// The last beneficiary to claim receives the whole balance, so rounding never
+    /// leaves dust behind, unless a renounced share is left for the depositor. With
+    /// vesting or tranches, only the released part of the share less what was already
+    /// paid out is owed.
+    pub fn share_amount(&self, index: usize, balance: u64, now: i64) -> u64 {
+        let beneficiary = &self.beneficiaries[index];
+        let share = if self.unclaimed_count() <= 1 && self.fully_allocated() {
+            balance.saturating_add(beneficiary.claimed_amount)
+        } else {
+            (self.amount as u128 * beneficiary.share_bps as u128 / TOTAL_SHARE_BPS as u128) as u64
+        };
+        let unlocked = (share as u128 * self.unlocked_bps(now) as u128 / TOTAL_SHARE_BPS as u128) as u64;
+        self.vested_amount(unlocked, now)
+            .saturating_sub(beneficiary.claimed_amount)
+            .min(balance)
+    }
+
+    /// Release tranches in use
+    pub fn tranches(&self) -> &[ReleaseTranche] {
+        &self.tranches[..self.tranche_count as usize]
+    }
+
+    /// Basis points of every share unlocked at `now` by the release tranches
+    ///
+    /// Unlocking follows the time since the last proof of life, so a returning
+    /// depositor stops it. A confirmed death or the release date unlocks everything.
+    pub fn unlocked_bps(&self, now: i64) -> u16 {
+        if self.tranche_count == 0 || self.death_confirmed(now) || self.released(now) {
+            return TOTAL_SHARE_BPS;
+        }
+        let elapsed = now.saturating_sub(self.last_proof_timestamp).max(0) as u64;
+        let timeout = self.timeout_at(now);
+        self.tranches()
+            .iter()
+            .filter(|t| elapsed >= timeout.saturating_mul(t.timeout_multiple as u64))
+            .map(|t| t.unlocked_bps)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Whether every share is unlocked and vested at `now`
+    pub fn fully_released(&self, now: i64) -> bool {
+        self.unlocked_bps(now) == TOTAL_SHARE_BPS && self.fully_vested(now)
+    }
+
+    /// Earliest time the deposit became claimable through expiry, the release date or a
+    /// confirmed death, as things stand at `now` (None if none of them applies)
+    pub fn claimable_since(&self, now: i64) -> Option<i64> {
+        let expired_at = (!self.release_at_only)
+            .then(|| self.last_proof_timestamp.saturating_add(self.timeout_at(now) as i64));
+        let released_at = (self.release_at != 0).then_some(self.release_at);
+        let death_confirmed_at = (self.death_confirmed_at != 0)
+            .then(|| self.death_confirmed_at.saturating_add(GUARDIAN_GRACE_SECONDS as i64));
+        [expired_at, released_at, death_confirmed_at].into_iter().flatten().min()
+    }
+
+    /// Start vesting from the moment the deposit became claimable
+    pub fn start_vesting(&mut self, now: i64) {
+        if self.vesting_seconds != 0 && self.vesting_started_at == 0 {
+            self.vesting_started_at = self.claimable_since(now).map_or(now, |since| since.min(now));
+        }
+    }
+
+    /// Fallback receivers in line
+    pub fn fallback_chain(&self) -> &[FallbackReceiver] {
+        &self.fallback_chain[..self.fallback_chain_len as usize]
+    }
+
+    /// Position of `receiver` in the fallback chain
+    pub fn fallback_index(&self, receiver: &Pubkey) -> Option<usize> {
+        self.fallback_chain().iter().position(|f| f.receiver == *receiver)
+    }
+
+    /// Fallback receiver whose claim window is open at `now`, closing the beneficiaries' and
+    /// earlier fallbacks' windows
+    ///
+    /// None while the beneficiaries may still claim: before the first window opens or once a
+    /// payout has been made. A pending claim restarts the windows when its challenge period
+    /// ends, so the initiator gets a window to finalize it before the chain moves on; a
+    /// fallback with a pending claim stays in line until then.
+    pub fn active_fallback(&self, now: i64) -> Option<usize> {
+        if self.claimed_amount > 0 {
+            return None;
+        }
+        let since = if self.has_pending_claim() {
+            self.claim_finalizable_at
+        } else {
+            self.claimable_since(now)?
+        };
+        let opened = self
+            .fallback_chain()
+            .iter()
+            .rposition(|f| now >= since.saturating_add(f.grace_seconds as i64));
+        match self.fallback_index(&self.claim_initiator).filter(|_| self.has_pending_claim()) {
+            Some(initiator) => Some(opened.map_or(initiator, |index| index.max(initiator))),
+            None => opened,
+        }
+    }
+
+    /// Make the fallback at `index` the sole beneficiary, dropping it and everyone before
+    /// it from the chain
+    pub fn hand_over_to_fallback(&mut self, index: usize) {
+        self.beneficiaries = [Beneficiary::default(); MAX_BENEFICIARIES];
+        self.beneficiaries[0] = Beneficiary {
+            receiver: self.fallback_chain[index].receiver,
+            share_bps: TOTAL_SHARE_BPS,
+            claimed: false,
+            claimed_amount: 0,
+        };
+        self.beneficiary_count = 1;
+
+        let len = self.fallback_chain_len as usize;
+        self.fallback_chain.copy_within(index + 1..len, 0);
+        let remaining = len - index - 1;
+        self.fallback_chain[remaining..].fill(FallbackReceiver::default());
+        self.fallback_chain_len = remaining as u8;
+    }
+
+    /// Whether every share can be paid out in full at `now`
+    pub fn fully_vested(&self, now: i64) -> bool {
+        self.vesting_seconds == 0
+            || (self.vesting_started_at != 0
+                && now.saturating_sub(self.vesting_started_at) >= self.vesting_seconds as i64)
+    }
+
+    /// Part of `share` vested at `now`, growing linearly over `vesting_seconds`
+    pub fn vested_amount(&self, share: u64, now: i64) -> u64 {
+        if self.fully_vested(now) {
+            return share;
+        }
+        if self.vesting_started_at == 0 {
+            return 0;
+        }
+        let elapsed = now.saturating_sub(self.vesting_started_at).max(0) as u128;
+        (share as u128 * elapsed / self.vesting_seconds as u128) as u64
+    }
+
+    /// Record a payout to the beneficiary at `index`, closing the deposit once every share is paid
+    pub fn record_claim(&mut self, index: usize, amount: u64, now: i64) {
+        let fully_released = self.fully_released(now);
+        let beneficiary = &mut self.beneficiaries[index];
+        beneficiary.claimed_amount = beneficiary.claimed_amount.saturating_add(amount);
+        beneficiary.claimed = fully_released;
+        self.claimed_amount = self.claimed_amount.saturating_add(amount);
+        // A renounced share stays deposited for the depositor to withdraw
+        self.is_closed = self.fully_allocated() && self.beneficiaries().iter().all(|b| b.claimed);
+    }
+}
+
+/// Build a beneficiary list from `(receiver, share_bps)` pairs
+///
+/// Requires 1 to `MAX_BENEFICIARIES` distinct receivers with non-zero shares adding
+/// up to `TOTAL_SHARE_BPS`.
+pub fn build_beneficiaries(
+    shares: &[(Pubkey, u16)],
+) -> Result<(u8, [Beneficiary; MAX_BENEFICIARIES]), DielemmaError> {
+    if shares.is_empty() || shares.len() > MAX_BENEFICIARIES {
+        return Err(DielemmaError::InvalidBeneficiaries);
+    }
+
+    let mut beneficiaries = [Beneficiary::default(); MAX_BENEFICIARIES];
+    let mut total: u32 = 0;
+    for (i, (receiver, share_bps)) in shares.iter().enumerate() {
+        if *share_bps == 0 || shares[..i].iter().any(|(other, _)| other == receiver) {
+            return Err(DielemmaError::InvalidBeneficiaries);
+        }
+        total += *share_bps as u32;
+        beneficiaries[i] = Beneficiary {
+            receiver: *receiver,
+            share_bps: *share_bps,
+            claimed: false,
+            claimed_amount: 0,
+        };
+    }
+    if total != TOTAL_SHARE_BPS as u32 {
+        return Err(DielemmaError::InvalidBeneficiaries);
+    }
+
+    Ok((shares.len() as u8, beneficiaries))
+}
+
+/// Build a fallback chain from `(receiver, grace_seconds)` pairs
+///
+/// Requires up to `MAX_FALLBACK_RECEIVERS` distinct receivers with strictly increasing,
+/// non-zero grace periods of at most `MAX_TIMEOUT_SECONDS`.
+pub fn build_fallback_chain(
+    fallbacks: &[(Pubkey, u64)],
+) -> Result<(u8, [FallbackReceiver; MAX_FALLBACK_RECEIVERS]), DielemmaError> {
+    if fallbacks.len() > MAX_FALLBACK_RECEIVERS {
+        return Err(DielemmaError::InvalidFallbackChain);
+    }
+
+    let mut chain = [FallbackReceiver::default(); MAX_FALLBACK_RECEIVERS];
+    let mut previous_grace = 0;
+    for (i, (receiver, grace_seconds)) in fallbacks.iter().enumerate() {
+        if *grace_seconds <= previous_grace
+            || *grace_seconds > MAX_TIMEOUT_SECONDS
+            || fallbacks[..i].iter().any(|(other, _)| other == receiver)
+        {
+            return Err(DielemmaError::InvalidFallbackChain);
+        }
+        chain[i] = FallbackReceiver {
+            receiver: *receiver,
+            grace_seconds: *grace_seconds,
+        };
+        previous_grace = *grace_seconds;
+    }
+
+    Ok((fallbacks.len() as u8, chain))
+}
+
+/// Build a release schedule from `(timeout_multiple, unlocked_bps)` pairs
+///
+/// Requires up to `MAX_RELEASE_TRANCHES` tranches with strictly increasing multiples
+/// (starting at 1 or more) and cumulative shares, the last unlocking `TOTAL_SHARE_BPS`.
+/// An empty list releases everything at expiry.
+pub fn build_tranches(
+    tranches: &[(u8, u16)],
+) -> Result<(u8, [ReleaseTranche; MAX_RELEASE_TRANCHES]), DielemmaError> {
+    if tranches.len() > MAX_RELEASE_TRANCHES {
+        return Err(DielemmaError::InvalidTranches);
+    }
+
+    let mut schedule = [ReleaseTranche::default(); MAX_RELEASE_TRANCHES];
+    let (mut previous_multiple, mut previous_bps) = (0, 0);
+    for (i, (timeout_multiple, unlocked_bps)) in tranches.iter().enumerate() {
+        if *timeout_multiple <= previous_multiple || *unlocked_bps <= previous_bps || *unlocked_bps > TOTAL_SHARE_BPS {
+            return Err(DielemmaError::InvalidTranches);
+        }
+        schedule[i] = ReleaseTranche {
+            timeout_multiple: *timeout_multiple,
+            unlocked_bps: *unlocked_bps,
+        };
+        previous_multiple = *timeout_multiple;
+        previous_bps = *unlocked_bps;
+    }
+    if !tranches.is_empty() && previous_bps != TOTAL_SHARE_BPS {
+        return Err(DielemmaError::InvalidTranches);
+    }
+
+    Ok((tranches.len() as u8, schedule))
+}
+
+/// `DepositAccount::token_mint` of native SOL deposits
+pub const NATIVE_SOL_MINT: Pubkey = Pubkey::new_from_array([0; 32]);
+
+/// Calculate the size needed for a DepositAccount
+/// 32 (depositor) + 1 (beneficiary_count) + 430 (beneficiaries) + 32 (token_mint) + 8 (amount) +
+/// 8 (claimed_amount) + 8 (last_proof_timestamp) + 8 (timeout_seconds) + 8 (pending_timeout_seconds) +
+/// 8 (pending_timeout_effective_at) + 32 (claim_initiator) + 8 (claim_finalizable_at) +
+/// 1 (delegate_count) + 120 (delegates) + 8 (heartbeat_nonce) + 1 (profile_linked) + 1 (guardian_count) +
+/// 160 (guardians) + 1 (guardian_threshold) + 1 (death_attestations) + 8 (death_confirmed_at) +
+/// 8 (vesting_seconds) + 8 (vesting_started_at) + 1 (tranche_count) + 12 (tranches) + 8 (release_at) +
+/// 1 (release_at_only) + 8 (withdraw_locked_until) + 32 (fallback_receiver) + 1 (fallback_chain_len) +
+/// 160 (fallback_chain) + 1 (bump) + 1 (is_closed) + 4 (seed length) + 32 (seed data) + 1 (layout_version)
+/// = 1162 bytes
+pub const DEPOSIT_ACCOUNT_SIZE: usize = 32 + 1 + BENEFICIARY_SIZE * MAX_BENEFICIARIES + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 32
+    + 8 + 1 + DELEGATE_SIZE * MAX_DELEGATES + 8 + 1 + 1 + 32 * MAX_GUARDIANS + 1 + 1 + 8 + 8 + 8 + 1
+    + RELEASE_TRANCHE_SIZE * MAX_RELEASE_TRANCHES + 8 + 1 + 8 + 32 + 1 + FALLBACK_RECEIVER_SIZE * MAX_FALLBACK_RECEIVERS
+    + 1 + 1 + 4 + MAX_DEPOSIT_SEED_LENGTH + 1;
+
+/// `DepositAccount::layout_version` written by this program; deposits in any other layout are
+/// rejected until converted by `MigrateDeposit`
+pub const DEPOSIT_LAYOUT_VERSION: u8 = 1;
+
+/// Deposit account state written before beneficiaries were introduced, converted by `MigrateDeposit`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct LegacyDepositAccount {
+    /// Depositor's public key
+    pub depositor: Pubkey,
+    /// Receiver who can claim if proof-of-life expires
+    pub receiver: Pubkey,
+    /// Token mint address
+    pub token_mint: Pubkey,
+    /// Amount of tokens deposited
+    pub amount: u64,
+    /// Last proof-of-life timestamp (unix timestamp)
+    pub last_proof_timestamp: i64,
+    /// Timeout period in seconds
+    pub timeout_seconds: u64,
+    /// Bump seed for PDA
+    pub bump: u8,
+    /// Whether tokens have been withdrawn/claimed
+    pub is_closed: bool,
+    /// Length of deposit_seed
+    pub deposit_seed_len: u32,
+    /// Deposit seed used to derive this account's PDA (fixed-size array)
+    pub deposit_seed: [u8; MAX_DEPOSIT_SEED_LENGTH],
+}
+
+/// Size of a LegacyDepositAccount
+/// 32 (depositor) + 32 (receiver) + 32 (token_mint) + 8 (amount) + 8 (last_proof_timestamp) +
+/// 8 (timeout_seconds) + 1 (bump) + 1 (is_closed) + 4 (seed length) + 32 (seed data)
+/// = 158 bytes
+pub const LEGACY_DEPOSIT_ACCOUNT_SIZE: usize = 32 + 32 + 32 + 8 + 8 + 8 + 1 + 1 + 4 + MAX_DEPOSIT_SEED_LENGTH;
+
+/// Per-depositor liveness timestamp shared by every deposit linked to it
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ProfileAccount {
+    /// Depositor owning the profile
+    pub depositor: Pubkey,
+    /// Last proof-of-life timestamp recorded with ProfileProofOfLife (unix timestamp)
+    pub last_proof_timestamp: i64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+/// Calculate the size needed for a ProfileAccount
+/// 32 (depositor) + 8 (last_proof_timestamp) + 1 (bump)
+/// = 41 bytes
+pub const PROFILE_ACCOUNT_SIZE: usize = 32 + 8 + 1;
+
+/// Global program config stored on-chain
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ConfigAccount {
+    /// Admin allowed to update the config
+    pub admin: Pubkey,
+    /// Admin proposed by the current admin (default pubkey if none)
+    pub pending_admin: Pubkey,
+    /// DLM mint burned by proof-of-life
+    pub dlm_mint: Pubkey,
+    /// Wallet whose DLM ATA receives the burned tokens
+    pub burn_address: Pubkey,
+    /// Amount of DLM (in smallest unit) burned per proof-of-life
+    pub proof_of_life_cost: u64,
+    /// Seconds an initiated claim waits before it can be finalized
+    pub claim_challenge_seconds: u64,
+    /// Whether Deposit, ProofOfLife, Withdraw and Claim are paused
+    pub paused: bool,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+/// Calculate the size needed for a ConfigAccount
+/// 32 (admin) + 32 (pending_admin) + 32 (dlm_mint) + 32 (burn_address) +
+/// 8 (proof_of_life_cost) + 8 (claim_challenge_seconds) + 1 (paused) + 1 (bump)
+/// = 146 bytes
+pub const CONFIG_ACCOUNT_SIZE: usize = 32 + 32 + 32 + 32 + 8 + 8 + 1 + 1;
+
+// Derive PDA seeds
+pub const DEPOSIT_SEED_PREFIX: &[u8] = b"deposit";
+pub const TOKEN_ACCOUNT_SEED_PREFIX: &[u8] = b"token_account";
+pub const CONFIG_SEED_PREFIX: &[u8] = b"config";
+pub const PROFILE_SEED_PREFIX: &[u8] = b"profile";
+
+// Entry point for the Dielemma program
+entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // Manually parse instruction to avoid stack overflow with Borsh + String
+    // Instruction format: discriminant (4 bytes) + data
+    if instruction_data.len() < 4 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let discriminant = u32::from_le_bytes(
+        instruction_data[0..4]
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?
+    );
+
+    let data = &instruction_data[4..];
+
+    match discriminant {
+        0 => {
+            // Deposit instruction
+            let (deposit_seed, rest) = unpack_deposit_seed(data)?;
+            let (receiver, rest) = unpack_pubkey(rest)?;
+            let (amount, rest) = unpack_u64(rest)?;
+            let (timeout_seconds, rest) = unpack_u64(rest)?;
+            let (withdraw_locked_until, _) = unpack_optional_i64(rest)?;
+
+            process_deposit(
+                program_id,
+                accounts,
+                deposit_seed,
+                &receiver,
+                amount,
+                timeout_seconds,
+                withdraw_locked_until,
+            )
+        }
+        1 => {
+            // ProofOfLife instruction
+            let (deposit_seed, _) = unpack_deposit_seed(data)?;
+            process_proof_of_life(program_id, accounts, deposit_seed)
+        }
+        2 => {
+            // Withdraw instruction
+            let (deposit_seed, _) = unpack_deposit_seed(data)?;
+            process_withdraw(program_id, accounts, deposit_seed)
+        }
+        3 => {
+            // Claim instruction
+            let (deposit_seed, _) = unpack_deposit_seed(data)?;
+            process_claim(program_id, accounts, deposit_seed)
+        }
+        4 => {
+            // CloseAccount instruction
+            let (deposit_seed, _) = unpack_deposit_seed(data)?;
+            process_close_account(program_id, accounts, deposit_seed)
+        }
+        5 => {
+            // InitializeConfig instruction
+            let (dlm_mint, rest) = unpack_pubkey(data)?;
+            let (burn_address, rest) = unpack_pubkey(rest)?;
+            let (proof_of_life_cost, _) = unpack_u64(rest)?;
+            process_initialize_config(program_id, accounts, &dlm_mint, &burn_address, proof_of_life_cost)
+        }
+        6 => {
+            // SetPaused instruction
+            let (paused, _) = unpack_bool(data)?;
+            process_set_paused(program_id, accounts, paused)
+        }
+        7 => {
+            // UpdateConfig instruction
+            let (dlm_mint, rest) = unpack_pubkey(data)?;
+            let (burn_address, rest) = unpack_pubkey(rest)?;
+            let (proof_of_life_cost, _) = unpack_u64(rest)?;
+            process_update_config(program_id, accounts, &dlm_mint, &burn_address, proof_of_life_cost)
+        }
+        8 => {
+            // ProposeAdmin instruction
+            let (new_admin, _) = unpack_pubkey(data)?;
+            process_propose_admin(program_id, accounts, &new_admin)
+        }
+        9 => {
+            // AcceptAdmin instruction
+            process_accept_admin(program_id, accounts)
+        }
+        10 => {
+            // DepositSol instruction
+            let (deposit_seed, rest) = unpack_deposit_seed(data)?;
+            let (receiver, rest) = unpack_pubkey(rest)?;
+            let (amount, rest) = unpack_u64(rest)?;
+            let (timeout_seconds, rest) = unpack_u64(rest)?;
+            let (withdraw_locked_until, _) = unpack_optional_i64(rest)?;
+
+            process_deposit_sol(
+                program_id,
+                accounts,
+                deposit_seed,
+                &receiver,
+                amount,
+                timeout_seconds,
+                withdraw_locked_until,
+            )
+        }
+        11 => {
+            // WithdrawSol instruction
+            let (deposit_seed, _) = unpack_deposit_seed(data)?;
+            process_withdraw_sol(program_id, accounts, deposit_seed)
+        }
+        12 => {
+            // ClaimSol instruction
+            let (deposit_seed, _) = unpack_deposit_seed(data)?;
+            process_claim_sol(program_id, accounts, deposit_seed)
+        }
+        13 => {
+            // SetBeneficiaries instruction
+            let (deposit_seed, rest) = unpack_deposit_seed(data)?;
+            let beneficiaries = unpack_beneficiaries(rest)?;
+            process_set_beneficiaries(program_id, accounts, deposit_seed, &beneficiaries)
+        }
+        14 => {
+            // UpdateReceiver instruction
+            let (deposit_seed, rest) = unpack_deposit_seed(data)?;
+            let (old_receiver, rest) = unpack_pubkey(rest)?;
+            let (new_receiver, rest) = unpack_pubkey(rest)?;
+            let (reset_timer, _) = unpack_bool(rest)?;
+            process_update_receiver(program_id, accounts, deposit_seed, &old_receiver, &new_receiver, reset_timer)
+        }
+        15 => {
+            // UpdateTimeout instruction
+            let (deposit_seed, rest) = unpack_deposit_seed(data)?;
+            let (timeout_seconds, _) = unpack_u64(rest)?;
+            process_update_timeout(program_id, accounts, deposit_seed, timeout_seconds)
+        }
+        16 => {
+            // TopUp instruction
+            let (deposit_seed, rest) = unpack_deposit_seed(data)?;
+            let (amount, _) = unpack_u64(rest)?;
+            process_top_up(program_id, accounts, deposit_seed, amount)
+        }
+        17 => {
+            // PartialWithdraw instruction
+            let (deposit_seed, rest) = unpack_deposit_seed(data)?;
+            let (amount, _) = unpack_u64(rest)?;
+            process_partial_withdraw(program_id, accounts, deposit_seed, amount)
+        }
+        18 => {
+            // InitiateClaim instruction
+            let (deposit_seed, _) = unpack_deposit_seed(data)?;
+            process_initiate_claim(program_id, accounts, deposit_seed)
+        }
+        19 => {
+            // CancelClaim instruction
+            let (deposit_seed, _) = unpack_deposit_seed(data)?;
+            process_cancel_claim(program_id, accounts, deposit_seed)
+        }
+        20 => {
+            // SetClaimChallengePeriod instruction
+            let (challenge_seconds, _) = unpack_u64(data)?;
+            process_set_claim_challenge_period(program_id, accounts, challenge_seconds)
+        }
+        21 => {
+            // AddDelegate instruction
+            let (deposit_seed, rest) = unpack_deposit_seed(data)?;
+            let (delegate, rest) = unpack_pubkey(rest)?;
+            let (expires_at, _) = unpack_i64(rest)?;
+            process_add_delegate(program_id, accounts, deposit_seed, &delegate, expires_at)
+        }
+        22 => {
+            // RevokeDelegate instruction
+            let (deposit_seed, rest) = unpack_deposit_seed(data)?;
+            let (delegate, _) = unpack_pubkey(rest)?;
+            process_revoke_delegate(program_id, accounts, deposit_seed, &delegate)
+        }
+        23 => {
+            // SignedProofOfLife instruction
+            let (deposit_seed, rest) = unpack_deposit_seed(data)?;
+            let (nonce, rest) = unpack_u64(rest)?;
+            let (expires_at, _) = unpack_i64(rest)?;
+            process_signed_proof_of_life(program_id, accounts, deposit_seed, nonce, expires_at)
+        }
+        24 => {
+            // BatchProofOfLife instruction
+            let deposit_seeds = unpack_deposit_seeds(data)?;
+            process_batch_proof_of_life(program_id, accounts, &deposit_seeds)
+        }
+        25 => {
+            // CreateProfile instruction
+            process_create_profile(program_id, accounts)
+        }
+        26 => {
+            // SetProfileLink instruction
+            let (deposit_seed, rest) = unpack_deposit_seed(data)?;
+            let (linked, _) = unpack_bool(rest)?;
+            process_set_profile_link(program_id, accounts, deposit_seed, linked)
+        }
+        27 => {
+            // ProfileProofOfLife instruction
+            process_profile_proof_of_life(program_id, accounts)
+        }
+        28 => {
+            // SetGuardians instruction
+            let (deposit_seed, rest) = unpack_deposit_seed(data)?;
+            let (threshold, rest) = rest.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+            let guardians = unpack_guardians(rest)?;
+            process_set_guardians(program_id, accounts, deposit_seed, *threshold, &guardians)
+        }
+        29 => {
+            // AttestDeath instruction
+            let (deposit_seed, _) = unpack_deposit_seed(data)?;
+            process_attest_death(program_id, accounts, deposit_seed)
+        }
+        30 => {
+            // SetVesting instruction
+            let (deposit_seed, rest) = unpack_deposit_seed(data)?;
+            let (vesting_seconds, _) = unpack_u64(rest)?;
+            process_set_vesting(program_id, accounts, deposit_seed, vesting_seconds)
+        }
+        31 => {
+            // SetReleaseTranches instruction
+            let (deposit_seed, rest) = unpack_deposit_seed(data)?;
+            let tranches = unpack_tranches(rest)?;
+            process_set_release_tranches(program_id, accounts, deposit_seed, &tranches)
+        }
+        32 => {
+            // SetReleaseDate instruction
+            let (deposit_seed, rest) = unpack_deposit_seed(data)?;
+            let (release_at, rest) = unpack_i64(rest)?;
+            let (release_at_only, _) = unpack_bool(rest)?;
+            process_set_release_date(program_id, accounts, deposit_seed, release_at, release_at_only)
+        }
+        33 => {
+            // SetFallbackReceiver instruction
+            let (deposit_seed, rest) = unpack_deposit_seed(data)?;
+            let (fallback_receiver, _) = unpack_pubkey(rest)?;
+            process_set_fallback_receiver(program_id, accounts, deposit_seed, &fallback_receiver)
+        }
+        34 => {
+            // Renounce instruction
+            let (deposit_seed, _) = unpack_deposit_seed(data)?;
+            process_renounce(program_id, accounts, deposit_seed)
+        }
+        35 => {
+            // SetFallbackChain instruction
+            let (deposit_seed, rest) = unpack_deposit_seed(data)?;
+            let fallbacks = unpack_fallback_chain(rest)?;
+            process_set_fallback_chain(program_id, accounts, deposit_seed, &fallbacks)
+        }
+        36 => {
+            // RotateDepositor instruction
+            let (deposit_seed, _) = unpack_deposit_seed(data)?;
+            process_rotate_depositor(program_id, accounts, deposit_seed)
+        }
+        37 => {
+            // MigrateDeposit instruction
+            let (deposit_seed, _) = unpack_deposit_seed(data)?;
+            process_migrate_deposit(program_id, accounts, deposit_seed)
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+/// Parse a length-prefixed deposit seed, returning it and the remaining bytes
+fn unpack_deposit_seed(data: &[u8]) -> Result<(&str, &[u8]), ProgramError> {
+    if data.len() < 4 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let seed_len = u32::from_le_bytes(data[0..4].try_into().unwrap()) as usize;
+    if seed_len > MAX_DEPOSIT_SEED_LENGTH {
+        msg!("Invalid deposit seed length");
+        return Err(DielemmaError::InvalidDepositSeed.into());
+    }
+    if 4 + seed_len > data.len() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let deposit_seed = std::str::from_utf8(&data[4..4 + seed_len])
+        .map_err(|_| DielemmaError::InvalidDepositSeed)?;
+
+    Ok((deposit_seed, &data[4 + seed_len..]))
+}
+
+/// Parse a 32-byte public key, returning it and the remaining bytes
+fn unpack_pubkey(data: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
+    if data.len() < 32 {
+        msg!("Invalid instruction data: insufficient bytes");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (key, rest) = data.split_at(32);
+    let key = Pubkey::try_from(key).map_err(|_| ProgramError::InvalidInstructionData)?;
+    Ok((key, rest))
+}
+
+/// Parse a little-endian u64, returning it and the remaining bytes
+fn unpack_u64(data: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
+    if data.len() < 8 {
+        msg!("Invalid instruction data: insufficient bytes");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (value, rest) = data.split_at(8);
+    Ok((u64::from_le_bytes(value.try_into().unwrap()), rest))
+}
+
+/// Parse a little-endian i64, returning it and the remaining bytes
+fn unpack_i64(data: &[u8]) -> Result<(i64, &[u8]), ProgramError> {
+    let (value, rest) = unpack_u64(data)?;
+    Ok((value as i64, rest))
+}
+
+/// Parse a trailing little-endian i64 that older clients leave out, defaulting to 0
+fn unpack_optional_i64(data: &[u8]) -> Result<(i64, &[u8]), ProgramError> {
+    if data.is_empty() {
+        return Ok((0, data));
+    }
+    unpack_i64(data)
+}
+
+/// Parse a one-byte count followed by `(receiver, share_bps)` pairs
+fn unpack_beneficiaries(data: &[u8]) -> Result<Vec<(Pubkey, u16)>, ProgramError> {
+    let (count, mut rest) = data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+    if *count as usize > MAX_BENEFICIARIES {
+        msg!("Too many beneficiaries");
+        return Err(DielemmaError::InvalidBeneficiaries.into());
+    }
+
+    let mut beneficiaries = Vec::with_capacity(*count as usize);
+    for _ in 0..*count {
+        let (receiver, after_key) = unpack_pubkey(rest)?;
+        if after_key.len() < 2 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let (share_bps, after_share) = after_key.split_at(2);
+        beneficiaries.push((receiver, u16::from_le_bytes(share_bps.try_into().unwrap())));
+        rest = after_share;
+    }
+    Ok(beneficiaries)
+}
+
+/// Parse a one-byte count followed by that many guardian public keys
+fn unpack_guardians(data: &[u8]) -> Result<Vec<Pubkey>, ProgramError> {
+    let (count, mut rest) = data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+    if *count as usize > MAX_GUARDIANS {
+        msg!("Too many guardians");
+        return Err(DielemmaError::InvalidGuardians.into());
+    }
+
+    let mut guardians = Vec::with_capacity(*count as usize);
+    for _ in 0..*count {
+        let (guardian, after_key) = unpack_pubkey(rest)?;
+        guardians.push(guardian);
+        rest = after_key;
+    }
+    Ok(guardians)
+}
+
+/// Parse a one-byte count followed by that many `(receiver, u64 grace_seconds)` pairs
+fn unpack_fallback_chain(data: &[u8]) -> Result<Vec<(Pubkey, u64)>, ProgramError> {
+    let (count, mut rest) = data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+    if *count as usize > MAX_FALLBACK_RECEIVERS {
+        msg!("Too many fallback receivers");
+        return Err(DielemmaError::InvalidFallbackChain.into());
+    }
+
+    let mut fallbacks = Vec::with_capacity(*count as usize);
+    for _ in 0..*count {
+        let (receiver, after_key) = unpack_pubkey(rest)?;
+        let (grace_seconds, after_grace) = unpack_u64(after_key)?;
+        fallbacks.push((receiver, grace_seconds));
+        rest = after_grace;
+    }
+    Ok(fallbacks)
+}
+
+/// Parse a one-byte count followed by that many `(u8 timeout_multiple, u16 unlocked_bps)` pairs
+fn unpack_tranches(data: &[u8]) -> Result<Vec<(u8, u16)>, ProgramError> {
+    let (count, mut rest) = data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+    if *count as usize > MAX_RELEASE_TRANCHES {
+        msg!("Too many release tranches");
+        return Err(DielemmaError::InvalidTranches.into());
+    }
+
+    let mut tranches = Vec::with_capacity(*count as usize);
+    for _ in 0..*count {
+        if rest.len() < 3 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        tranches.push((rest[0], u16::from_le_bytes([rest[1], rest[2]])));
+        rest = &rest[3..];
+    }
+    Ok(tranches)
+}
+
+/// Parse a one-byte count followed by that many length-prefixed deposit seeds
+fn unpack_deposit_seeds(data: &[u8]) -> Result<Vec<&str>, ProgramError> {
+    let (count, mut rest) = data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+    if *count == 0 || *count as usize > MAX_BATCH_DEPOSITS {
+        msg!("Batch must hold between 1 and {} deposits", MAX_BATCH_DEPOSITS);
+        return Err(DielemmaError::InvalidBatch.into());
+    }
+
+    let mut deposit_seeds = Vec::with_capacity(*count as usize);
+    for _ in 0..*count {
+        let (deposit_seed, after_seed) = unpack_deposit_seed(rest)?;
+        deposit_seeds.push(deposit_seed);
+        rest = after_seed;
+    }
+    Ok(deposit_seeds)
+}
+
+/// Parse a one-byte boolean (0 or 1), returning it and the remaining bytes
+fn unpack_bool(data: &[u8]) -> Result<(bool, &[u8]), ProgramError> {
+    match data.split_first() {
+        Some((0, rest)) => Ok((false, rest)),
+        Some((1, rest)) => Ok((true, rest)),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+/// Process deposit instruction
+fn process_deposit(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,  // Use reference to avoid copying
+    receiver: &Pubkey,   // Use reference to avoid copying
+    amount: u64,
+    timeout_seconds: u64,
+    withdraw_locked_until: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let depositor_token_account = next_account_info(account_info_iter)?;
+    let deposit_token_account = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let extra_accounts = account_info_iter.as_slice();
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    // Verify system program
+    if system_program.key != &system_program::id() {
+        msg!("Invalid system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Verify rent sysvar
+    if rent_account.key != &Rent::id() {
+        msg!("Invalid rent sysvar");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Verify token program - accept both legacy Token and Token-2022
+    assert_token_program(token_program)?;
+
+    validate_deposit_terms(amount, timeout_seconds)?;
+
+    // Verify token account ownership
+    let token_account_state = unpack_token_account(depositor_token_account, token_program)?;
+    if token_account_state.owner != *depositor.key {
+        msg!("Token account must be owned by depositor");
+        return Err(DielemmaError::InvalidTokenAccountOwner.into());
+    }
+    if token_account_state.mint != *token_mint.key {
+        msg!("Token account mint does not match the token mint");
+        return Err(DielemmaError::MintMismatch.into());
+    }
+
+    // Read decimals from the mint (with or without extensions)
+    let decimals = unpack_mint_decimals(token_mint, token_program)?;
+
+    // Get clock for timestamp
+    let clock = Clock::get()?;
+
+    // Derive PDA for deposit account (using client-provided seed)
+    let (deposit_pda, bump) = Pubkey::find_program_address(
+        &[DEPOSIT_SEED_PREFIX, depositor.key.as_ref(), deposit_seed.as_bytes()],
+        program_id,
+    );
+
+    if deposit_account.key != &deposit_pda {
+        msg!("Invalid deposit account PDA");
+        return Err(DielemmaError::InvalidDepositAccount.into());
+    }
+
+    // Check if deposit account already exists
+    if deposit_account.lamports() > 0 {
+        msg!("Deposit account already exists");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // Calculate minimum rent exemption
+    let rent = Rent::get()?;
+    let required_lamports = rent
+        .minimum_balance(DEPOSIT_ACCOUNT_SIZE)
+        .max(1);
+
+    // Create deposit account
+    let create_deposit_account_ix = system_instruction::create_account(
+        depositor.key,
+        deposit_account.key,
+        required_lamports,
+        DEPOSIT_ACCOUNT_SIZE as u64,
+        program_id,
+    );
+
+    invoke_signed(
+        &create_deposit_account_ix,
+        &[
+            depositor.clone(),
+            deposit_account.clone(),
+            system_program.clone(),
+        ],
+        &[&[
+            DEPOSIT_SEED_PREFIX,
+            depositor.key.as_ref(),
+            deposit_seed.as_bytes(),
+            &[bump],
+        ]],
+    )?;
+
+    create_vault(
+        program_id,
+        depositor,
+        deposit_account,
+        deposit_token_account,
+        token_mint,
+        token_program,
+        system_program,
+        &rent,
+    )?;
+
+    // Transfer tokens from depositor to deposit token account
+    transfer_tokens(
+        token_program,
+        depositor_token_account,  // Source: depositor's ATA
+        token_mint,
+        deposit_token_account,    // Destination: deposit's token account
+        depositor,
+        extra_accounts,
+        amount,
+        decimals,
+        &[],
+    )?;
+
+    // A transfer-fee mint withholds part of the transfer in the vault's fee extension,
+    // so record what the vault's balance actually became rather than `amount`
+    let received_amount = unpack_token_account(deposit_token_account, token_program)?.amount;
+    if received_amount == 0 {
+        msg!("Deposit amount is entirely consumed by the transfer fee");
+        return Err(DielemmaError::InvalidAmount.into());
+    }
+
+    // Create deposit account state; the receiver gets the whole deposit until beneficiaries are set
+    let (seed_len, seed_array) = pack_deposit_seed(deposit_seed);
+    let (beneficiary_count, beneficiaries) = build_beneficiaries(&[(*receiver, TOTAL_SHARE_BPS)])?;
+
+    let deposit_state = DepositAccount {
+        depositor: *depositor.key,
+        beneficiary_count,
+        beneficiaries,
+        token_mint: *token_mint.key,
+        amount: received_amount,
+        claimed_amount: 0,
+        last_proof_timestamp: clock.unix_timestamp,
+        timeout_seconds,
+        pending_timeout_seconds: 0,
+        pending_timeout_effective_at: 0,
+        claim_initiator: Pubkey::default(),
+        claim_finalizable_at: 0,
+        delegate_count: 0,
+        delegates: [Delegate::default(); MAX_DELEGATES],
+        heartbeat_nonce: 0,
+        profile_linked: false,
+        guardian_count: 0,
+        guardians: [Pubkey::default(); MAX_GUARDIANS],
+        guardian_threshold: 0,
+        death_attestations: 0,
+        death_confirmed_at: 0,
+        vesting_seconds: 0,
+        vesting_started_at: 0,
+        tranche_count: 0,
+        tranches: [ReleaseTranche::default(); MAX_RELEASE_TRANCHES],
+        release_at: 0,
+        release_at_only: false,
+        withdraw_locked_until,
+        fallback_receiver: Pubkey::default(),
+        fallback_chain_len: 0,
+        fallback_chain: [FallbackReceiver::default(); MAX_FALLBACK_RECEIVERS],
+        bump,
+        is_closed: false,
+        deposit_seed_len: seed_len,
+        deposit_seed: seed_array,
+        layout_version: DEPOSIT_LAYOUT_VERSION,
+    };
+
+    // Serialize and write to account
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "Deposit successful: {} tokens sent, {} received after transfer fees, to receiver {}",
+        amount,
+        received_amount,
+        receiver
+    );
+    Ok(())
+}
+
+/// Process proof-of-life instruction
+fn process_proof_of_life(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,  // Use reference
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let signer = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let signer_dlm_token_account = next_account_info(account_info_iter)?;
+    let burn_dlm_token_account = next_account_info(account_info_iter)?;
+    let dlm_mint_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Verify depositor or delegate is signer
+    if !signer.is_signer {
+        msg!("Depositor or delegate must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    let mut deposit_state = load_proof_of_life_deposit(program_id, deposit_account, deposit_seed)?;
+
+    // Verify depositor or an active delegate
+    let clock = Clock::get()?;
+    if deposit_state.depositor != *signer.key {
+        let delegate = match deposit_state.delegate_index(signer.key) {
+            Some(index) => deposit_state.delegates[index],
+            None => {
+                msg!("Only the depositor or a delegate can perform proof of life");
+                return Err(DielemmaError::NotDepositor.into());
+            }
+        };
+        if !delegate.is_active(clock.unix_timestamp) {
+            msg!("Delegate expired at {}", delegate.expires_at);
+            return Err(DielemmaError::DelegateExpired.into());
+        }
+    }
+
+    // Remaining accounts for Token-2022 extensions (transfer hooks, fees, etc.)
+    let extra_accounts = account_info_iter.as_slice();
+
+    let transfer_amount = burn_proof_of_life_cost(
+        &config,
+        signer,
+        signer_dlm_token_account,
+        burn_dlm_token_account,
+        dlm_mint_account,
+        token_program,
+        extra_accounts,
+    )?;
+
+    // Update timestamp
+    if deposit_state.has_pending_claim() {
+        msg!("Pending claim by {} voided", deposit_state.claim_initiator);
+    }
+    deposit_state.record_proof_of_life(clock.unix_timestamp);
+
+    // Serialize back
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    msg!("Proof of life recorded at {} with {} tokens transferred to burn address", deposit_state.last_proof_timestamp, transfer_amount);
+    Ok(())
+}
+
+/// Process signed proof-of-life instruction
+///
+/// The depositor signs a heartbeat message off-chain; the relayer submitting it pays
+/// the fees and the DLM cost.
+fn process_signed_proof_of_life(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+    nonce: u64,
+    expires_at: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let relayer = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let relayer_dlm_token_account = next_account_info(account_info_iter)?;
+    let burn_dlm_token_account = next_account_info(account_info_iter)?;
+    let dlm_mint_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let instructions_sysvar = next_account_info(account_info_iter)?;
+    let extra_accounts = account_info_iter.as_slice();
+
+    // Verify relayer is signer
+    if !relayer.is_signer {
+        msg!("Relayer must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    let mut deposit_state = load_proof_of_life_deposit(program_id, deposit_account, deposit_seed)?;
+
+    let clock = Clock::get()?;
+    if clock.unix_timestamp > expires_at {
+        msg!("Heartbeat expired at {}", expires_at);
+        return Err(DielemmaError::HeartbeatExpired.into());
+    }
+    if nonce <= deposit_state.heartbeat_nonce {
+        msg!("Heartbeat nonce must be greater than {}", deposit_state.heartbeat_nonce);
+        return Err(DielemmaError::HeartbeatNonceUsed.into());
+    }
+
+    let message = instruction::heartbeat_message(program_id, deposit_account.key, nonce, expires_at);
+    verify_ed25519_signature(instructions_sysvar, &deposit_state.depositor, &message)?;
+
+    let transfer_amount = burn_proof_of_life_cost(
+        &config,
+        relayer,
+        relayer_dlm_token_account,
+        burn_dlm_token_account,
+        dlm_mint_account,
+        token_program,
+        extra_accounts,
+    )?;
+
+    if deposit_state.has_pending_claim() {
+        msg!("Pending claim by {} voided", deposit_state.claim_initiator);
+    }
+    deposit_state.heartbeat_nonce = nonce;
+    deposit_state.record_proof_of_life(clock.unix_timestamp);
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "Signed proof of life (nonce {}) relayed by {} recorded at {} with {} tokens transferred to burn address",
+        nonce,
+        relayer.key,
+        deposit_state.last_proof_timestamp,
+        transfer_amount
+    );
+    Ok(())
+}
+
+/// Process batch proof-of-life instruction
+///
+/// Every deposit must belong to the signing depositor; the DLM cost is burned once
+/// for the whole batch.
+fn process_batch_proof_of_life(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seeds: &[&str],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let depositor_dlm_token_account = next_account_info(account_info_iter)?;
+    let burn_dlm_token_account = next_account_info(account_info_iter)?;
+    let dlm_mint_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let mut deposit_accounts = Vec::with_capacity(deposit_seeds.len());
+    for _ in deposit_seeds {
+        deposit_accounts.push(next_account_info(account_info_iter)?);
+    }
+    let extra_accounts = account_info_iter.as_slice();
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    // Validate every deposit before touching any of them
+    let mut deposit_states = Vec::with_capacity(deposit_seeds.len());
+    for (index, (deposit_account, deposit_seed)) in deposit_accounts.iter().zip(deposit_seeds).enumerate() {
+        if deposit_accounts[..index].iter().any(|other| other.key == deposit_account.key) {
+            msg!("Deposit account {} is listed twice", deposit_account.key);
+            return Err(DielemmaError::InvalidBatch.into());
+        }
+        let deposit_state = load_proof_of_life_deposit(program_id, deposit_account, deposit_seed)?;
+        if deposit_state.depositor != *depositor.key {
+            msg!("Deposit {} belongs to another depositor", deposit_account.key);
+            return Err(DielemmaError::NotDepositor.into());
+        }
+        deposit_states.push(deposit_state);
+    }
+
+    let transfer_amount = burn_proof_of_life_cost(
+        &config,
+        depositor,
+        depositor_dlm_token_account,
+        burn_dlm_token_account,
+        dlm_mint_account,
+        token_program,
+        extra_accounts,
+    )?;
+
+    let clock = Clock::get()?;
+    for (deposit_account, deposit_state) in deposit_accounts.iter().zip(deposit_states.iter_mut()) {
+        if deposit_state.has_pending_claim() {
+            msg!("Pending claim by {} on {} voided", deposit_state.claim_initiator, deposit_account.key);
+        }
+        deposit_state.record_proof_of_life(clock.unix_timestamp);
+        deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+    }
+
+    msg!(
+        "Proof of life recorded on {} deposits at {} with {} tokens transferred to burn address",
+        deposit_states.len(),
+        clock.unix_timestamp,
+        transfer_amount
+    );
+    Ok(())
+}
+
+/// Process profile proof-of-life instruction
+///
+/// Only the profile's timestamp changes; linked deposits catch up with it when a
+/// claim loads them.
+fn process_profile_proof_of_life(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let profile_account = next_account_info(account_info_iter)?;
+    let depositor_dlm_token_account = next_account_info(account_info_iter)?;
+    let burn_dlm_token_account = next_account_info(account_info_iter)?;
+    let dlm_mint_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let extra_accounts = account_info_iter.as_slice();
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    let mut profile = load_profile(program_id, depositor.key, profile_account)?;
+
+    let transfer_amount = burn_proof_of_life_cost(
+        &config,
+        depositor,
+        depositor_dlm_token_account,
+        burn_dlm_token_account,
+        dlm_mint_account,
+        token_program,
+        extra_accounts,
+    )?;
+
+    profile.last_proof_timestamp = Clock::get()?.unix_timestamp;
+    profile.serialize(&mut &mut profile_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "Proof of life recorded on liveness profile at {} with {} tokens transferred to burn address",
+        profile.last_proof_timestamp,
+        transfer_amount
+    );
+    Ok(())
+}
+
+/// Process withdraw instruction
+fn process_withdraw(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,  // Use reference
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let depositor_token_account = next_account_info(account_info_iter)?;
+    let deposit_token_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let mint_account = next_account_info(account_info_iter)?;
+    let extra_accounts = account_info_iter.as_slice();
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+    assert_token_program(token_program)?;
+
+    // CRITICAL: Verify token account ownership and save mint for later validation
+    let token_mint = {
+        let token_account_state = unpack_token_account(depositor_token_account, token_program)?;
+        if token_account_state.owner != *depositor.key {
+            msg!("Token account must be owned by depositor");
+            return Err(DielemmaError::InvalidTokenAccountOwner.into());
+        }
+        token_account_state.mint
+    };
+
+    let mut deposit_state = load_withdrawable_deposit(program_id, depositor, deposit_account, deposit_seed)?;
+    assert_withdraw_unlocked(&deposit_state)?;
+
+    // Verify destination token account and mint account match deposit mint
+    if token_mint != deposit_state.token_mint || mint_account.key != &deposit_state.token_mint {
+        msg!("Destination token account mint does not match deposit mint");
+        msg!("Expected: {}", deposit_state.token_mint);
+        msg!("Got: {}", token_mint);
+        return Err(DielemmaError::MintMismatch.into());
+    }
+    let decimals = unpack_mint_decimals(mint_account, token_program)?;
+    let token_amount = vault_balance(deposit_token_account, token_program, &mut deposit_state)?;
+
+    // CRITICAL: Mark as closed BEFORE transfer to prevent race condition/double withdrawal
+    deposit_state.is_closed = true;
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    let balance_before = unpack_token_account(depositor_token_account, token_program)?.amount;
+
+    // Transfer tokens back to depositor (from deposit_token_account to depositor_token_account)
+    transfer_tokens(
+        token_program,
+        deposit_token_account,    // Source: deposit's token account
+        mint_account,
+        depositor_token_account,  // Destination: depositor's ATA
+        deposit_account,
+        extra_accounts,
+        token_amount,
+        decimals,
+        &[&[
+            DEPOSIT_SEED_PREFIX,
+            depositor.key.as_ref(),
+            deposit_seed.as_bytes(),
+            &[deposit_state.bump],
+        ]],
+    )?;
+
+    let received_amount = received_since(depositor_token_account, token_program, balance_before)?;
+
+    // Sweep the fee withheld on the emptied vault to the mint, if the mint was passed writable
+    if mint_account.is_writable {
+        harvest_vault_fees(token_program, mint_account, deposit_token_account)?;
+    }
+    msg!(
+        "Withdrawal successful: {} tokens sent, {} received after transfer fees",
+        token_amount,
+        received_amount
+    );
+    Ok(())
+}
+
+/// Process partial withdraw instruction
+fn process_partial_withdraw(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let depositor_token_account = next_account_info(account_info_iter)?;
+    let deposit_token_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let mint_account = next_account_info(account_info_iter)?;
+    let extra_accounts = account_info_iter.as_slice();
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+    assert_token_program(token_program)?;
+
+    let token_account_state = unpack_token_account(depositor_token_account, token_program)?;
+    if token_account_state.owner != *depositor.key {
+        msg!("Token account must be owned by depositor");
+        return Err(DielemmaError::InvalidTokenAccountOwner.into());
+    }
+
+    let mut deposit_state = load_withdrawable_deposit(program_id, depositor, deposit_account, deposit_seed)?;
+    if deposit_state.is_native() {
+        msg!("Deposit holds native SOL, use WithdrawSol");
+        return Err(DielemmaError::NotNativeDeposit.into());
+    }
+    assert_withdraw_unlocked(&deposit_state)?;
+
+    // Shares are computed from `amount`, so it cannot shrink once payouts have started
+    if deposit_state.claimed_amount > 0 {
+        msg!("Deposit has already been partially claimed");
+        return Err(DielemmaError::ShareAlreadyClaimed.into());
+    }
+
+    if amount == 0 || amount >= deposit_state.amount {
+        msg!(
+            "Partial withdrawal must be between 1 and {} tokens, use Withdraw to empty the deposit",
+            deposit_state.amount.saturating_sub(1)
+        );
+        return Err(DielemmaError::InvalidAmount.into());
+    }
+
+    if token_account_state.mint != deposit_state.token_mint || mint_account.key != &deposit_state.token_mint {
+        msg!("Destination token account mint does not match deposit mint");
+        return Err(DielemmaError::MintMismatch.into());
+    }
+    let decimals = unpack_mint_decimals(mint_account, token_program)?;
+
+    // Update the recorded amount before the transfer, as Withdraw closes the deposit first
+    deposit_state.amount -= amount;
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    let balance_before = token_account_state.amount;
+
+    transfer_tokens(
+        token_program,
+        deposit_token_account,
+        mint_account,
+        depositor_token_account,
+        deposit_account,
+        extra_accounts,
+        amount,
+        decimals,
+        &[&[
+            DEPOSIT_SEED_PREFIX,
+            depositor.key.as_ref(),
+            deposit_seed.as_bytes(),
+            &[deposit_state.bump],
+        ]],
+    )?;
+
+    let received_amount = received_since(depositor_token_account, token_program, balance_before)?;
+    msg!(
+        "Partial withdrawal successful: {} tokens sent, {} received after transfer fees, {} remain deposited",
+        amount,
+        received_amount,
+        deposit_state.amount
+    );
+    Ok(())
+}
+
+/// Process claim instruction
+fn process_claim(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,  // Use reference
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let receiver = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let receiver_token_account = next_account_info(account_info_iter)?;
+    let deposit_token_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let mint_account = next_account_info(account_info_iter)?;
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+    assert_token_program(token_program)?;
+
+    // CRITICAL: Verify token account ownership and save mint for later validation
+    let token_mint = {
+        let token_account_state = unpack_token_account(receiver_token_account, token_program)?;
+        if token_account_state.owner != *receiver.key {
+            msg!("Token account must be owned by receiver");
+            return Err(DielemmaError::InvalidTokenAccountOwner.into());
+        }
+        token_account_state.mint
+    };
+
+    let (mut deposit_state, index) =
+        load_claimable_deposit(program_id, receiver, deposit_account, deposit_seed, account_info_iter)?;
+    assert_claim_finalizable(&deposit_state)?;
+    let index = index.ok_or(DielemmaError::ClaimNotInitiated)?;
+    let extra_accounts = account_info_iter.as_slice();
+
+    // Verify destination token account and mint account match deposit mint
+    if token_mint != deposit_state.token_mint || mint_account.key != &deposit_state.token_mint {
+        msg!("Destination token account mint does not match deposit mint");
+        msg!("Expected: {}", deposit_state.token_mint);
+        msg!("Got: {}", token_mint);
+        return Err(DielemmaError::MintMismatch.into());
+    }
+    let decimals = unpack_mint_decimals(mint_account, token_program)?;
+
+    // Receiver's vested share of the current token balance
+    let now = Clock::get()?.unix_timestamp;
+    deposit_state.start_vesting(now);
+    let vault_amount = vault_balance(deposit_token_account, token_program, &mut deposit_state)?;
+    let token_amount = deposit_state.share_amount(index, vault_amount, now);
+    assert_something_vested(&deposit_state, token_amount, now)?;
+    let balance_before = unpack_token_account(receiver_token_account, token_program)?.amount;
+
+    // CRITICAL: Record the claim BEFORE transfer to prevent race condition/double claim
+    deposit_state.record_claim(index, token_amount, now);
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    // Transfer tokens to receiver (from deposit_token_account to receiver_token_account)
+    transfer_tokens(
+        token_program,
+        deposit_token_account,   // Source: deposit's token account
+        mint_account,
+        receiver_token_account,  // Destination: receiver's ATA
+        deposit_account,
+        extra_accounts,
+        token_amount,
+        decimals,
+        &[&[
+            DEPOSIT_SEED_PREFIX,
+            deposit_state.depositor.as_ref(),
+            deposit_seed.as_bytes(),
+            &[deposit_state.bump],
+        ]],
+    )?;
+
+    let received_amount = received_since(receiver_token_account, token_program, balance_before)?;
+
+    // Sweep the fee withheld on the emptied vault to the mint, if the mint was passed writable
+    if deposit_state.is_closed && mint_account.is_writable {
+        harvest_vault_fees(token_program, mint_account, deposit_token_account)?;
+    }
+    msg!(
+        "Claim successful: {} tokens sent, {} received by receiver after transfer fees",
+        token_amount,
+        received_amount
+    );
+    Ok(())
+}
+
+/// Process close account instruction
+fn process_close_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,  // Use reference
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let refund_recipient = next_account_info(account_info_iter)?;
+    let _system_program = next_account_info(account_info_iter)?;
+
+    // Deserialize deposit account
+    let deposit_state = unpack_deposit(deposit_account)?;
+
+    // Derive PDA
+    let (deposit_pda, _bump) = Pubkey::find_program_address(
+        &[DEPOSIT_SEED_PREFIX, deposit_state.depositor.as_ref(), deposit_seed.as_bytes()],
+        program_id,
+    );
+
+    if deposit_account.key != &deposit_pda {
+        msg!("Invalid deposit account PDA");
+        return Err(DielemmaError::InvalidDepositAccount.into());
+    }
+
+    // Verify authority (must be depositor or a receiver)
+    if deposit_state.depositor != *authority.key && deposit_state.beneficiary_index(authority.key).is_none() {
+        msg!("Only depositor or receiver can close the account");
+        return Err(DielemmaError::Unauthorized.into());
+    }
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        msg!("Authority must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Check if tokens have been withdrawn/claimed
+    if !deposit_state.is_closed {
+        msg!("Cannot close account with active tokens");
+        return Err(DielemmaError::DepositStillActive.into());
+    }
+
+    // Close account and transfer lamports
+    let close_lamports = deposit_account.lamports();
+    **deposit_account.lamports.borrow_mut() = 0;
+    **refund_recipient.lamports.borrow_mut() += close_lamports;
+
+    msg!("Account closed, {} lamports refunded", close_lamports);
+    Ok(())
+}
+
+/// Process deposit SOL instruction
+fn process_deposit_sol(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+    receiver: &Pubkey,
+    amount: u64,
+    timeout_seconds: u64,
+    withdraw_locked_until: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    // Verify system program
+    if system_program.key != &system_program::id() {
+        msg!("Invalid system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    validate_deposit_terms(amount, timeout_seconds)?;
+
+    // Derive PDA for deposit account (using client-provided seed)
+    let (deposit_pda, bump) = Pubkey::find_program_address(
+        &[DEPOSIT_SEED_PREFIX, depositor.key.as_ref(), deposit_seed.as_bytes()],
+        program_id,
+    );
+
+    if deposit_account.key != &deposit_pda {
+        msg!("Invalid deposit account PDA");
+        return Err(DielemmaError::InvalidDepositAccount.into());
+    }
+
+    // Check if deposit account already exists
+    if deposit_account.lamports() > 0 {
+        msg!("Deposit account already exists");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // The deposit account holds the deposited lamports on top of its rent exemption
+    let rent = Rent::get()?;
+    let required_lamports = rent
+        .minimum_balance(DEPOSIT_ACCOUNT_SIZE)
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    let create_deposit_account_ix = system_instruction::create_account(
+        depositor.key,
+        deposit_account.key,
+        required_lamports,
+        DEPOSIT_ACCOUNT_SIZE as u64,
+        program_id,
+    );
+
+    invoke_signed(
+        &create_deposit_account_ix,
+        &[
+            depositor.clone(),
+            deposit_account.clone(),
+            system_program.clone(),
+        ],
+        &[&[
+            DEPOSIT_SEED_PREFIX,
+            depositor.key.as_ref(),
+            deposit_seed.as_bytes(),
+            &[bump],
+        ]],
+    )?;
+
+    let clock = Clock::get()?;
+    let (seed_len, seed_array) = pack_deposit_seed(deposit_seed);
+    let (beneficiary_count, beneficiaries) = build_beneficiaries(&[(*receiver, TOTAL_SHARE_BPS)])?;
+
+    let deposit_state = DepositAccount {
+        depositor: *depositor.key,
+        beneficiary_count,
+        beneficiaries,
+        token_mint: NATIVE_SOL_MINT,
+        amount,
+        claimed_amount: 0,
+        last_proof_timestamp: clock.unix_timestamp,
+        timeout_seconds,
+        pending_timeout_seconds: 0,
+        pending_timeout_effective_at: 0,
+        claim_initiator: Pubkey::default(),
+        claim_finalizable_at: 0,
+        delegate_count: 0,
+        delegates: [Delegate::default(); MAX_DELEGATES],
+        heartbeat_nonce: 0,
+        profile_linked: false,
+        guardian_count: 0,
+        guardians: [Pubkey::default(); MAX_GUARDIANS],
+        guardian_threshold: 0,
+        death_attestations: 0,
+        death_confirmed_at: 0,
+        vesting_seconds: 0,
+        vesting_started_at: 0,
+        tranche_count: 0,
+        tranches: [ReleaseTranche::default(); MAX_RELEASE_TRANCHES],
+        release_at: 0,
+        release_at_only: false,
+        withdraw_locked_until,
+        fallback_receiver: Pubkey::default(),
+        fallback_chain_len: 0,
+        fallback_chain: [FallbackReceiver::default(); MAX_FALLBACK_RECEIVERS],
+        bump,
+        is_closed: false,
+        deposit_seed_len: seed_len,
+        deposit_seed: seed_array,
+        layout_version: DEPOSIT_LAYOUT_VERSION,
+    };
+
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    msg!("SOL deposit successful: {} lamports to receiver {}", amount, receiver);
+    Ok(())
+}
+
+/// Process withdraw SOL instruction
+fn process_withdraw_sol(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    let mut deposit_state = load_withdrawable_deposit(program_id, depositor, deposit_account, deposit_seed)?;
+    if !deposit_state.is_native() {
+        msg!("Deposit holds tokens, use Withdraw");
+        return Err(DielemmaError::NotNativeDeposit.into());
+    }
+    assert_withdraw_unlocked(&deposit_state)?;
+
+    deposit_state.is_closed = true;
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    // Rent exemption stays in the deposit account until CloseAccount
+    let lamports = deposit_state.remaining_amount();
+    transfer_lamports(deposit_account, depositor, lamports)?;
+
+    msg!("SOL withdrawal successful: {} lamports", lamports);
+    Ok(())
+}
+
+/// Process claim SOL instruction
+fn process_claim_sol(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let receiver = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    let (mut deposit_state, index) =
+        load_claimable_deposit(program_id, receiver, deposit_account, deposit_seed, account_info_iter)?;
+    assert_claim_finalizable(&deposit_state)?;
+    let index = index.ok_or(DielemmaError::ClaimNotInitiated)?;
+    let now = Clock::get()?.unix_timestamp;
+    deposit_state.start_vesting(now);
+
+    if deposit_state.is_native() {
+        let lamports = deposit_state.share_amount(index, deposit_state.remaining_amount(), now);
+        assert_something_vested(&deposit_state, lamports, now)?;
+        deposit_state.record_claim(index, lamports, now);
+        deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+        transfer_lamports(deposit_account, receiver, lamports)?;
+        msg!("SOL claim successful: {} lamports transferred to receiver", lamports);
+        return Ok(());
+    }
+
+    // wSOL deposit: closing the vault unwraps its lamports for the receiver
+    if deposit_state.token_mint != spl_token::native_mint::id()
+        && deposit_state.token_mint != spl_token_2022::native_mint::id()
+    {
+        msg!("Deposit holds neither SOL nor wSOL, use Claim");
+        return Err(DielemmaError::NotNativeDeposit.into());
+    }
+
+    let deposit_token_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    assert_token_program(token_program)?;
+
+    let (token_account_pda, _token_bump) = Pubkey::find_program_address(
+        &[TOKEN_ACCOUNT_SEED_PREFIX, deposit_account.key.as_ref()],
+        program_id,
+    );
+    if deposit_token_account.key != &token_account_pda {
+        msg!("Invalid token account PDA");
+        return Err(DielemmaError::InvalidVaultAccount.into());
+    }
+
+    // Closing the vault pays out everything, so only the last share can be unwrapped
+    if deposit_state.unclaimed_count() > 1 || !deposit_state.fully_allocated() {
+        msg!("Other beneficiaries or the depositor are owed part of the vault, use Claim");
+        return Err(DielemmaError::SharesOutstanding.into());
+    }
+    if deposit_state.unlocked_bps(now) < TOTAL_SHARE_BPS {
+        msg!("Deposit is not fully unlocked yet, use Claim");
+        return Err(DielemmaError::InvalidTranches.into());
+    }
+    if !deposit_state.fully_vested(now) {
+        msg!("Deposit is still vesting, use Claim");
+        return Err(DielemmaError::InvalidVesting.into());
+    }
+
+    let token_amount = vault_balance(deposit_token_account, token_program, &mut deposit_state)?;
+    deposit_state.record_claim(index, token_amount, now);
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    // Close the vault into the deposit account, so the vault's rent stays with the deposit
+    // for CloseAccount to refund, and pass the unwrapped lamports on to the receiver
+    let close_vault_ix = close_account(
+        token_program.key,
+        deposit_token_account.key,
+        deposit_account.key,
+        deposit_account.key,
+        &[],
+    )?;
+
+    invoke_signed(
+        &close_vault_ix,
+        &[
+            deposit_token_account.clone(),
+            deposit_account.clone(),
+            token_program.clone(),
+        ],
+        &[&[
+            DEPOSIT_SEED_PREFIX,
+            deposit_state.depositor.as_ref(),
+            deposit_seed.as_bytes(),
+            &[deposit_state.bump],
+        ]],
+    )?;
+
+    transfer_lamports(deposit_account, receiver, token_amount)?;
+
+    msg!("wSOL claim successful: {} lamports unwrapped and transferred to receiver", token_amount);
+    Ok(())
+}
+
+/// Process set beneficiaries instruction
+fn process_set_beneficiaries(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+    shares: &[(Pubkey, u16)],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    let mut deposit_state = load_withdrawable_deposit(program_id, depositor, deposit_account, deposit_seed)?;
+    assert_withdraw_unlocked(&deposit_state)?;
+
+    // Shares are fractions of the whole deposit, so they cannot change once payouts started
+    if deposit_state.claimed_amount > 0 || deposit_state.beneficiaries().iter().any(|b| b.claimed) {
+        msg!("A share of this deposit has already been claimed");
+        return Err(DielemmaError::ShareAlreadyClaimed.into());
+    }
+
+    let (beneficiary_count, beneficiaries) = match build_beneficiaries(shares) {
+        Ok(beneficiaries) => beneficiaries,
+        Err(e) => {
+            msg!("Beneficiaries must be 1 to {} distinct receivers with shares adding up to {} bps", MAX_BENEFICIARIES, TOTAL_SHARE_BPS);
+            return Err(e.into());
+        }
+    };
+    deposit_state.beneficiary_count = beneficiary_count;
+    deposit_state.beneficiaries = beneficiaries;
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    for beneficiary in deposit_state.beneficiaries() {
+        msg!("Beneficiary {}: {} bps", beneficiary.receiver, beneficiary.share_bps);
+    }
+    Ok(())
+}
+
+/// Process update receiver instruction
+fn process_update_receiver(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+    old_receiver: &Pubkey,
+    new_receiver: &Pubkey,
+    reset_timer: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    let mut deposit_state = load_withdrawable_deposit(program_id, depositor, deposit_account, deposit_seed)?;
+    assert_withdraw_unlocked(&deposit_state)?;
+
+    let index = match deposit_state.beneficiary_index(old_receiver) {
+        Some(index) => index,
+        None => {
+            msg!("{} is not a receiver of this deposit", old_receiver);
+            return Err(DielemmaError::NotReceiver.into());
+        }
+    };
+    if deposit_state.beneficiaries[index].claimed {
+        msg!("Receiver has already claimed their share");
+        return Err(DielemmaError::ShareAlreadyClaimed.into());
+    }
+    if deposit_state.beneficiary_index(new_receiver).is_some() {
+        msg!("{} is already a receiver of this deposit", new_receiver);
+        return Err(DielemmaError::InvalidBeneficiaries.into());
+    }
+
+    deposit_state.beneficiaries[index].receiver = *new_receiver;
+    if reset_timer {
+        deposit_state.record_proof_of_life(Clock::get()?.unix_timestamp);
+    }
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    msg!("Receiver updated: {} -> {}", old_receiver, new_receiver);
+    if reset_timer {
+        msg!("Proof of life recorded at {}", deposit_state.last_proof_timestamp);
+    }
+    Ok(())
+}
+
+/// Process update timeout instruction
+///
+/// A reduction only takes effect once the current timeout has elapsed, so a
+/// compromised depositor key cannot make the deposit claimable straight away.
+fn process_update_timeout(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+    timeout_seconds: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    validate_timeout(timeout_seconds)?;
+
+    let mut deposit_state = load_withdrawable_deposit(program_id, depositor, deposit_account, deposit_seed)?;
+    assert_withdraw_unlocked(&deposit_state)?;
+
+    if deposit_state.profile_linked {
+        let profile_account = next_account_info(account_info_iter)?;
+        let profile = load_profile(program_id, depositor.key, profile_account)?;
+        deposit_state.sync_profile(&profile);
+    }
+
+    // A longer timeout must not revive an expired deposit without a proof of life
+    let now = Clock::get()?.unix_timestamp;
+    if deposit_state.expired(now) || deposit_state.has_pending_claim() {
+        msg!("Proof of life has expired or a claim is pending, prove life first");
+        return Err(DielemmaError::DepositExpired.into());
+    }
+    let current_timeout = deposit_state.timeout_at(now);
+
+    if timeout_seconds >= current_timeout {
+        deposit_state.timeout_seconds = timeout_seconds;
+        deposit_state.pending_timeout_seconds = 0;
+        deposit_state.pending_timeout_effective_at = 0;
+        msg!("Timeout updated: {} -> {} seconds", current_timeout, timeout_seconds);
+    } else {
+        let effective_at = now
+            .checked_add(current_timeout as i64)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        deposit_state.timeout_seconds = current_timeout;
+        deposit_state.pending_timeout_seconds = timeout_seconds;
+        deposit_state.pending_timeout_effective_at = effective_at;
+        msg!(
+            "Timeout reduction scheduled: {} -> {} seconds at {}",
+            current_timeout,
+            timeout_seconds,
+            effective_at
+        );
+    }
+
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+/// Process top up instruction
+fn process_top_up(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let depositor_token_account = next_account_info(account_info_iter)?;
+    let deposit_token_account = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let extra_accounts = account_info_iter.as_slice();
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+    assert_token_program(token_program)?;
+
+    let mut deposit_state = load_withdrawable_deposit(program_id, depositor, deposit_account, deposit_seed)?;
+    if deposit_state.is_native() {
+        msg!("Deposit holds native SOL, which cannot be topped up");
+        return Err(DielemmaError::NotNativeDeposit.into());
+    }
+
+    // Shares are computed from `amount`, so it cannot grow once payouts have started
+    if deposit_state.claimed_amount > 0 {
+        msg!("Deposit has already been partially claimed");
+        return Err(DielemmaError::ShareAlreadyClaimed.into());
+    }
+
+    if amount == 0 {
+        msg!("Top-up amount must be greater than 0");
+        return Err(DielemmaError::InvalidAmount.into());
+    }
+    match deposit_state.amount.checked_add(amount) {
+        Some(total) if total <= MAX_DEPOSIT_AMOUNT => {}
+        _ => {
+            msg!("Deposit amount exceeds maximum allowed");
+            return Err(DielemmaError::InvalidAmount.into());
+        }
+    }
+
+    // Verify the source token account and the mint match the deposit
+    let token_account_state = unpack_token_account(depositor_token_account, token_program)?;
+    if token_account_state.owner != *depositor.key {
+        msg!("Token account must be owned by depositor");
+        return Err(DielemmaError::InvalidTokenAccountOwner.into());
+    }
+    if token_account_state.mint != deposit_state.token_mint || token_mint.key != &deposit_state.token_mint {
+        msg!("Token account mint does not match deposit mint");
+        return Err(DielemmaError::MintMismatch.into());
+    }
+    let decimals = unpack_mint_decimals(token_mint, token_program)?;
+
+    // Verify the vault belongs to this deposit
+    let (token_account_pda, _token_bump) =
+        Pubkey::find_program_address(&[TOKEN_ACCOUNT_SEED_PREFIX, deposit_account.key.as_ref()], program_id);
+    if deposit_token_account.key != &token_account_pda {
+        msg!("Invalid token account PDA");
+        return Err(DielemmaError::InvalidVaultAccount.into());
+    }
+
+    let balance_before = unpack_token_account(deposit_token_account, token_program)?.amount;
+
+    transfer_tokens(
+        token_program,
+        depositor_token_account,
+        token_mint,
+        deposit_token_account,
+        depositor,
+        extra_accounts,
+        amount,
+        decimals,
+        &[],
+    )?;
+
+    // Record the net amount received, as for the initial deposit
+    let received_amount = received_since(deposit_token_account, token_program, balance_before)?;
+    if received_amount == 0 {
+        msg!("Top-up amount is entirely consumed by the transfer fee");
+        return Err(DielemmaError::InvalidAmount.into());
+    }
+    deposit_state.amount = deposit_state
+        .amount
+        .checked_add(received_amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "Top-up successful: {} tokens sent, {} received after transfer fees, deposit now holds {}",
+        amount,
+        received_amount,
+        deposit_state.amount
+    );
+    Ok(())
+}
+
+/// Process initiate claim instruction
+fn process_initiate_claim(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let receiver = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    let (mut deposit_state, _index) =
+        load_claimable_deposit(program_id, receiver, deposit_account, deposit_seed, account_info_iter)?;
+
+    if deposit_state.has_pending_claim() {
+        msg!("Claim already initiated by {}", deposit_state.claim_initiator);
+        return Err(DielemmaError::ClaimAlreadyInitiated.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    deposit_state.claim_initiator = *receiver.key;
+    deposit_state.claim_finalizable_at = now
+        .checked_add(config.claim_challenge_seconds as i64)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "Claim initiated by {}, can be finalized at {}",
+        receiver.key,
+        deposit_state.claim_finalizable_at
+    );
+    Ok(())
+}
+
+/// Process cancel claim instruction
+///
+/// Not subject to the pause switch: it only protects the depositor. The deposit stays
+/// expired, so a ProofOfLife is needed to stop a new claim from being initiated. A
+/// withdraw-locked deposit can only void claims through ProofOfLife, as cancelling for
+/// free again and again would keep the receivers from it indefinitely.
+fn process_cancel_claim(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut deposit_state = load_withdrawable_deposit(program_id, depositor, deposit_account, deposit_seed)?;
+    assert_withdraw_unlocked(&deposit_state)?;
+
+    if !deposit_state.has_pending_claim() {
+        msg!("No claim to cancel");
+        return Err(DielemmaError::ClaimNotInitiated.into());
+    }
+
+    let initiator = deposit_state.claim_initiator;
+    deposit_state.clear_pending_claim();
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    msg!("Claim by {} cancelled by depositor", initiator);
+    Ok(())
+}
+
+/// Process add delegate instruction
+fn process_add_delegate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+    delegate: &Pubkey,
+    expires_at: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    let mut deposit_state = load_withdrawable_deposit(program_id, depositor, deposit_account, deposit_seed)?;
+
+    if delegate == depositor.key {
+        msg!("The depositor cannot be their own delegate");
+        return Err(DielemmaError::InvalidDelegate.into());
+    }
+    if expires_at != 0 && expires_at <= Clock::get()?.unix_timestamp {
+        msg!("Delegate expiry must be in the future");
+        return Err(DielemmaError::InvalidDelegate.into());
+    }
+
+    let index = match deposit_state.delegate_index(delegate) {
+        Some(index) => index,
+        None if (deposit_state.delegate_count as usize) < MAX_DELEGATES => {
+            deposit_state.delegate_count += 1;
+            deposit_state.delegate_count as usize - 1
+        }
+        None => {
+            msg!("A deposit can have at most {} delegates", MAX_DELEGATES);
+            return Err(DielemmaError::InvalidDelegate.into());
+        }
+    };
+    deposit_state.delegates[index] = Delegate {
+        key: *delegate,
+        expires_at,
+    };
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    msg!("Delegate {} added, expires at {}", delegate, expires_at);
+    Ok(())
+}
+
+/// Process revoke delegate instruction
+fn process_revoke_delegate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+    delegate: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    let mut deposit_state = load_withdrawable_deposit(program_id, depositor, deposit_account, deposit_seed)?;
+
+    let index = match deposit_state.delegate_index(delegate) {
+        Some(index) => index,
+        None => {
+            msg!("{} is not a delegate of this deposit", delegate);
+            return Err(DielemmaError::InvalidDelegate.into());
+        }
+    };
+
+    // Move the last delegate into the freed slot
+    let last = deposit_state.delegate_count as usize - 1;
+    deposit_state.delegates[index] = deposit_state.delegates[last];
+    deposit_state.delegates[last] = Delegate::default();
+    deposit_state.delegate_count -= 1;
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    msg!("Delegate {} revoked", delegate);
+    Ok(())
+}
+
+/// Process set guardians instruction
+fn process_set_guardians(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+    threshold: u8,
+    guardians: &[Pubkey],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    let mut deposit_state = load_withdrawable_deposit(program_id, depositor, deposit_account, deposit_seed)?;
+
+    let valid_threshold = if guardians.is_empty() {
+        threshold == 0
+    } else {
+        threshold >= 1 && threshold as usize <= guardians.len()
+    };
+    let valid_keys = guardians
+        .iter()
+        .enumerate()
+        .all(|(i, guardian)| guardian != depositor.key && !guardians[..i].contains(guardian));
+    if !valid_threshold || !valid_keys {
+        msg!(
+            "Guardians must be up to {} distinct keys other than the depositor, with a threshold between 1 and their number",
+            MAX_GUARDIANS
+        );
+        return Err(DielemmaError::InvalidGuardians.into());
+    }
+
+    deposit_state.guardian_count = guardians.len() as u8;
+    deposit_state.guardians = [Pubkey::default(); MAX_GUARDIANS];
+    deposit_state.guardians[..guardians.len()].copy_from_slice(guardians);
+    deposit_state.guardian_threshold = threshold;
+    deposit_state.clear_death_attestations();
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    msg!("Guardians set: {} of {}", threshold, guardians.len());
+    Ok(())
+}
+
+/// Process attest death instruction
+fn process_attest_death(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let guardian = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Verify guardian is signer
+    if !guardian.is_signer {
+        msg!("Guardian must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    let mut deposit_state = load_proof_of_life_deposit(program_id, deposit_account, deposit_seed)?;
+
+    // Catch up with the profile first, so its proof of life only voids earlier attestations
+    if deposit_state.profile_linked {
+        let profile_account = next_account_info(account_info_iter)?;
+        let profile = load_profile(program_id, &deposit_state.depositor, profile_account)?;
+        deposit_state.sync_profile(&profile);
+    }
+
+    let index = match deposit_state.guardian_index(guardian.key) {
+        Some(index) => index,
+        None => {
+            msg!("Only a guardian of this deposit can attest death");
+            return Err(DielemmaError::NotGuardian.into());
+        }
+    };
+    if deposit_state.death_attestations & (1 << index) != 0 {
+        msg!("Guardian {} has already attested", guardian.key);
+        return Err(DielemmaError::AlreadyAttested.into());
+    }
+
+    deposit_state.death_attestations |= 1 << index;
+    let attestations = deposit_state.attestation_count();
+    if attestations >= deposit_state.guardian_threshold && deposit_state.death_confirmed_at == 0 {
+        deposit_state.death_confirmed_at = Clock::get()?.unix_timestamp;
+        msg!(
+            "Death confirmed by {} guardians, claimable from {} unless the depositor proves life",
+            attestations,
+            deposit_state.death_confirmed_at.saturating_add(GUARDIAN_GRACE_SECONDS as i64)
+        );
+    }
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    msg!("Death attested by guardian {} ({} of {})", guardian.key, attestations, deposit_state.guardian_threshold);
+    Ok(())
+}
+
+/// Process set vesting instruction
+fn process_set_vesting(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+    vesting_seconds: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    let mut deposit_state = load_withdrawable_deposit(program_id, depositor, deposit_account, deposit_seed)?;
+    assert_withdraw_unlocked(&deposit_state)?;
+
+    // Receivers were promised the schedule in force when they started claiming
+    if deposit_state.claimed_amount > 0 || deposit_state.vesting_started_at != 0 {
+        msg!("A share of this deposit has already been claimed");
+        return Err(DielemmaError::ShareAlreadyClaimed.into());
+    }
+    if vesting_seconds > MAX_VESTING_SECONDS {
+        msg!("Vesting duration must be at most {} seconds", MAX_VESTING_SECONDS);
+        return Err(DielemmaError::InvalidVesting.into());
+    }
+
+    deposit_state.vesting_seconds = vesting_seconds;
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    msg!("Vesting set to {} seconds", vesting_seconds);
+    Ok(())
+}
+
+/// Process set release tranches instruction
+fn process_set_release_tranches(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+    tranches: &[(u8, u16)],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    let mut deposit_state = load_withdrawable_deposit(program_id, depositor, deposit_account, deposit_seed)?;
+    assert_withdraw_unlocked(&deposit_state)?;
+
+    // Receivers were promised the schedule in force when they started claiming
+    if deposit_state.claimed_amount > 0 {
+        msg!("A share of this deposit has already been claimed");
+        return Err(DielemmaError::ShareAlreadyClaimed.into());
+    }
+
+    let (tranche_count, schedule) = match build_tranches(tranches) {
+        Ok(schedule) => schedule,
+        Err(e) => {
+            msg!(
+                "Tranches must be up to {} increasing timeout multiples with increasing shares ending at {} bps",
+                MAX_RELEASE_TRANCHES,
+                TOTAL_SHARE_BPS
+            );
+            return Err(e.into());
+        }
+    };
+    deposit_state.tranche_count = tranche_count;
+    deposit_state.tranches = schedule;
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    for tranche in deposit_state.tranches() {
+        msg!("Tranche: {} bps after {}x timeout", tranche.unlocked_bps, tranche.timeout_multiple);
+    }
+    Ok(())
+}
+
+/// Process set release date instruction
+fn process_set_release_date(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+    release_at: i64,
+    release_at_only: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    let mut deposit_state = load_withdrawable_deposit(program_id, depositor, deposit_account, deposit_seed)?;
+    assert_withdraw_unlocked(&deposit_state)?;
+
+    // Receivers were promised the release terms in force when they started claiming
+    if deposit_state.claimed_amount > 0 {
+        msg!("A share of this deposit has already been claimed");
+        return Err(DielemmaError::ShareAlreadyClaimed.into());
+    }
+
+    // A deposit ignoring expiry without a release date could never be claimed
+    let now = Clock::get()?.unix_timestamp;
+    let valid = if release_at == 0 { !release_at_only } else { release_at > now };
+    if !valid {
+        msg!("Release date must be in the future, and is required when proof-of-life expiry is ignored");
+        return Err(DielemmaError::InvalidReleaseDate.into());
+    }
+
+    deposit_state.release_at = release_at;
+    deposit_state.release_at_only = release_at_only;
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    if release_at == 0 {
+        msg!("Release date removed");
+    } else if release_at_only {
+        msg!("Release date set to {}, proof-of-life expiry ignored", release_at);
+    } else {
+        msg!("Release date set to {}", release_at);
+    }
+    Ok(())
+}
+
+/// Process set fallback receiver instruction
+fn process_set_fallback_receiver(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+    fallback_receiver: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
 
-    // Verify rent sysvar
-    if rent_account.key != &Rent::id() {
-        msg!("Invalid rent sysvar");
-        return Err(ProgramError::InvalidAccountData);
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    let mut deposit_state = load_withdrawable_deposit(program_id, depositor, deposit_account, deposit_seed)?;
+    assert_withdraw_unlocked(&deposit_state)?;
+
+    if fallback_receiver == depositor.key || deposit_state.beneficiary_index(fallback_receiver).is_some() {
+        msg!("Fallback receiver must be neither the depositor nor a current receiver");
+        return Err(DielemmaError::InvalidBeneficiaries.into());
     }
 
-    // Verify token program - accept both legacy Token and Token-2022
-    let is_token2022 = token_program.key == &spl_token_2022::id();
-    let is_legacy_token = token_program.key == &spl_token::id();
-    if !is_token2022 && !is_legacy_token {
-        msg!("Invalid token program");
-        return Err(ProgramError::IncorrectProgramId);
+    deposit_state.fallback_receiver = *fallback_receiver;
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    if *fallback_receiver == Pubkey::default() {
+        msg!("Fallback receiver removed");
+    } else {
+        msg!("Fallback receiver set to {}", fallback_receiver);
     }
+    Ok(())
+}
 
-    // Validate deposit amount
-    const MAX_DEPOSIT_AMOUNT: u64 = 100_000_000_000_000_000; // 100 million DLM (adjust as needed)
-    if amount == 0 {
-        msg!("Deposit amount must be greater than 0");
-        return Err(ProgramError::InvalidInstructionData);
+/// Process renounce instruction
+fn process_renounce(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let receiver = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Verify receiver is signer
+    if !receiver.is_signer {
+        msg!("Receiver must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
     }
 
-    if amount > MAX_DEPOSIT_AMOUNT {
-        msg!("Deposit amount exceeds maximum allowed");
-        return Err(ProgramError::InvalidInstructionData);
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    let mut deposit_state = load_proof_of_life_deposit(program_id, deposit_account, deposit_seed)?;
+
+    let index = match deposit_state.beneficiary_index(receiver.key) {
+        Some(index) => index,
+        None => {
+            msg!("Only a designated receiver can renounce");
+            return Err(DielemmaError::NotReceiver.into());
+        }
+    };
+    if deposit_state.beneficiaries[index].claimed {
+        msg!("Receiver has already claimed their share");
+        return Err(DielemmaError::ShareAlreadyClaimed.into());
     }
 
-    // Validate timeout range (1 minute to 10 years)
-    const MIN_TIMEOUT_SECONDS: u64 = 60; // 1 minute
-    const MAX_TIMEOUT_SECONDS: u64 = 315360000; // 10 years
-    if timeout_seconds < MIN_TIMEOUT_SECONDS || timeout_seconds > MAX_TIMEOUT_SECONDS {
-        msg!("Timeout must be between {} and {} seconds", MIN_TIMEOUT_SECONDS, MAX_TIMEOUT_SECONDS);
-        return Err(ProgramError::InvalidInstructionData);
+    // A claim initiated by the renouncing receiver no longer stands
+    if deposit_state.claim_initiator == *receiver.key {
+        deposit_state.clear_pending_claim();
     }
 
-    // Verify token account ownership
-    let token_account_data = depositor_token_account.data.borrow();
-    let token_account_state = TokenAccount::unpack(&token_account_data)
-        .map_err(|_| ProgramError::InvalidAccountData)?;
-    if token_account_state.owner != *depositor.key {
-        msg!("Token account must be owned by depositor");
-        return Err(ProgramError::InvalidAccountData);
+    let fallback_receiver = deposit_state.fallback_receiver;
+    let share_bps = deposit_state.beneficiaries[index].share_bps;
+    if fallback_receiver != Pubkey::default() && deposit_state.beneficiary_index(&fallback_receiver).is_none() {
+        deposit_state.beneficiaries[index].receiver = fallback_receiver;
+        deposit_state.fallback_receiver = Pubkey::default();
+        msg!("Receiver {} renounced {} bps, passed to fallback receiver {}", receiver.key, share_bps, fallback_receiver);
+    } else {
+        deposit_state.remove_beneficiary(index);
+        if !deposit_state.withdraw_locked(Clock::get()?.unix_timestamp) {
+            msg!("Receiver {} renounced {} bps, left to the depositor", receiver.key, share_bps);
+        } else if deposit_state.spread_share(share_bps) {
+            // The depositor cannot withdraw the share, so the other receivers take it over
+            msg!("Receiver {} renounced {} bps, split among the remaining receivers", receiver.key, share_bps);
+        } else if deposit_state.fallback_chain_len == 0 || deposit_state.claimed_amount > 0 {
+            // With nobody left to pay out to, the withdrawal lock would strand the deposit
+            deposit_state.withdraw_locked_until = 0;
+            msg!("Receiver {} renounced {} bps, withdrawal lock lifted", receiver.key, share_bps);
+        } else {
+            msg!("Receiver {} renounced {} bps, left to the fallback chain", receiver.key, share_bps);
+        }
     }
-    drop(token_account_data); // Explicitly drop the borrow before we need to borrow again
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+    Ok(())
+}
 
-    // Get clock for timestamp
-    let clock = Clock::get()?;
+/// Process set fallback chain instruction
+fn process_set_fallback_chain(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+    fallbacks: &[(Pubkey, u64)],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
 
-    // Derive PDA for deposit account (using client-provided seed)
-    let (deposit_pda, bump) = Pubkey::find_program_address(
-        &[DEPOSIT_SEED_PREFIX, depositor.key.as_ref(), deposit_seed.as_bytes()],
+    let depositor = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    let mut deposit_state = load_withdrawable_deposit(program_id, depositor, deposit_account, deposit_seed)?;
+    assert_withdraw_unlocked(&deposit_state)?;
+
+    // Receivers were promised the order in force when claims started
+    if deposit_state.claimed_amount > 0 {
+        msg!("A share of this deposit has already been claimed");
+        return Err(DielemmaError::ShareAlreadyClaimed.into());
+    }
+
+    if fallbacks
+        .iter()
+        .any(|(receiver, _)| receiver == depositor.key || deposit_state.beneficiary_index(receiver).is_some())
+    {
+        msg!("Fallback receivers must be neither the depositor nor a current receiver");
+        return Err(DielemmaError::InvalidFallbackChain.into());
+    }
+    let (fallback_chain_len, fallback_chain) = match build_fallback_chain(fallbacks) {
+        Ok(chain) => chain,
+        Err(e) => {
+            msg!(
+                "Fallback chain must be up to {} distinct receivers with increasing grace periods of 1 to {} seconds",
+                MAX_FALLBACK_RECEIVERS,
+                MAX_TIMEOUT_SECONDS
+            );
+            return Err(e.into());
+        }
+    };
+    deposit_state.fallback_chain_len = fallback_chain_len;
+    deposit_state.fallback_chain = fallback_chain;
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    for fallback in deposit_state.fallback_chain() {
+        msg!("Fallback receiver {}: {} seconds after expiry", fallback.receiver, fallback.grace_seconds);
+    }
+    Ok(())
+}
+
+/// Process rotate depositor instruction
+fn process_rotate_depositor(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let new_depositor = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let new_deposit_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Both keys must agree to the move
+    if !depositor.is_signer || !new_depositor.is_signer {
+        msg!("Current and new depositor must both sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    // Verify system program
+    if system_program.key != &system_program::id() {
+        msg!("Invalid system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut deposit_state = load_withdrawable_deposit(program_id, depositor, deposit_account, deposit_seed)?;
+
+    if new_depositor.key == depositor.key
+        || deposit_state.beneficiary_index(new_depositor.key).is_some()
+        || deposit_state.fallback_index(new_depositor.key).is_some()
+        || deposit_state.fallback_receiver == *new_depositor.key
+        || deposit_state.guardian_index(new_depositor.key).is_some()
+        || deposit_state.delegate_index(new_depositor.key).is_some()
+    {
+        msg!("New depositor must not be the current depositor, a receiver, a guardian or a delegate");
+        return Err(DielemmaError::InvalidNewDepositor.into());
+    }
+
+    // The liveness profile belongs to the old key: keep its latest proof, then unlink
+    if deposit_state.profile_linked {
+        let profile_account = next_account_info(account_info_iter)?;
+        let profile = load_profile(program_id, depositor.key, profile_account)?;
+        deposit_state.sync_profile(&profile);
+        deposit_state.profile_linked = false;
+    }
+
+    // Derive PDA for the new deposit account
+    let (new_deposit_pda, new_bump) = Pubkey::find_program_address(
+        &[DEPOSIT_SEED_PREFIX, new_depositor.key.as_ref(), deposit_seed.as_bytes()],
         program_id,
     );
 
-    if deposit_account.key != &deposit_pda {
-        msg!("Invalid deposit account PDA");
-        return Err(ProgramError::InvalidAccountData);
+    if new_deposit_account.key != &new_deposit_pda {
+        msg!("Invalid new deposit account PDA");
+        return Err(DielemmaError::InvalidDepositAccount.into());
     }
 
-    // Check if deposit account already exists
-    if deposit_account.lamports() > 0 {
-        msg!("Deposit account already exists");
+    if new_deposit_account.lamports() > 0 {
+        msg!("New depositor already has a deposit with this seed");
         return Err(ProgramError::AccountAlreadyInitialized);
     }
 
-    // Calculate minimum rent exemption
     let rent = Rent::get()?;
-    let required_lamports = rent
-        .minimum_balance(DEPOSIT_ACCOUNT_SIZE)
-        .max(1);
-
-    // Create deposit account
     let create_deposit_account_ix = system_instruction::create_account(
-        depositor.key,
-        deposit_account.key,
-        required_lamports,
+        new_depositor.key,
+        new_deposit_account.key,
+        rent.minimum_balance(DEPOSIT_ACCOUNT_SIZE),
         DEPOSIT_ACCOUNT_SIZE as u64,
         program_id,
     );
@@ -444,256 +3722,627 @@ fn process_deposit(
     invoke_signed(
         &create_deposit_account_ix,
         &[
-            depositor.clone(),
-            deposit_account.clone(),
+            new_depositor.clone(),
+            new_deposit_account.clone(),
             system_program.clone(),
         ],
         &[&[
             DEPOSIT_SEED_PREFIX,
-            depositor.key.as_ref(),
+            new_depositor.key.as_ref(),
             deposit_seed.as_bytes(),
-            &[bump],
+            &[new_bump],
         ]],
     )?;
 
-    // Derive PDA for deposit token account
-    let (token_account_pda, token_bump) = Pubkey::find_program_address(
-        &[
-            TOKEN_ACCOUNT_SEED_PREFIX,
-            deposit_pda.as_ref(),
-        ],
-        program_id,
+    let deposit_signer_seeds: &[&[u8]] = &[
+        DEPOSIT_SEED_PREFIX,
+        depositor.key.as_ref(),
+        deposit_seed.as_bytes(),
+        &[deposit_state.bump],
+    ];
+
+    if deposit_state.is_native() {
+        transfer_lamports(deposit_account, new_deposit_account, deposit_state.remaining_amount())?;
+    } else {
+        let deposit_token_account = next_account_info(account_info_iter)?;
+        let new_deposit_token_account = next_account_info(account_info_iter)?;
+        let token_mint = next_account_info(account_info_iter)?;
+        let token_program = next_account_info(account_info_iter)?;
+        let extra_accounts = account_info_iter.as_slice();
+
+        assert_token_program(token_program)?;
+        if token_mint.key != &deposit_state.token_mint {
+            msg!("Token mint does not match deposit mint");
+            return Err(DielemmaError::MintMismatch.into());
+        }
+        let decimals = unpack_mint_decimals(token_mint, token_program)?;
+
+        let (token_account_pda, _token_bump) =
+            Pubkey::find_program_address(&[TOKEN_ACCOUNT_SEED_PREFIX, deposit_account.key.as_ref()], program_id);
+        if deposit_token_account.key != &token_account_pda {
+            msg!("Invalid token account PDA");
+            return Err(DielemmaError::InvalidVaultAccount.into());
+        }
+
+        create_vault(
+            program_id,
+            new_depositor,
+            new_deposit_account,
+            new_deposit_token_account,
+            token_mint,
+            token_program,
+            system_program,
+            &rent,
+        )?;
+
+        let token_amount = vault_balance(deposit_token_account, token_program, &mut deposit_state)?;
+        transfer_tokens(
+            token_program,
+            deposit_token_account,
+            token_mint,
+            new_deposit_token_account,
+            deposit_account,
+            extra_accounts,
+            token_amount,
+            decimals,
+            &[deposit_signer_seeds],
+        )?;
+
+        // A transfer-fee mint takes its cut on the move, so record what the new vault received
+        let received_amount = unpack_token_account(new_deposit_token_account, token_program)?.amount;
+        deposit_state.amount = deposit_state
+            .claimed_amount
+            .checked_add(received_amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        // Close the emptied vault, refunding its rent to the current depositor
+        harvest_vault_fees(token_program, token_mint, deposit_token_account)?;
+        let close_vault_ix = close_account(
+            token_program.key,
+            deposit_token_account.key,
+            depositor.key,
+            deposit_account.key,
+            &[],
+        )?;
+
+        invoke_signed(
+            &close_vault_ix,
+            &[
+                deposit_token_account.clone(),
+                depositor.clone(),
+                deposit_account.clone(),
+                token_program.clone(),
+            ],
+            &[deposit_signer_seeds],
+        )?;
+    }
+
+    let mut rotated_state = deposit_state.clone();
+    rotated_state.depositor = *new_depositor.key;
+    rotated_state.bump = new_bump;
+    rotated_state.serialize(&mut &mut new_deposit_account.data.borrow_mut()[..])?;
+
+    // Close the old deposit account, marking it closed first in case it is refunded later in the transaction
+    deposit_state.is_closed = true;
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+    transfer_lamports(deposit_account, depositor, deposit_account.lamports())?;
+
+    msg!(
+        "Deposit moved from depositor {} to {} at {}",
+        depositor.key,
+        new_depositor.key,
+        new_deposit_account.key
     );
+    Ok(())
+}
 
-    if deposit_token_account.key != &token_account_pda {
-        msg!("Invalid token account PDA");
+/// Process migrate deposit instruction
+fn process_migrate_deposit(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_seed: &str,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let payer = next_account_info(account_info_iter)?;
+    let deposit_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // Verify payer is signer
+    if !payer.is_signer {
+        msg!("Payer must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify system program
+    if system_program.key != &system_program::id() {
+        msg!("Invalid system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if deposit_account.owner != program_id {
+        msg!("Deposit account is not owned by this program");
+        return Err(DielemmaError::InvalidDepositAccount.into());
+    }
+
+    if deposit_account.data_len() != LEGACY_DEPOSIT_ACCOUNT_SIZE {
+        msg!("Deposit account is not in the legacy layout");
         return Err(ProgramError::InvalidAccountData);
     }
 
-    // Calculate token account size
-    let token_account_size = TokenAccount::LEN;
+    let legacy = LegacyDepositAccount::try_from_slice(&deposit_account.data.borrow())?;
 
-    // Create token account (needs PDA signature since it will be owned by PDA)
-    let create_token_account_ix = system_instruction::create_account(
-        depositor.key,
-        deposit_token_account.key,
-        rent.minimum_balance(token_account_size),
-        token_account_size as u64,
-        &spl_token::id(),
+    // Derive PDA from the stored depositor
+    let (deposit_pda, _bump) = Pubkey::find_program_address(
+        &[DEPOSIT_SEED_PREFIX, legacy.depositor.as_ref(), deposit_seed.as_bytes()],
+        program_id,
     );
 
-    invoke_signed(
-        &create_token_account_ix,
-        &[
-            depositor.clone(),
-            deposit_token_account.clone(),
-            system_program.clone(),
-        ],
-        &[&[
-            TOKEN_ACCOUNT_SEED_PREFIX,
-            deposit_pda.as_ref(),
-            &[token_bump],
-        ]],
-    )?;
+    if deposit_account.key != &deposit_pda {
+        msg!("Invalid deposit account PDA");
+        return Err(DielemmaError::InvalidDepositAccount.into());
+    }
 
-    // Initialize token account (owned by deposit PDA, so we need invoke_signed)
-    let init_token_account_ix = initialize_account(
-        &spl_token::id(),
-        deposit_token_account.key,
-        token_mint.key,
-        deposit_account.key,
-    )?;
+    // Top up rent for the larger account before growing it
+    let rent = Rent::get()?;
+    let shortfall = rent
+        .minimum_balance(DEPOSIT_ACCOUNT_SIZE)
+        .saturating_sub(deposit_account.lamports());
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, deposit_account.key, shortfall),
+            &[payer.clone(), deposit_account.clone(), system_program.clone()],
+        )?;
+    }
+    deposit_account.realloc(DEPOSIT_ACCOUNT_SIZE, false)?;
 
-    invoke_signed(
-        &init_token_account_ix,
-        &[
-            deposit_token_account.clone(),
-            token_mint.clone(),
-            deposit_account.clone(),
-            rent_account.clone(),
-            token_program.clone(),
-        ],
-        &[&[
-            TOKEN_ACCOUNT_SEED_PREFIX,
-            deposit_pda.as_ref(),
-            &[token_bump],
-        ]],
-    )?;
+    let (beneficiary_count, beneficiaries) = build_beneficiaries(&[(legacy.receiver, TOTAL_SHARE_BPS)])?;
+    let deposit_state = DepositAccount {
+        depositor: legacy.depositor,
+        beneficiary_count,
+        beneficiaries,
+        token_mint: legacy.token_mint,
+        amount: legacy.amount,
+        claimed_amount: 0,
+        last_proof_timestamp: legacy.last_proof_timestamp,
+        timeout_seconds: legacy.timeout_seconds,
+        pending_timeout_seconds: 0,
+        pending_timeout_effective_at: 0,
+        claim_initiator: Pubkey::default(),
+        claim_finalizable_at: 0,
+        delegate_count: 0,
+        delegates: [Delegate::default(); MAX_DELEGATES],
+        heartbeat_nonce: 0,
+        profile_linked: false,
+        guardian_count: 0,
+        guardians: [Pubkey::default(); MAX_GUARDIANS],
+        guardian_threshold: 0,
+        death_attestations: 0,
+        death_confirmed_at: 0,
+        vesting_seconds: 0,
+        vesting_started_at: 0,
+        tranche_count: 0,
+        tranches: [ReleaseTranche::default(); MAX_RELEASE_TRANCHES],
+        release_at: 0,
+        release_at_only: false,
+        withdraw_locked_until: 0,
+        fallback_receiver: Pubkey::default(),
+        fallback_chain_len: 0,
+        fallback_chain: [FallbackReceiver::default(); MAX_FALLBACK_RECEIVERS],
+        bump: legacy.bump,
+        is_closed: legacy.is_closed,
+        deposit_seed_len: legacy.deposit_seed_len,
+        deposit_seed: legacy.deposit_seed,
+        layout_version: DEPOSIT_LAYOUT_VERSION,
+    };
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
 
-    // Transfer tokens from depositor to deposit token account
-    let transfer_ix = transfer(
-        &spl_token::id(),
-        depositor_token_account.key,  // Source: depositor's ATA
-        deposit_token_account.key,      // Destination: deposit's token account
+    msg!("Deposit {} migrated to layout version {}", deposit_account.key, DEPOSIT_LAYOUT_VERSION);
+    Ok(())
+}
+
+/// Process create profile instruction
+fn process_create_profile(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?;
+    let profile_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        msg!("Depositor must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    // Verify system program
+    if system_program.key != &system_program::id() {
+        msg!("Invalid system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (profile_pda, bump) =
+        Pubkey::find_program_address(&[PROFILE_SEED_PREFIX, depositor.key.as_ref()], program_id);
+    if profile_account.key != &profile_pda {
+        msg!("Invalid liveness profile PDA");
+        return Err(DielemmaError::InvalidProfileAccount.into());
+    }
+
+    // Check if profile account already exists
+    if profile_account.lamports() > 0 {
+        msg!("Liveness profile already exists");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let rent = Rent::get()?;
+    let create_profile_account_ix = system_instruction::create_account(
         depositor.key,
-        &[],
-        amount,
-    )?;
+        profile_account.key,
+        rent.minimum_balance(PROFILE_ACCOUNT_SIZE),
+        PROFILE_ACCOUNT_SIZE as u64,
+        program_id,
+    );
 
-    invoke(
-        &transfer_ix,
+    invoke_signed(
+        &create_profile_account_ix,
         &[
-            depositor_token_account.clone(),
-            deposit_token_account.clone(),
             depositor.clone(),
-            token_program.clone(),
+            profile_account.clone(),
+            system_program.clone(),
         ],
+        &[&[PROFILE_SEED_PREFIX, depositor.key.as_ref(), &[bump]]],
     )?;
 
-    // Create deposit account state
-    let seed_bytes = deposit_seed.as_bytes();
-    let seed_len = seed_bytes.len() as u32;
-
-    // Initialize fixed-size array with seed data
-    let mut seed_array = [0u8; MAX_DEPOSIT_SEED_LENGTH];
-    seed_array[..seed_bytes.len()].copy_from_slice(seed_bytes);
-
-    let deposit_state = DepositAccount {
-        depositor: *depositor.key,
-        receiver: *receiver,  // Copy the Pubkey
-        token_mint: *token_mint.key,
-        amount,
-        last_proof_timestamp: clock.unix_timestamp,
-        timeout_seconds,
+    let profile = ProfileAccount {
+        depositor: *depositor.key,
+        last_proof_timestamp: Clock::get()?.unix_timestamp,
         bump,
-        is_closed: false,
-        deposit_seed_len: seed_len,
-        deposit_seed: seed_array,
     };
+    profile.serialize(&mut &mut profile_account.data.borrow_mut()[..])?;
 
-    // Serialize and write to account
-    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
-
-    msg!("Deposit successful: {} tokens to receiver {}", amount, receiver);
+    msg!("Liveness profile created for {} at {}", depositor.key, profile.last_proof_timestamp);
     Ok(())
 }
 
-/// Process proof-of-life instruction
-fn process_proof_of_life(
+/// Process set profile link instruction
+///
+/// Linking or unlinking never shortens the deposit's liveness: the deposit first
+/// catches up with the profile's proof of life.
+fn process_set_profile_link(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    deposit_seed: &str,  // Use reference
+    deposit_seed: &str,
+    linked: bool,
 ) -> ProgramResult {
-    msg!("=== Proof of Life Started ===");
-    msg!("Deposit seed: {}", deposit_seed);
-    msg!("Number of accounts: {}", accounts.len());
-
     let account_info_iter = &mut accounts.iter();
 
     let depositor = next_account_info(account_info_iter)?;
-    msg!("Depositor: {}", depositor.key);
-
     let deposit_account = next_account_info(account_info_iter)?;
-    msg!("Deposit account: {}", deposit_account.key);
-
-    let depositor_dlm_token_account = next_account_info(account_info_iter)?;
-    msg!("Depositor DLM token account: {}", depositor_dlm_token_account.key);
-    msg!("Depositor DLM token account data length: {}", depositor_dlm_token_account.data.borrow().len());
-
-    let burn_dlm_token_account = next_account_info(account_info_iter)?;
-    msg!("Burn DLM token account: {}", burn_dlm_token_account.key);
-
-    let dlm_mint_account = next_account_info(account_info_iter)?;
-    msg!("DLM mint account: {}", dlm_mint_account.key);
-
-    let token_program = next_account_info(account_info_iter)?;
-    msg!("Token program: {}", token_program.key);
+    let config_account = next_account_info(account_info_iter)?;
+    let profile_account = next_account_info(account_info_iter)?;
 
     // Verify depositor is signer
     if !depositor.is_signer {
         msg!("Depositor must sign the transaction");
         return Err(ProgramError::MissingRequiredSignature);
     }
-    msg!("✓ Depositor is signer");
 
-    // Verify token program
-    if token_program.key != &spl_token_2022::id() {
-        msg!("Invalid token program, expected Token-2022");
-        msg!("Expected: {}", spl_token_2022::id());
-        msg!("Got: {}", token_program.key);
-        return Err(ProgramError::IncorrectProgramId);
+    let config = load_config(program_id, config_account)?;
+    assert_not_paused(&config)?;
+
+    let mut deposit_state = load_withdrawable_deposit(program_id, depositor, deposit_account, deposit_seed)?;
+    let profile = load_profile(program_id, depositor.key, profile_account)?;
+
+    deposit_state.sync_profile(&profile);
+    deposit_state.profile_linked = linked;
+    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+
+    if linked {
+        msg!("Deposit linked to liveness profile {}", profile_account.key);
+    } else {
+        msg!("Deposit unlinked from liveness profile {}", profile_account.key);
+    }
+    Ok(())
+}
+
+/// Move lamports out of an account owned by this program
+fn transfer_lamports(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    **from.try_borrow_mut_lamports()? = from
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **to.try_borrow_mut_lamports()? = to
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+/// Validate the amount and timeout of a new deposit
+fn validate_deposit_terms(amount: u64, timeout_seconds: u64) -> ProgramResult {
+    // Validate deposit amount
+    if amount == 0 {
+        msg!("Deposit amount must be greater than 0");
+        return Err(DielemmaError::InvalidAmount.into());
     }
-    msg!("✓ Token program is Token-2022");
 
-    // Verify DLM mint account matches expected mint address
-    let expected_mint = OFFICIAL_DLM_TOKEN_MINT.parse::<Pubkey>()
-        .map_err(|_| ProgramError::InvalidAccountData)?;
-    if dlm_mint_account.key != &expected_mint {
-        msg!("Invalid DLM mint account");
-        msg!("Expected: {}", expected_mint);
-        msg!("Got: {}", dlm_mint_account.key);
-        return Err(ProgramError::InvalidAccountData);
+    if amount > MAX_DEPOSIT_AMOUNT {
+        msg!("Deposit amount exceeds maximum allowed");
+        return Err(DielemmaError::InvalidAmount.into());
     }
-    msg!("✓ DLM mint account is correct");
 
+    validate_timeout(timeout_seconds)
+}
+
+/// Validate a timeout against `MIN_TIMEOUT_SECONDS` and `MAX_TIMEOUT_SECONDS`
+fn validate_timeout(timeout_seconds: u64) -> ProgramResult {
+    if !(MIN_TIMEOUT_SECONDS..=MAX_TIMEOUT_SECONDS).contains(&timeout_seconds) {
+        msg!("Timeout must be between {} and {} seconds", MIN_TIMEOUT_SECONDS, MAX_TIMEOUT_SECONDS);
+        return Err(DielemmaError::InvalidTimeout.into());
+    }
+
+    Ok(())
+}
+
+/// Copy a deposit seed into the fixed-size array stored in `DepositAccount`
+fn pack_deposit_seed(deposit_seed: &str) -> (u32, [u8; MAX_DEPOSIT_SEED_LENGTH]) {
+    let seed_bytes = deposit_seed.as_bytes();
+    let mut seed_array = [0u8; MAX_DEPOSIT_SEED_LENGTH];
+    seed_array[..seed_bytes.len()].copy_from_slice(seed_bytes);
+    (seed_bytes.len() as u32, seed_array)
+}
+
+/// Load a deposit for withdrawal, verifying the PDA, the depositor and that it is still open
+fn load_withdrawable_deposit(
+    program_id: &Pubkey,
+    depositor: &AccountInfo,
+    deposit_account: &AccountInfo,
+    deposit_seed: &str,
+) -> Result<DepositAccount, ProgramError> {
     // Derive PDA
-    msg!("Deriving deposit PDA...");
     let (deposit_pda, _bump) = Pubkey::find_program_address(
         &[DEPOSIT_SEED_PREFIX, depositor.key.as_ref(), deposit_seed.as_bytes()],
         program_id,
     );
-    msg!("Derived PDA: {}", deposit_pda);
 
     if deposit_account.key != &deposit_pda {
         msg!("Invalid deposit account PDA");
-        msg!("Expected: {}", deposit_pda);
-        msg!("Got: {}", deposit_account.key);
-        return Err(ProgramError::InvalidAccountData);
+        return Err(DielemmaError::InvalidDepositAccount.into());
     }
-    msg!("✓ Deposit account PDA is valid");
 
     // Deserialize deposit account
-    msg!("Deserializing deposit account...");
-    let mut deposit_state = DepositAccount::try_from_slice(&deposit_account.data.borrow())
-        .map_err(|e| {
-            msg!("Failed to deserialize deposit account: {:?}", e);
-            ProgramError::InvalidAccountData
-        })?;
-    msg!("✓ Deposit account deserialized successfully");
+    let deposit_state = unpack_deposit(deposit_account)?;
 
     // Verify depositor
     if deposit_state.depositor != *depositor.key {
-        msg!("Only the depositor can perform proof of life");
+        msg!("Only the depositor can withdraw");
+        return Err(DielemmaError::NotDepositor.into());
+    }
+
+    // Check if already closed
+    if deposit_state.is_closed {
+        msg!("Deposit already withdrawn or claimed");
+        return Err(DielemmaError::AlreadyClosed.into());
+    }
+
+    Ok(deposit_state)
+}
+
+/// Load a deposit for a claim, verifying the PDA, the receiver's signature and that
+/// proof-of-life has expired
+///
+/// For a deposit linked to the depositor's liveness profile, the profile account is taken
+/// from `account_info_iter` and its proof of life counts too. A fallback receiver whose
+/// claim window is open and who initiated the pending claim is made the sole beneficiary
+/// of the returned deposit; one yet to initiate its own claim voids the lapsed one.
+/// Returns the deposit and the receiver's position in its beneficiary list (None for a
+/// fallback receiver without a pending claim).
+fn load_claimable_deposit<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    receiver: &AccountInfo,
+    deposit_account: &AccountInfo,
+    deposit_seed: &str,
+    account_info_iter: &mut impl Iterator<Item = &'a AccountInfo<'b>>,
+) -> Result<(DepositAccount, Option<usize>), ProgramError> {
+    // Deserialize deposit account
+    let mut deposit_state = unpack_deposit(deposit_account)?;
+
+    // Derive PDA
+    let (deposit_pda, _bump) = Pubkey::find_program_address(
+        &[DEPOSIT_SEED_PREFIX, deposit_state.depositor.as_ref(), deposit_seed.as_bytes()],
+        program_id,
+    );
+
+    if deposit_account.key != &deposit_pda {
+        msg!("Invalid deposit account PDA");
+        return Err(DielemmaError::InvalidDepositAccount.into());
+    }
+
+    // Verify receiver
+    let beneficiary_index = deposit_state.beneficiary_index(receiver.key);
+    let fallback_index = deposit_state.fallback_index(receiver.key);
+    if beneficiary_index.is_none() && fallback_index.is_none() {
+        msg!("Only a designated receiver can claim");
+        return Err(DielemmaError::NotReceiver.into());
+    }
+
+    // Verify receiver is signer
+    if !receiver.is_signer {
+        msg!("Receiver must sign the claim transaction");
         return Err(ProgramError::MissingRequiredSignature);
     }
-    msg!("✓ Depositor verified");
+
+    // Check if already closed
+    if deposit_state.is_closed {
+        msg!("Deposit already withdrawn or claimed");
+        return Err(DielemmaError::AlreadyClosed.into());
+    }
+
+    if deposit_state.profile_linked {
+        let profile_account = next_account_info(account_info_iter)?;
+        let profile = load_profile(program_id, &deposit_state.depositor, profile_account)?;
+        deposit_state.sync_profile(&profile);
+    }
+
+    // Check if proof-of-life has expired
+    let clock = Clock::get()?;
+
+    // Validate timestamp is not in the future
+    if deposit_state.last_proof_timestamp > clock.unix_timestamp {
+        msg!("Invalid last_proof_timestamp: future date detected");
+        return Err(DielemmaError::InvalidTimestamp.into());
+    }
+
+    // Validate timestamp is not unreasonably old (before Solana genesis)
+    const MIN_VALID_TIMESTAMP: i64 = 1598000000; // ~August 2020
+    if deposit_state.last_proof_timestamp < MIN_VALID_TIMESTAMP {
+        msg!("Invalid last_proof_timestamp: unreasonably old date");
+        return Err(DielemmaError::InvalidTimestamp.into());
+    }
+
+    if !deposit_state.expired(clock.unix_timestamp) {
+        if deposit_state.released(clock.unix_timestamp) {
+            msg!("Release date {} reached", deposit_state.release_at);
+        } else if deposit_state.death_confirmed(clock.unix_timestamp) {
+            msg!("Death confirmed by guardians at {}", deposit_state.death_confirmed_at);
+        } else {
+            msg!(
+                "Proof of life has not expired yet. Elapsed: {}, Required: {}",
+                clock.unix_timestamp - deposit_state.last_proof_timestamp,
+                deposit_state.timeout_at(clock.unix_timestamp)
+            );
+            if deposit_state.release_at != 0 {
+                msg!("Release date: {}", deposit_state.release_at);
+            }
+            return Err(DielemmaError::NotExpired.into());
+        }
+    }
+
+    // Only whoever is in line may claim: the beneficiaries until a fallback window opens.
+    // A fallback takes over the deposit only through a claim of its own.
+    let active_fallback = deposit_state.active_fallback(clock.unix_timestamp);
+    let index = match (beneficiary_index, active_fallback) {
+        (Some(index), None) => Some(index),
+        (_, Some(active)) if fallback_index == Some(active) => {
+            if deposit_state.has_pending_claim() && deposit_state.claim_initiator == *receiver.key {
+                msg!("Fallback receiver {} takes over the deposit", receiver.key);
+                deposit_state.hand_over_to_fallback(active);
+                Some(0)
+            } else {
+                // Whoever initiated the pending claim is no longer in line
+                deposit_state.clear_pending_claim();
+                None
+            }
+        }
+        _ => {
+            msg!("Receiver's claim window is not open");
+            return Err(DielemmaError::OutsideClaimWindow.into());
+        }
+    };
+
+    if index.is_some_and(|index| deposit_state.beneficiaries[index].claimed) {
+        msg!("Receiver has already claimed their share");
+        return Err(DielemmaError::ShareAlreadyClaimed.into());
+    }
+
+    Ok((deposit_state, index))
+}
+
+/// Load a deposit for proof of life, deriving the PDA from the stored depositor since a
+/// delegate, relayer or guardian may be the one signing
+fn load_proof_of_life_deposit(
+    program_id: &Pubkey,
+    deposit_account: &AccountInfo,
+    deposit_seed: &str,
+) -> Result<DepositAccount, ProgramError> {
+    if deposit_account.owner != program_id {
+        msg!("Deposit account is not owned by this program");
+        return Err(DielemmaError::InvalidDepositAccount.into());
+    }
+    let deposit_state = unpack_deposit(deposit_account)?;
+
+    // Derive PDA
+    let (deposit_pda, _bump) = Pubkey::find_program_address(
+        &[DEPOSIT_SEED_PREFIX, deposit_state.depositor.as_ref(), deposit_seed.as_bytes()],
+        program_id,
+    );
+
+    if deposit_account.key != &deposit_pda {
+        msg!("Invalid deposit account PDA");
+        msg!("Expected: {}", deposit_pda);
+        msg!("Got: {}", deposit_account.key);
+        return Err(DielemmaError::InvalidDepositAccount.into());
+    }
 
     // Check if already closed
     if deposit_state.is_closed {
         msg!("Deposit account is already closed");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(DielemmaError::AlreadyClosed.into());
+    }
+
+    Ok(deposit_state)
+}
+
+/// Transfer the configured proof-of-life cost of DLM from `payer`'s ATA to the burn address's ATA
+///
+/// Returns the amount transferred.
+fn burn_proof_of_life_cost<'a>(
+    config: &ConfigAccount,
+    payer: &AccountInfo<'a>,
+    payer_dlm_token_account: &AccountInfo<'a>,
+    burn_dlm_token_account: &AccountInfo<'a>,
+    dlm_mint_account: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    extra_accounts: &[AccountInfo<'a>],
+) -> Result<u64, ProgramError> {
+    // Verify token program
+    if token_program.key != &spl_token_2022::id() {
+        msg!("Invalid token program, expected Token-2022");
+        msg!("Expected: {}", spl_token_2022::id());
+        msg!("Got: {}", token_program.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Verify DLM mint account matches the configured mint address
+    let expected_mint = config.dlm_mint;
+    if dlm_mint_account.key != &expected_mint {
+        msg!("Invalid DLM mint account");
+        msg!("Expected: {}", expected_mint);
+        msg!("Got: {}", dlm_mint_account.key);
+        return Err(DielemmaError::InvalidDlmMint.into());
     }
-    msg!("✓ Deposit account is not closed");
 
     // Derive expected addresses for validation
     let associated_token_program_id = SPL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID.parse::<Pubkey>()
         .map_err(|_| ProgramError::InvalidAccountData)?;
 
-    msg!("✓ associated_token_program_id: {}", associated_token_program_id);
-
-    // Derive depositor's DLM ATA
-    let (depositor_dlm_ata, _ata_bump) = Pubkey::find_program_address(
+    // Derive payer's DLM ATA
+    let (payer_dlm_ata, _ata_bump) = Pubkey::find_program_address(
         &[
-            depositor.key.as_ref(),
+            payer.key.as_ref(),
             token_program.key.as_ref(),
             dlm_mint_account.key.as_ref(),
         ],
         &associated_token_program_id,
     );
 
-    // Verify depositor's DLM token account matches expected ATA
-    if depositor_dlm_token_account.key != &depositor_dlm_ata {
-        msg!("depositor_dlm_token_account.key: {}", depositor_dlm_token_account.key);
-        msg!("depositor_dlm_ata: {}", &depositor_dlm_ata);
-        msg!("Invalid depositor DLM token account");
-        return Err(ProgramError::InvalidAccountData);
+    // Verify payer's DLM token account matches expected ATA
+    if payer_dlm_token_account.key != &payer_dlm_ata {
+        msg!("payer_dlm_token_account.key: {}", payer_dlm_token_account.key);
+        msg!("payer_dlm_ata: {}", &payer_dlm_ata);
+        msg!("Invalid payer DLM token account");
+        return Err(DielemmaError::InvalidDlmTokenAccount.into());
     }
 
-    msg!("✓ depositor dlm token account check passed");
-
-    // Derive BURN_ADDRESS's ATA for the DLM token
-    let burn_address = BURN_ADDRESS.parse::<Pubkey>()
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+    // Derive the configured burn address's ATA for the DLM token
+    let burn_address = config.burn_address;
     let (burn_dlm_ata, _burn_ata_bump) = Pubkey::find_program_address(
         &[
             burn_address.as_ref(),
@@ -703,367 +4352,652 @@ fn process_proof_of_life(
         &associated_token_program_id,
     );
 
-    msg!("✓ burn_address: {}", burn_address);
-
     // Verify burn DLM token account matches expected ATA
     if burn_dlm_token_account.key != &burn_dlm_ata {
         msg!("Invalid burn DLM token account");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(DielemmaError::InvalidBurnTokenAccount.into());
+    }
+
+    // Amount to transfer: configured proof-of-life cost, decimals read from the mint
+    let transfer_amount = config.proof_of_life_cost;
+    let decimals = {
+        let mint_data = dlm_mint_account.data.borrow();
+        StateWithExtensions::<Mint>::unpack(&mint_data)
+            .map_err(|_| DielemmaError::InvalidDlmMint)?
+            .base
+            .decimals
+    };
+
+    transfer_tokens(
+        token_program,
+        payer_dlm_token_account,
+        dlm_mint_account,
+        burn_dlm_token_account,
+        payer,
+        extra_accounts,
+        transfer_amount,
+        decimals,
+        &[],
+    )?;
+
+    Ok(transfer_amount)
+}
+
+/// Verify that the instruction preceding this one is an ed25519 program instruction
+/// checking `signer`'s signature over exactly `message`
+///
+/// The ed25519 program has already verified the signature when the transaction was
+/// processed; this only makes sure it covers the expected key and message. All
+/// offsets must point into the ed25519 instruction itself.
+fn verify_ed25519_signature(instructions_sysvar: &AccountInfo, signer: &Pubkey, message: &[u8]) -> ProgramResult {
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    if current_index == 0 {
+        msg!("Missing ed25519 signature instruction");
+        return Err(DielemmaError::InvalidHeartbeat.into());
+    }
+    let ed25519_ix = load_instruction_at_checked(current_index as usize - 1, instructions_sysvar)?;
+    if ed25519_ix.program_id != ed25519_program::id() {
+        msg!("Instruction before proof of life is not an ed25519 signature check");
+        return Err(DielemmaError::InvalidHeartbeat.into());
+    }
+
+    let data = &ed25519_ix.data;
+    let read_u16 = |at: usize| -> Result<usize, ProgramError> {
+        data.get(at..at + 2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+            .ok_or_else(|| DielemmaError::InvalidHeartbeat.into())
+    };
+    // Header: signature count and padding, then one set of seven u16 offsets
+    if data.first() != Some(&1) {
+        msg!("Expected exactly one ed25519 signature");
+        return Err(DielemmaError::InvalidHeartbeat.into());
+    }
+    let public_key_offset = read_u16(6)?;
+    let message_offset = read_u16(10)?;
+    let message_size = read_u16(12)?;
+    for instruction_index_at in [4, 8, 14] {
+        if read_u16(instruction_index_at)? != u16::MAX as usize {
+            msg!("ed25519 signature data must be inline");
+            return Err(DielemmaError::InvalidHeartbeat.into());
+        }
+    }
+
+    let public_key = data.get(public_key_offset..public_key_offset + 32);
+    let signed_message = data.get(message_offset..message_offset + message_size);
+    if public_key != Some(signer.as_ref()) || signed_message != Some(message) {
+        msg!("Heartbeat was not signed by the depositor for this deposit, nonce and expiry");
+        return Err(DielemmaError::InvalidHeartbeat.into());
+    }
+
+    Ok(())
+}
+
+/// Verify an initiated claim has passed its challenge period
+fn assert_claim_finalizable(deposit_state: &DepositAccount) -> ProgramResult {
+    if !deposit_state.has_pending_claim() {
+        msg!("Claim must be initiated first");
+        return Err(DielemmaError::ClaimNotInitiated.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    if now < deposit_state.claim_finalizable_at {
+        msg!(
+            "Claim challenge period ends at {}, current time {}",
+            deposit_state.claim_finalizable_at,
+            now
+        );
+        return Err(DielemmaError::ChallengePeriodActive.into());
+    }
+
+    Ok(())
+}
+
+/// Reject a withdrawal, or a change to who is paid or when, while the deposit's withdrawal
+/// lock is in force
+fn assert_withdraw_unlocked(deposit_state: &DepositAccount) -> ProgramResult {
+    if deposit_state.withdraw_locked(Clock::get()?.unix_timestamp) {
+        if deposit_state.withdraw_locked_until == WITHDRAW_LOCKED_FOREVER {
+            msg!("Deposit is irrevocable");
+        } else {
+            msg!("Deposit is locked until {}", deposit_state.withdraw_locked_until);
+        }
+        return Err(DielemmaError::WithdrawLocked.into());
+    }
+    Ok(())
+}
+
+/// Reject a claim that would pay out nothing while the deposit is still being released
+fn assert_something_vested(deposit_state: &DepositAccount, amount: u64, now: i64) -> ProgramResult {
+    if amount == 0 && deposit_state.unlocked_bps(now) < TOTAL_SHARE_BPS {
+        msg!("Nothing unlocked yet, {} bps of each share are unlocked", deposit_state.unlocked_bps(now));
+        return Err(DielemmaError::NothingUnlocked.into());
+    }
+    if amount == 0 && !deposit_state.fully_vested(now) {
+        msg!(
+            "Nothing vested yet, vesting ends at {}",
+            deposit_state.vesting_started_at.saturating_add(deposit_state.vesting_seconds as i64)
+        );
+        return Err(DielemmaError::NothingVested.into());
+    }
+    Ok(())
+}
+
+/// Load the config account, verifying it is the initialized config PDA
+fn load_config(program_id: &Pubkey, config_account: &AccountInfo) -> Result<ConfigAccount, ProgramError> {
+    let (config_pda, _bump) = Pubkey::find_program_address(&[CONFIG_SEED_PREFIX], program_id);
+    if config_account.key != &config_pda || config_account.owner != program_id {
+        msg!("Invalid config account");
+        return Err(DielemmaError::InvalidConfigAccount.into());
+    }
+
+    ConfigAccount::try_from_slice(&config_account.data.borrow())
+        .map_err(|_| DielemmaError::InvalidConfigAccount.into())
+}
+
+/// Deserialize a deposit account, rejecting accounts not in the current layout
+fn unpack_deposit(deposit_account: &AccountInfo) -> Result<DepositAccount, ProgramError> {
+    let data = deposit_account.data.borrow();
+    if data.len() == LEGACY_DEPOSIT_ACCOUNT_SIZE {
+        msg!("Deposit account uses the legacy layout, convert it with MigrateDeposit");
+        return Err(DielemmaError::OutdatedDepositLayout.into());
+    }
+
+    let deposit_state = DepositAccount::try_from_slice(&data).map_err(|e| {
+        msg!("Failed to deserialize deposit account: {:?}", e);
+        ProgramError::InvalidAccountData
+    })?;
+    if deposit_state.layout_version != DEPOSIT_LAYOUT_VERSION {
+        msg!("Deposit account layout version {} is not supported", deposit_state.layout_version);
+        return Err(DielemmaError::OutdatedDepositLayout.into());
+    }
+
+    Ok(deposit_state)
+}
+
+/// Load a depositor's liveness profile, verifying it is their initialized profile PDA
+fn load_profile(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    profile_account: &AccountInfo,
+) -> Result<ProfileAccount, ProgramError> {
+    let (profile_pda, _bump) =
+        Pubkey::find_program_address(&[PROFILE_SEED_PREFIX, depositor.as_ref()], program_id);
+    if profile_account.key != &profile_pda || profile_account.owner != program_id {
+        msg!("Invalid liveness profile account");
+        return Err(DielemmaError::InvalidProfileAccount.into());
+    }
+
+    ProfileAccount::try_from_slice(&profile_account.data.borrow())
+        .map_err(|_| DielemmaError::InvalidProfileAccount.into())
+}
+
+/// Load the config account for an admin instruction, verifying the admin signature
+fn load_config_as_admin(
+    program_id: &Pubkey,
+    admin: &AccountInfo,
+    config_account: &AccountInfo,
+) -> Result<ConfigAccount, ProgramError> {
+    if !admin.is_signer {
+        msg!("Admin must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+    if config.admin != *admin.key {
+        msg!("Only the admin can update the config");
+        return Err(DielemmaError::NotAdmin.into());
+    }
+
+    Ok(config)
+}
+
+/// Fail if the admin has paused the program
+fn assert_not_paused(config: &ConfigAccount) -> ProgramResult {
+    if config.paused {
+        msg!("Program is paused");
+        return Err(DielemmaError::ProgramPaused.into());
+    }
+    Ok(())
+}
+
+/// Verify the token program is legacy Token or Token-2022
+fn assert_token_program(token_program: &AccountInfo) -> ProgramResult {
+    if token_program.key != &spl_token::id() && token_program.key != &spl_token_2022::id() {
+        msg!("Invalid token program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+/// Unpack a token account owned by `token_program`, with or without extensions
+fn unpack_token_account(account: &AccountInfo, token_program: &AccountInfo) -> Result<TokenAccount, ProgramError> {
+    if account.owner != token_program.key {
+        msg!("Token account {} is not owned by the token program", account.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let data = account.data.borrow();
+    StateWithExtensions::<TokenAccount>::unpack(&data)
+        .map(|state| state.base)
+        .map_err(|_| ProgramError::InvalidAccountData)
+}
+
+/// Read the vault balance that a withdrawal or claim pays out
+///
+/// Transfer fees withheld on the vault sit in its fee extension, not in `amount`,
+/// and harvesting them to the mint leaves `amount` untouched. The balance should
+/// therefore always equal the recorded deposit amount less claimed shares; anything else (a direct
+/// transfer into the vault, a permanent delegate) is reconciled into `deposit_state.amount`,
+/// so shares are computed from what the vault actually holds.
+fn vault_balance(
+    vault: &AccountInfo,
+    token_program: &AccountInfo,
+    deposit_state: &mut DepositAccount,
+) -> Result<u64, ProgramError> {
+    let balance = unpack_token_account(vault, token_program)?.amount;
+    if balance != deposit_state.remaining_amount() {
+        msg!("Vault balance {} differs from recorded deposit amount {}, reconciling", balance, deposit_state.remaining_amount());
+        deposit_state.amount = deposit_state
+            .claimed_amount
+            .checked_add(balance)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+    }
+    Ok(balance)
+}
+
+/// Amount a token account gained since `balance_before`, i.e. net of transfer fees
+fn received_since(
+    account: &AccountInfo,
+    token_program: &AccountInfo,
+    balance_before: u64,
+) -> Result<u64, ProgramError> {
+    unpack_token_account(account, token_program)?
+        .amount
+        .checked_sub(balance_before)
+        .ok_or(ProgramError::ArithmeticOverflow)
+}
+
+/// Read the decimals of a mint owned by `token_program`, with or without extensions
+fn unpack_mint_decimals(mint: &AccountInfo, token_program: &AccountInfo) -> Result<u8, ProgramError> {
+    if mint.owner != token_program.key {
+        msg!("Token mint is not owned by the token program");
+        return Err(ProgramError::IncorrectProgramId);
     }
+    let data = mint.data.borrow();
+    StateWithExtensions::<Mint>::unpack(&data)
+        .map(|state| state.base.decimals)
+        .map_err(|_| ProgramError::InvalidAccountData)
+}
 
-    msg!("✓ burn_address check passed");
+/// Ask the token program how large a token account for `mint` must be
+///
+/// Token-2022 mints may require extensions on every token account (transfer fee
+/// amount, transfer hook state, ...), so the size is not a constant.
+fn get_vault_size<'a>(token_program: &AccountInfo<'a>, mint: &AccountInfo<'a>) -> Result<usize, ProgramError> {
+    let get_size_ix = get_account_data_size(token_program.key, mint.key, &[])?;
+    invoke(&get_size_ix, &[mint.clone(), token_program.clone()])?;
+
+    match get_return_data() {
+        Some((program_id, data)) if program_id == *token_program.key && data.len() == 8 => {
+            Ok(u64::from_le_bytes(data[..].try_into().unwrap()) as usize)
+        }
+        _ => {
+            msg!("Token program did not return the token account size");
+            Err(ProgramError::InvalidAccountData)
+        }
+    }
+}
 
-    // Amount to transfer: 1 DLM token (6 decimals)
-    let transfer_amount: u64 = 1_000_000;
-    let decimals: u8 = 6; // DLM token has 6 decimals
+/// Create and initialize the token vault PDA of `deposit_account`, paid for by `payer`
+#[allow(clippy::too_many_arguments)]
+fn create_vault<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    deposit_account: &AccountInfo<'a>,
+    vault: &AccountInfo<'a>,
+    token_mint: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    rent: &Rent,
+) -> ProgramResult {
+    // Derive PDA for deposit token account
+    let (token_account_pda, token_bump) =
+        Pubkey::find_program_address(&[TOKEN_ACCOUNT_SEED_PREFIX, deposit_account.key.as_ref()], program_id);
 
-    // Prepare remaining accounts for Token-2022 extensions (transfer hooks, fees, etc.)
-    let remaining_accounts: Vec<&AccountInfo> = account_info_iter.collect();
+    if vault.key != &token_account_pda {
+        msg!("Invalid token account PDA");
+        return Err(DielemmaError::InvalidVaultAccount.into());
+    }
 
-    msg!("✓ Total remaining accounts for Token-2022 extensions: {}", remaining_accounts.len());
+    // Calculate token account size, including any extensions required by the mint
+    let token_account_size = get_vault_size(token_program, token_mint)?;
 
-    // Build the transfer_checked instruction
-    let transfer_ix = transfer_checked(
+    // Create token account (needs PDA signature since it will be owned by PDA)
+    let create_token_account_ix = system_instruction::create_account(
+        payer.key,
+        vault.key,
+        rent.minimum_balance(token_account_size),
+        token_account_size as u64,
         token_program.key,
-        depositor_dlm_token_account.key,
-        dlm_mint_account.key,
-        burn_dlm_token_account.key,
-        depositor.key,
-        &[],
-        transfer_amount,
-        decimals,
-    )?;
-
-    msg!("✓ Transfer instruction created");
-
-    // Prepare all required accounts for the invoke
-    let mut account_list = vec![
-        depositor_dlm_token_account.clone(),
-        dlm_mint_account.clone(),
-        burn_dlm_token_account.clone(),
-        depositor.clone(),
-    ];
-
-    // Add remaining accounts for Token-2022 extensions
-    for account in remaining_accounts {
-        account_list.push(account.clone());
-    }
+    );
 
-    // Invoke the transfer with all accounts
-    invoke(
-        &transfer_ix,
-        &account_list,
+    invoke_signed(
+        &create_token_account_ix,
+        &[payer.clone(), vault.clone(), system_program.clone()],
+        &[&[TOKEN_ACCOUNT_SEED_PREFIX, deposit_account.key.as_ref(), &[token_bump]]],
     )?;
 
-    msg!("✓ Transfer completed successfully");
+    // Initialize token account with the deposit PDA as its owner
+    let init_token_account_ix = initialize_account3(token_program.key, vault.key, token_mint.key, deposit_account.key)?;
 
-    // Update timestamp
-    let clock = Clock::get()?;
-    deposit_state.last_proof_timestamp = clock.unix_timestamp;
+    invoke(
+        &init_token_account_ix,
+        &[vault.clone(), token_mint.clone(), token_program.clone()],
+    )
+}
 
-    // Serialize back
-    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+/// Sweep transfer fees withheld on `vault` to the mint, once it is emptied or before it is closed
+///
+/// Token-2022 refuses to close an account with withheld fees; harvesting them is
+/// permissionless, needs the mint writable, and is a no-op for mints without the
+/// transfer-fee extension.
+fn harvest_vault_fees<'a>(
+    token_program: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    vault: &AccountInfo<'a>,
+) -> ProgramResult {
+    let withheld_amount = {
+        let data = vault.data.borrow();
+        let state = StateWithExtensions::<TokenAccount>::unpack(&data)?;
+        state
+            .get_extension::<TransferFeeAmount>()
+            .map(|fee_amount| u64::from(fee_amount.withheld_amount))
+            .unwrap_or(0)
+    };
+    if withheld_amount == 0 {
+        return Ok(());
+    }
 
-    msg!("Proof of life recorded at {} with {} tokens transferred to burn address", deposit_state.last_proof_timestamp, transfer_amount);
-    Ok(())
+    let harvest_ix = harvest_withheld_tokens_to_mint(token_program.key, mint.key, &[vault.key])?;
+    invoke(&harvest_ix, &[mint.clone(), vault.clone(), token_program.clone()])
 }
 
-#[cfg(test)]
-mod tests {
+/// Transfer tokens with `transfer_checked`
+///
+/// `extra_accounts` are appended to both the instruction and the CPI account list,
+/// so Token-2022 can reach the accounts its extensions need (e.g. a transfer hook
+/// program and its validation account). `signer_seeds` is empty unless `authority`
+/// is a PDA of this program.
+#[allow(clippy::too_many_arguments)]
+fn transfer_tokens<'a>(
+    token_program: &AccountInfo<'a>,
+    source: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    extra_accounts: &[AccountInfo<'a>],
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut transfer_ix = transfer_checked(
+        token_program.key,
+        source.key,
+        mint.key,
+        destination.key,
+        authority.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+    transfer_ix.accounts.extend(extra_accounts.iter().map(|account| AccountMeta {
+        pubkey: *account.key,
+        is_signer: account.is_signer,
+        is_writable: account.is_writable,
+    }));
+
+    let mut account_infos = vec![
+        source.clone(),
+        mint.clone(),
+        destination.clone(),
+        authority.clone(),
+        token_program.clone(),
+    ];
+    account_infos.extend_from_slice(extra_accounts);
+
+    invoke_signed(&transfer_ix, &account_infos, signer_seeds)
 }
 
-/// Process withdraw instruction
-fn process_withdraw(
+/// Process initialize config instruction
+fn process_initialize_config(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    deposit_seed: &str,  // Use reference
+    dlm_mint: &Pubkey,
+    burn_address: &Pubkey,
+    proof_of_life_cost: u64,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
-    let depositor = next_account_info(account_info_iter)?;
-    let deposit_account = next_account_info(account_info_iter)?;
-    let depositor_token_account = next_account_info(account_info_iter)?;
-    let deposit_token_account = next_account_info(account_info_iter)?;
-    let token_program = next_account_info(account_info_iter)?;
-
-    // CRITICAL: Verify token account ownership and save mint for later validation
-    let token_mint = {
-        let token_account_data = depositor_token_account.data.borrow();
-        let token_account_state = TokenAccount::unpack(&token_account_data)
-            .map_err(|_| ProgramError::InvalidAccountData)?;
-        if token_account_state.owner != *depositor.key {
-            msg!("Token account must be owned by depositor");
-            return Err(ProgramError::InvalidAccountData);
-        }
-        token_account_state.mint
-    };
-
-    // Derive PDA
-    let (deposit_pda, _bump) = Pubkey::find_program_address(
-        &[DEPOSIT_SEED_PREFIX, depositor.key.as_ref(), deposit_seed.as_bytes()],
-        program_id,
-    );
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let program_data = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
 
-    if deposit_account.key != &deposit_pda {
-        return Err(ProgramError::InvalidAccountData);
+    // Verify admin is signer
+    if !admin.is_signer {
+        msg!("Admin must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
     }
 
-    // Deserialize deposit account
-    let mut deposit_state = DepositAccount::try_from_slice(&deposit_account.data.borrow())?;
-
-    // Verify depositor
-    if deposit_state.depositor != *depositor.key {
-        msg!("Only the depositor can withdraw");
-        return Err(ProgramError::MissingRequiredSignature);
+    // Verify system program
+    if system_program.key != &system_program::id() {
+        msg!("Invalid system program");
+        return Err(ProgramError::IncorrectProgramId);
     }
 
-    // Check if already closed
-    if deposit_state.is_closed {
-        msg!("Deposit already withdrawn or claimed");
+    // Only the program upgrade authority may initialize the config, so nobody can front-run it
+    if program_data.key != &instruction::find_program_data_address(program_id)
+        || program_data.owner != &bpf_loader_upgradeable::id()
+    {
+        msg!("Invalid program data account");
         return Err(ProgramError::InvalidAccountData);
     }
+    let upgrade_authority = match limited_deserialize(
+        &program_data.data.borrow(),
+        UpgradeableLoaderState::size_of_programdata_metadata() as u64,
+    ) {
+        Ok(UpgradeableLoaderState::ProgramData { upgrade_authority_address, .. }) => upgrade_authority_address,
+        _ => {
+            msg!("Invalid program data account");
+            return Err(ProgramError::InvalidAccountData);
+        }
+    };
+    if upgrade_authority != Some(*admin.key) {
+        msg!("Only the program upgrade authority can initialize the config");
+        return Err(DielemmaError::NotAdmin.into());
+    }
 
-    // CRITICAL: Mark as closed BEFORE transfer to prevent race condition/double withdrawal
-    deposit_state.is_closed = true;
-    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+    if proof_of_life_cost == 0 {
+        msg!("Proof-of-life cost must be greater than 0");
+        return Err(DielemmaError::InvalidAmount.into());
+    }
 
-    // Verify destination token account matches deposit mint
-    if token_mint != deposit_state.token_mint {
-        msg!("Destination token account mint does not match deposit mint");
-        msg!("Expected: {}", deposit_state.token_mint);
-        msg!("Got: {}", token_mint);
-        return Err(ProgramError::InvalidAccountData);
+    // Derive PDA for config account
+    let (config_pda, bump) = Pubkey::find_program_address(&[CONFIG_SEED_PREFIX], program_id);
+    if config_account.key != &config_pda {
+        msg!("Invalid config account PDA");
+        return Err(DielemmaError::InvalidConfigAccount.into());
     }
 
-    // Get current token balance (scoped to ensure borrow is released before we borrow again)
-    let token_amount = {
-        let token_account_data = deposit_token_account.data.borrow();
-        let token_account_state = TokenAccount::unpack(&token_account_data)?;
-        token_account_state.amount
-    }; // token_account_data is dropped here
+    // Check if config account already exists
+    if config_account.lamports() > 0 {
+        msg!("Config account already exists");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
 
-    // Transfer tokens back to depositor (from deposit_token_account to depositor_token_account)
-    let transfer_ix = transfer(
-        &spl_token::id(),
-        deposit_token_account.key,      // Source: deposit's token account
-        depositor_token_account.key,    // Destination: depositor's ATA
-        deposit_account.key,
-        &[],
-        token_amount,
-    )?;
+    let rent = Rent::get()?;
+    let create_config_account_ix = system_instruction::create_account(
+        admin.key,
+        config_account.key,
+        rent.minimum_balance(CONFIG_ACCOUNT_SIZE),
+        CONFIG_ACCOUNT_SIZE as u64,
+        program_id,
+    );
 
     invoke_signed(
-        &transfer_ix,
+        &create_config_account_ix,
         &[
-            deposit_token_account.clone(),
-            depositor_token_account.clone(),
-            deposit_account.clone(),
-            token_program.clone(),
+            admin.clone(),
+            config_account.clone(),
+            system_program.clone(),
         ],
-        &[&[
-            DEPOSIT_SEED_PREFIX,
-            depositor.key.as_ref(),
-            deposit_seed.as_bytes(),
-            &[deposit_state.bump],
-        ]],
+        &[&[CONFIG_SEED_PREFIX, &[bump]]],
     )?;
 
-    msg!("Withdrawal successful: {} tokens", token_amount);
+    let config = ConfigAccount {
+        admin: *admin.key,
+        pending_admin: Pubkey::default(),
+        dlm_mint: *dlm_mint,
+        burn_address: *burn_address,
+        proof_of_life_cost,
+        claim_challenge_seconds: DEFAULT_CLAIM_CHALLENGE_SECONDS,
+        paused: false,
+        bump,
+    };
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    msg!("Config initialized: admin {}, DLM mint {}, burn address {}, cost {}", admin.key, dlm_mint, burn_address, proof_of_life_cost);
     Ok(())
 }
 
-/// Process claim instruction
-fn process_claim(
+/// Process set paused instruction
+fn process_set_paused(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    deposit_seed: &str,  // Use reference
+    paused: bool,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
-    let receiver = next_account_info(account_info_iter)?;
-    let deposit_account = next_account_info(account_info_iter)?;
-    let receiver_token_account = next_account_info(account_info_iter)?;
-    let deposit_token_account = next_account_info(account_info_iter)?;
-    let token_program = next_account_info(account_info_iter)?;
-
-    // CRITICAL: Verify token account ownership and save mint for later validation
-    let token_mint = {
-        let token_account_data = receiver_token_account.data.borrow();
-        let token_account_state = TokenAccount::unpack(&token_account_data)
-            .map_err(|_| ProgramError::InvalidAccountData)?;
-        if token_account_state.owner != *receiver.key {
-            msg!("Token account must be owned by receiver");
-            return Err(ProgramError::InvalidAccountData);
-        }
-        token_account_state.mint
-    };
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
 
-    // Deserialize deposit account once (mutable from start to avoid double deserialization)
-    let mut deposit_state = DepositAccount::try_from_slice(&deposit_account.data.borrow())?;
+    let mut config = load_config_as_admin(program_id, admin, config_account)?;
+    config.paused = paused;
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
 
-    // Derive PDA
-    let (deposit_pda, _bump) = Pubkey::find_program_address(
-        &[DEPOSIT_SEED_PREFIX, deposit_state.depositor.as_ref(), deposit_seed.as_bytes()],
-        program_id,
-    );
+    msg!("Pause toggled: {}", paused);
+    Ok(())
+}
 
-    if deposit_account.key != &deposit_pda {
-        return Err(ProgramError::InvalidAccountData);
-    }
+/// Process set claim challenge period instruction
+fn process_set_claim_challenge_period(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    challenge_seconds: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
 
-    // Verify receiver
-    if deposit_state.receiver != *receiver.key {
-        msg!("Only the designated receiver can claim");
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
 
-    // Verify receiver is signer
-    if !receiver.is_signer {
-        msg!("Receiver must sign the claim transaction");
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    let mut config = load_config_as_admin(program_id, admin, config_account)?;
 
-    // Check if already closed
-    if deposit_state.is_closed {
-        msg!("Deposit already withdrawn or claimed");
-        return Err(ProgramError::InvalidAccountData);
+    if challenge_seconds > MAX_CLAIM_CHALLENGE_SECONDS {
+        msg!("Challenge period must be at most {} seconds", MAX_CLAIM_CHALLENGE_SECONDS);
+        return Err(DielemmaError::InvalidChallengePeriod.into());
     }
 
-    // Check if proof-of-life has expired
-    let clock = Clock::get()?;
+    config.claim_challenge_seconds = challenge_seconds;
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
 
-    // Validate timestamp is not in the future
-    if deposit_state.last_proof_timestamp > clock.unix_timestamp {
-        msg!("Invalid last_proof_timestamp: future date detected");
-        return Err(ProgramError::InvalidAccountData);
-    }
+    msg!("Claim challenge period set to {} seconds", challenge_seconds);
+    Ok(())
+}
 
-    // Validate timestamp is not unreasonably old (before Solana genesis)
-    const MIN_VALID_TIMESTAMP: i64 = 1598000000; // ~August 2020
-    if deposit_state.last_proof_timestamp < MIN_VALID_TIMESTAMP {
-        msg!("Invalid last_proof_timestamp: unreasonably old date");
-        return Err(ProgramError::InvalidAccountData);
-    }
+/// Process update config instruction
+fn process_update_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    dlm_mint: &Pubkey,
+    burn_address: &Pubkey,
+    proof_of_life_cost: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
 
-    let elapsed = clock.unix_timestamp - deposit_state.last_proof_timestamp;
-    if elapsed < deposit_state.timeout_seconds as i64 {
-        msg!(
-            "Proof of life has not expired yet. Elapsed: {}, Required: {}",
-            elapsed,
-            deposit_state.timeout_seconds
-        );
-        return Err(ProgramError::InvalidAccountData);
-    }
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
 
-    // CRITICAL: Mark as closed BEFORE transfer to prevent race condition/double claim
-    deposit_state.is_closed = true;
-    deposit_state.serialize(&mut &mut deposit_account.data.borrow_mut()[..])?;
+    let mut config = load_config_as_admin(program_id, admin, config_account)?;
 
-    // Verify destination token account matches deposit mint
-    if token_mint != deposit_state.token_mint {
-        msg!("Destination token account mint does not match deposit mint");
-        msg!("Expected: {}", deposit_state.token_mint);
-        msg!("Got: {}", token_mint);
-        return Err(ProgramError::InvalidAccountData);
+    if proof_of_life_cost == 0 {
+        msg!("Proof-of-life cost must be greater than 0");
+        return Err(DielemmaError::InvalidAmount.into());
     }
 
-    // Get current token balance (scoped to ensure borrow is released before we borrow again)
-    let token_amount = {
-        let token_account_data = deposit_token_account.data.borrow();
-        let token_account_state = TokenAccount::unpack(&token_account_data)?;
-        token_account_state.amount
-    }; // token_account_data is dropped here
-
-    // Transfer tokens to receiver (from deposit_token_account to receiver_token_account)
-    let transfer_ix = transfer(
-        &spl_token::id(),
-        deposit_token_account.key,      // Source: deposit's token account
-        receiver_token_account.key,     // Destination: receiver's ATA
-        deposit_account.key,
-        &[],
-        token_amount,
-    )?;
-
-    invoke_signed(
-        &transfer_ix,
-        &[
-            deposit_token_account.clone(),
-            receiver_token_account.clone(),
-            deposit_account.clone(),
-            token_program.clone(),
-        ],
-        &[&[
-            DEPOSIT_SEED_PREFIX,
-            deposit_state.depositor.as_ref(),
-            deposit_seed.as_bytes(),
-            &[deposit_state.bump],
-        ]],
-    )?;
+    msg!("Official token updated: {} -> {}", config.dlm_mint, dlm_mint);
+    config.dlm_mint = *dlm_mint;
+    config.burn_address = *burn_address;
+    config.proof_of_life_cost = proof_of_life_cost;
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
 
-    msg!("Claim successful: {} tokens transferred to receiver", token_amount);
+    msg!("Config updated: burn address {}, cost {}", burn_address, proof_of_life_cost);
     Ok(())
 }
 
-/// Process close account instruction
-fn process_close_account(
+/// Process propose admin instruction
+fn process_propose_admin(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    deposit_seed: &str,  // Use reference
+    new_admin: &Pubkey,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
-    let authority = next_account_info(account_info_iter)?;
-    let deposit_account = next_account_info(account_info_iter)?;
-    let refund_recipient = next_account_info(account_info_iter)?;
-    let _system_program = next_account_info(account_info_iter)?;
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
 
-    // Deserialize deposit account
-    let deposit_state = DepositAccount::try_from_slice(&deposit_account.data.borrow())?;
+    let mut config = load_config_as_admin(program_id, admin, config_account)?;
+    config.pending_admin = *new_admin;
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
 
-    // Derive PDA
-    let (deposit_pda, _bump) = Pubkey::find_program_address(
-        &[DEPOSIT_SEED_PREFIX, deposit_state.depositor.as_ref(), deposit_seed.as_bytes()],
-        program_id,
-    );
+    msg!("Admin handover proposed: {} -> {}", admin.key, new_admin);
+    Ok(())
+}
 
-    if deposit_account.key != &deposit_pda {
-        return Err(ProgramError::InvalidAccountData);
-    }
+/// Process accept admin instruction
+fn process_accept_admin(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
 
-    // Verify authority (must be depositor or receiver)
-    if deposit_state.depositor != *authority.key && deposit_state.receiver != *authority.key {
-        msg!("Only depositor or receiver can close the account");
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    let new_admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
 
-    // Verify authority is signer
-    if !authority.is_signer {
-        msg!("Authority must sign the transaction");
+    // Verify new admin is signer
+    if !new_admin.is_signer {
+        msg!("Pending admin must sign the transaction");
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    // Check if tokens have been withdrawn/claimed
-    if !deposit_state.is_closed {
-        msg!("Cannot close account with active tokens");
-        return Err(ProgramError::InvalidAccountData);
+    let mut config = load_config(program_id, config_account)?;
+
+    // Default pubkey means no handover is pending
+    if config.pending_admin == Pubkey::default() || config.pending_admin != *new_admin.key {
+        msg!("Only the pending admin can accept the handover");
+        return Err(DielemmaError::NotPendingAdmin.into());
     }
 
-    // Close account and transfer lamports
-    let close_lamports = deposit_account.lamports();
-    **deposit_account.lamports.borrow_mut() = 0;
-    **refund_recipient.lamports.borrow_mut() += close_lamports;
+    let previous_admin = config.admin;
+    config.admin = *new_admin.key;
+    config.pending_admin = Pubkey::default();
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
 
-    msg!("Account closed, {} lamports refunded", close_lamports);
+    msg!("Ownership transferred: {} -> {}", previous_admin, new_admin.key);
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use solana_sdk::{
-        signature::{Keypair, Signer},
-        transaction::Transaction,
-    };
 
     #[test]
     fn test_instruction_packing() {
@@ -1073,6 +5007,7 @@ mod tests {
             receiver,
             amount: 1000,
             timeout_seconds: 86400,
+            withdraw_locked_until: 0,
         };
 
         let serialized = instruction.try_to_vec().unwrap();
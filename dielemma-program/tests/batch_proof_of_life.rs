@@ -0,0 +1,93 @@
+//! BatchProofOfLife: refreshing several deposits of one depositor for a single DLM cost
+
+mod common;
+
+use common::*;
+use dielemma_program::{instruction, DielemmaError};
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+
+const AMOUNT: u64 = 500_000;
+const SEEDS: [&str; 3] = ["first", "second", "third"];
+
+#[tokio::test]
+async fn test_batch_refreshes_all_deposits_for_one_cost() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    for seed in SEEDS {
+        env.deposit(seed, AMOUNT / 4, DAY).await.unwrap();
+    }
+
+    env.warp_after_last_proof(SEEDS[0], DAY as i64).await;
+    env.initiate_claim(SEEDS[1]).await.unwrap();
+    let expected_timestamp = now(&mut env.context).await;
+    env.batch_proof_of_life(&SEEDS).await.unwrap();
+
+    for seed in SEEDS {
+        let deposit = env.deposit_state(seed).await;
+        assert_eq!(deposit.last_proof_timestamp, expected_timestamp);
+        assert!(!deposit.has_pending_claim());
+    }
+    assert_eq!(env.balance(env.depositor_dlm).await, 9 * ONE_DLM);
+    assert_eq!(env.balance(env.burn_dlm).await, ONE_DLM);
+    assert_dielemma_error(env.claim(SEEDS[1]).await, DielemmaError::NotExpired);
+}
+
+#[tokio::test]
+async fn test_batch_is_all_or_nothing() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    for seed in SEEDS {
+        env.deposit(seed, AMOUNT / 4, DAY).await.unwrap();
+    }
+    env.withdraw(SEEDS[2]).await.unwrap();
+    let deposited_at = env.deposit_state(SEEDS[0]).await.last_proof_timestamp;
+
+    env.warp_after_last_proof(SEEDS[0], 100).await;
+    assert_dielemma_error(env.batch_proof_of_life(&SEEDS).await, DielemmaError::AlreadyClosed);
+    assert_eq!(env.deposit_state(SEEDS[0]).await.last_proof_timestamp, deposited_at);
+    assert_eq!(env.balance(env.depositor_dlm).await, 10 * ONE_DLM);
+
+    env.set_paused(true).await.unwrap();
+    assert_dielemma_error(env.batch_proof_of_life(&SEEDS[..2]).await, DielemmaError::ProgramPaused);
+}
+
+#[tokio::test]
+async fn test_batch_rejects_invalid_lists() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    for seed in SEEDS {
+        env.deposit(seed, AMOUNT / 4, DAY).await.unwrap();
+    }
+
+    assert_dielemma_error(env.batch_proof_of_life(&[]).await, DielemmaError::InvalidBatch);
+    assert_dielemma_error(env.batch_proof_of_life(&[SEEDS[0]; 11]).await, DielemmaError::InvalidBatch);
+    assert_dielemma_error(
+        env.batch_proof_of_life(&[SEEDS[0], SEEDS[1], SEEDS[0]]).await,
+        DielemmaError::InvalidBatch,
+    );
+
+    // Each account must be the PDA of the seed at the same position
+    let depositor = env.depositor.insecure_clone();
+    let mut ix = env.batch_proof_of_life_ix(&SEEDS[..2]);
+    ix.accounts.swap(6, 7);
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        DielemmaError::InvalidDepositAccount,
+    );
+}
+
+#[tokio::test]
+async fn test_batch_requires_depositor() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEEDS[0], AMOUNT, DAY).await.unwrap();
+    let delegate = env.new_delegate();
+    env.add_delegate(SEEDS[0], &delegate.pubkey(), 0).await.unwrap();
+
+    // Delegates refresh deposits one at a time through ProofOfLife
+    let mut ix = env.batch_proof_of_life_ix(&SEEDS[..1]);
+    ix.accounts[0].pubkey = delegate.pubkey();
+    ix.accounts[1].pubkey =
+        instruction::get_associated_token_address(&delegate.pubkey(), &env.dlm_mint, &spl_token_2022::id());
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&delegate]).await,
+        DielemmaError::NotDepositor,
+    );
+}
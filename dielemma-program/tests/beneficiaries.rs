@@ -0,0 +1,277 @@
+//! Deposits split between several beneficiaries by basis-point shares, and receiver changes
+
+mod common;
+
+use common::*;
+use dielemma_program::{DielemmaError, MAX_BENEFICIARIES};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+const SEED: &str = "test-seed";
+const AMOUNT: u64 = 1_000_001;
+
+#[tokio::test]
+async fn test_each_beneficiary_claims_their_share() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let (second, second_token) = env.new_receiver();
+    let (third, third_token) = env.new_receiver();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.set_beneficiaries(
+        SEED,
+        &[(env.receiver.pubkey(), 5_000), (second.pubkey(), 3_000), (third.pubkey(), 2_000)],
+    )
+    .await
+    .unwrap();
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim_as(SEED, &second, &second_token).await.unwrap();
+    assert_eq!(env.balance(second_token).await, 300_000);
+    assert!(!env.deposit_state(SEED).await.is_closed);
+    assert_dielemma_error(
+        env.claim_as(SEED, &second, &second_token).await,
+        DielemmaError::ShareAlreadyClaimed,
+    );
+
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, 500_000);
+    assert!(!env.deposit_state(SEED).await.is_closed);
+
+    // The last claim sweeps the rounding remainder
+    env.claim_as(SEED, &third, &third_token).await.unwrap();
+    assert_eq!(env.balance(third_token).await, 200_001);
+    assert_eq!(env.balance(env.vault_address(SEED)).await, 0);
+
+    let deposit = env.deposit_state(SEED).await;
+    assert!(deposit.is_closed);
+    assert_eq!(deposit.claimed_amount, AMOUNT);
+    assert!(deposit.beneficiaries().iter().all(|b| b.claimed));
+
+    // Any beneficiary may close the paid-out deposit
+    let ix = env.close_ix(SEED, &third.pubkey());
+    process(&mut env.context, &[ix], &[&third]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_proof_of_life_keeps_claimed_shares() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let (second, second_token) = env.new_receiver();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.set_beneficiaries(SEED, &[(env.receiver.pubkey(), 5_000), (second.pubkey(), 5_000)])
+        .await
+        .unwrap();
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, 500_000);
+
+    // A paid-out share is not owed again after the depositor returns and expires anew
+    env.proof_of_life(SEED).await.unwrap();
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim_as(SEED, &second).await;
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::ShareAlreadyClaimed);
+    env.claim_as(SEED, &second, &second_token).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, 500_000);
+    assert_eq!(env.balance(second_token).await, 500_001);
+    assert!(env.deposit_state(SEED).await.is_closed);
+}
+
+#[tokio::test]
+async fn test_set_beneficiaries_validates_shares() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let receiver = env.receiver.pubkey();
+    let other = Pubkey::new_unique();
+
+    for shares in [
+        vec![],
+        vec![(receiver, 5_000), (other, 4_999)],
+        vec![(receiver, 5_000), (other, 5_001)],
+        vec![(receiver, 10_000), (other, 0)],
+        vec![(receiver, 5_000), (receiver, 5_000)],
+        (0..=MAX_BENEFICIARIES).map(|_| (Pubkey::new_unique(), 1)).collect(),
+    ] {
+        assert_dielemma_error(
+            env.set_beneficiaries(SEED, &shares).await,
+            DielemmaError::InvalidBeneficiaries,
+        );
+    }
+
+    let shares: Vec<_> = (0..MAX_BENEFICIARIES).map(|_| (Pubkey::new_unique(), 1_000)).collect();
+    env.set_beneficiaries(SEED, &shares).await.unwrap();
+    assert_eq!(env.deposit_state(SEED).await.beneficiaries().len(), MAX_BENEFICIARIES);
+}
+
+#[tokio::test]
+async fn test_set_beneficiaries_rejects_invalid_requests() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let (second, second_token) = env.new_receiver();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let shares = [(env.receiver.pubkey(), 5_000), (second.pubkey(), 5_000)];
+
+    // Only the depositor can change beneficiaries: the PDA is derived from the signer
+    let receiver = env.receiver.insecure_clone();
+    let mut ix = dielemma_program::instruction::set_beneficiaries(
+        &dielemma_program::id(),
+        &receiver.pubkey(),
+        SEED,
+        &shares,
+    );
+    ix.accounts[1].pubkey = env.deposit_address(SEED);
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&receiver]).await,
+        DielemmaError::InvalidDepositAccount,
+    );
+
+    // Removed receivers lose their claim
+    env.set_beneficiaries(SEED, &[(second.pubkey(), 10_000)]).await.unwrap();
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::NotReceiver);
+
+    // Shares are frozen once one of them has been paid out
+    env.set_beneficiaries(SEED, &shares).await.unwrap();
+    env.wait_out_claim(SEED).await;
+    env.claim_as(SEED, &second, &second_token).await.unwrap();
+    assert_dielemma_error(
+        env.set_beneficiaries(SEED, &[(env.receiver.pubkey(), 10_000)]).await,
+        DielemmaError::ShareAlreadyClaimed,
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_after_partial_claim_returns_remainder() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let (second, second_token) = env.new_receiver();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.set_beneficiaries(SEED, &[(env.receiver.pubkey(), 2_500), (second.pubkey(), 7_500)])
+        .await
+        .unwrap();
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    env.withdraw(SEED).await.unwrap();
+
+    assert_eq!(env.balance(env.receiver_token).await, 250_000);
+    assert_eq!(env.balance(env.depositor_token).await, INITIAL_BALANCE - 250_000);
+    assert_dielemma_error(env.claim_as(SEED, &second, &second_token).await, DielemmaError::AlreadyClosed);
+}
+
+#[tokio::test]
+async fn test_sol_deposit_split_between_beneficiaries() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let second = Keypair::new();
+    fund(&mut env.context, &second.pubkey(), 1_000_000_000);
+    env.deposit_sol(SEED, 3_000_000_000, DAY).await.unwrap();
+    env.set_beneficiaries(SEED, &[(env.receiver.pubkey(), 6_000), (second.pubkey(), 4_000)])
+        .await
+        .unwrap();
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+
+    let before = env.lamports(env.receiver.pubkey()).await;
+    env.claim_sol(SEED, None).await.unwrap();
+    assert_eq!(env.lamports(env.receiver.pubkey()).await, before + 1_800_000_000);
+
+    let before = env.lamports(second.pubkey()).await;
+    let ix = dielemma_program::instruction::claim_sol(
+        &dielemma_program::id(),
+        &second.pubkey(),
+        &env.depositor.pubkey(),
+        None,
+        SEED,
+        false,
+    );
+    process(&mut env.context, &[ix], &[&second]).await.unwrap();
+    assert_eq!(env.lamports(second.pubkey()).await, before + 1_200_000_000);
+    assert!(env.deposit_state(SEED).await.is_closed);
+}
+
+#[tokio::test]
+async fn test_update_receiver_hands_over_share() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let (heir, heir_token) = env.new_receiver();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let old_receiver = env.receiver.pubkey();
+
+    env.warp_after_last_proof(SEED, DAY as i64 / 2).await;
+    env.update_receiver(SEED, &old_receiver, &heir.pubkey(), false).await.unwrap();
+
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.beneficiaries()[0].receiver, heir.pubkey());
+    assert_eq!(deposit.beneficiaries()[0].share_bps, 10_000);
+
+    // The timer was left alone, so the new receiver can claim after the original timeout
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::NotReceiver);
+    env.wait_out_claim_as(SEED, &heir).await;
+    env.claim_as(SEED, &heir, &heir_token).await.unwrap();
+    assert_eq!(env.balance(heir_token).await, AMOUNT);
+}
+
+#[tokio::test]
+async fn test_update_receiver_can_reset_timer() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let old_receiver = env.receiver.pubkey();
+    let new_receiver = Pubkey::new_unique();
+
+    env.warp_after_last_proof(SEED, DAY as i64 - 10).await;
+    let expected_timestamp = now(&mut env.context).await;
+    env.update_receiver(SEED, &old_receiver, &new_receiver, true).await.unwrap();
+    assert_eq!(env.deposit_state(SEED).await.last_proof_timestamp, expected_timestamp);
+}
+
+#[tokio::test]
+async fn test_update_receiver_rejects_invalid_requests() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let (second, second_token) = env.new_receiver();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let receiver = env.receiver.pubkey();
+    env.set_beneficiaries(SEED, &[(receiver, 5_000), (second.pubkey(), 5_000)])
+        .await
+        .unwrap();
+
+    let stranger = Pubkey::new_unique();
+    assert_dielemma_error(
+        env.update_receiver(SEED, &stranger, &Pubkey::new_unique(), false).await,
+        DielemmaError::NotReceiver,
+    );
+    assert_dielemma_error(
+        env.update_receiver(SEED, &receiver, &second.pubkey(), false).await,
+        DielemmaError::InvalidBeneficiaries,
+    );
+
+    // The receiver cannot redirect the deposit to themselves
+    let receiver_keypair = env.receiver.insecure_clone();
+    let mut ix = dielemma_program::instruction::update_receiver(
+        &dielemma_program::id(),
+        &receiver,
+        SEED,
+        &second.pubkey(),
+        &receiver,
+        false,
+    );
+    ix.accounts[1].pubkey = env.deposit_address(SEED);
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&receiver_keypair]).await,
+        DielemmaError::InvalidDepositAccount,
+    );
+
+    // A paid-out share cannot be redirected
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim_as(SEED, &second, &second_token).await.unwrap();
+    assert_dielemma_error(
+        env.update_receiver(SEED, &second.pubkey(), &stranger, false).await,
+        DielemmaError::ShareAlreadyClaimed,
+    );
+
+    env.withdraw(SEED).await.unwrap();
+    assert_dielemma_error(
+        env.update_receiver(SEED, &receiver, &stranger, false).await,
+        DielemmaError::AlreadyClosed,
+    );
+}
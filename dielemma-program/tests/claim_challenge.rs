@@ -0,0 +1,115 @@
+//! Two-phase claims: InitiateClaim, the challenge period, CancelClaim and finalizing with Claim
+
+mod common;
+
+use common::*;
+use dielemma_program::{DielemmaError, DEFAULT_CLAIM_CHALLENGE_SECONDS, MAX_CLAIM_CHALLENGE_SECONDS};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+
+const SEED: &str = "test-seed";
+const AMOUNT: u64 = 500_000;
+
+#[tokio::test]
+async fn test_claim_finalizes_after_challenge_period() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    env.warp_after_last_proof(SEED, DAY as i64 - 1).await;
+    assert_dielemma_error(env.initiate_claim(SEED).await, DielemmaError::NotExpired);
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::ClaimNotInitiated);
+    let initiated_at = now(&mut env.context).await;
+    env.initiate_claim(SEED).await.unwrap();
+
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.claim_initiator, env.receiver.pubkey());
+    assert_eq!(
+        deposit.claim_finalizable_at,
+        initiated_at + DEFAULT_CLAIM_CHALLENGE_SECONDS as i64
+    );
+    assert_dielemma_error(env.initiate_claim(SEED).await, DielemmaError::ClaimAlreadyInitiated);
+
+    set_clock(&mut env.context, deposit.claim_finalizable_at - 1).await;
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::ChallengePeriodActive);
+
+    set_clock(&mut env.context, deposit.claim_finalizable_at).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT);
+}
+
+#[tokio::test]
+async fn test_proof_of_life_voids_pending_claim() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.initiate_claim(SEED).await.unwrap();
+
+    env.proof_of_life(SEED).await.unwrap();
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.claim_initiator, Pubkey::default());
+    assert_eq!(deposit.claim_finalizable_at, 0);
+
+    // Even once the deposit expires again, the old claim cannot be finalized
+    env.warp_after_last_proof(SEED, DAY as i64 + DEFAULT_CLAIM_CHALLENGE_SECONDS as i64).await;
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::ClaimNotInitiated);
+}
+
+#[tokio::test]
+async fn test_cancel_claim() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    assert_dielemma_error(env.cancel_claim(SEED).await, DielemmaError::ClaimNotInitiated);
+
+    env.initiate_claim(SEED).await.unwrap();
+    let finalizable_at = env.deposit_state(SEED).await.claim_finalizable_at;
+
+    // Only the depositor can cancel
+    let receiver = env.receiver.insecure_clone();
+    let mut ix = dielemma_program::instruction::cancel_claim(&dielemma_program::id(), &receiver.pubkey(), SEED);
+    ix.accounts[1].pubkey = env.deposit_address(SEED);
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&receiver]).await,
+        DielemmaError::InvalidDepositAccount,
+    );
+
+    // Cancelling works while the program is paused
+    env.set_paused(true).await.unwrap();
+    env.cancel_claim(SEED).await.unwrap();
+    env.set_paused(false).await.unwrap();
+
+    set_clock(&mut env.context, finalizable_at).await;
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::ClaimNotInitiated);
+
+    // The deposit is still expired, so a new claim can be started
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_set_claim_challenge_period() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    assert_dielemma_error(
+        env.set_claim_challenge_period(MAX_CLAIM_CHALLENGE_SECONDS + 1).await,
+        DielemmaError::InvalidChallengePeriod,
+    );
+
+    let intruder = env.receiver.insecure_clone();
+    let ix = dielemma_program::instruction::set_claim_challenge_period(&dielemma_program::id(), &intruder.pubkey(), 0);
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&intruder]).await,
+        DielemmaError::NotAdmin,
+    );
+
+    // Without a challenge period the claim can be finalized right away
+    env.set_claim_challenge_period(0).await.unwrap();
+    assert_eq!(env.config().await.claim_challenge_seconds, 0);
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.initiate_claim(SEED).await.unwrap();
+    env.claim(SEED).await.unwrap();
+}
@@ -0,0 +1,900 @@
+//! Shared helpers for the Dielemma integration tests
+//!
+//! Mints and token accounts are written straight into the test bank instead of
+//! going through the token programs, so tests can place the stand-in DLM mint at
+//! the hardcoded `OFFICIAL_DLM_TOKEN_MINT` address.
+
+#![allow(dead_code)]
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use dielemma_program::{
+    instruction, ConfigAccount, DepositAccount, DielemmaError, LegacyDepositAccount, ProfileAccount,
+    MAX_DEPOSIT_SEED_LENGTH, OFFICIAL_DLM_TOKEN_MINT,
+};
+use solana_program::{program_option::COption, program_pack::Pack, pubkey::Pubkey, rent::Rent};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    bpf_loader_upgradeable,
+    clock::Clock,
+    instruction::{Instruction, InstructionError},
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::{Transaction, TransactionError},
+};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::{TransferFee, TransferFeeAmount, TransferFeeConfig},
+        ExtensionType, StateWithExtensions, StateWithExtensionsMut,
+    },
+    state::{Account as TokenAccount, AccountState, Mint},
+};
+
+/// DLM token decimals
+pub const DLM_DECIMALS: u8 = 6;
+/// Cost of one proof-of-life (1 DLM)
+pub const ONE_DLM: u64 = 1_000_000;
+/// One day in seconds
+pub const DAY: u64 = 86_400;
+/// Tokens minted to the depositor's token account
+pub const INITIAL_BALANCE: u64 = 1_000_000_000;
+
+pub fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::new(
+        "dielemma_program",
+        dielemma_program::id(),
+        processor!(dielemma_program::process_instruction),
+    );
+    program_test.prefer_bpf(false);
+    program_test
+}
+
+pub fn dlm_mint() -> Pubkey {
+    OFFICIAL_DLM_TOKEN_MINT.parse().unwrap()
+}
+
+/// Write an initialized mint with no extensions
+pub fn set_mint(context: &mut ProgramTestContext, address: &Pubkey, decimals: u8, token_program: &Pubkey) {
+    let mut data = vec![0u8; Mint::LEN];
+    Mint::pack(
+        Mint {
+            mint_authority: COption::None,
+            supply: u64::MAX / 2,
+            decimals,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        },
+        &mut data,
+    )
+    .unwrap();
+    set_account(context, address, data, token_program);
+}
+
+/// Write an initialized token account with no extensions
+pub fn set_token_account(
+    context: &mut ProgramTestContext,
+    address: &Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+    token_program: &Pubkey,
+) {
+    let mut data = vec![0u8; TokenAccount::LEN];
+    TokenAccount::pack(
+        TokenAccount {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        },
+        &mut data,
+    )
+    .unwrap();
+    set_account(context, address, data, token_program);
+}
+
+/// Write an initialized Token-2022 mint carrying the transfer-fee extension
+pub fn set_transfer_fee_mint(
+    context: &mut ProgramTestContext,
+    address: &Pubkey,
+    decimals: u8,
+    transfer_fee_basis_points: u16,
+    maximum_fee: u64,
+) {
+    let mut data = vec![0u8; ExtensionType::get_account_len::<Mint>(&[ExtensionType::TransferFeeConfig])];
+    let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    let fee = TransferFee {
+        epoch: 0.into(),
+        maximum_fee: maximum_fee.into(),
+        transfer_fee_basis_points: transfer_fee_basis_points.into(),
+    };
+    let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+    config.older_transfer_fee = fee;
+    config.newer_transfer_fee = fee;
+    state.base = Mint {
+        mint_authority: COption::None,
+        supply: u64::MAX / 2,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    set_account(context, address, data, &spl_token_2022::id());
+}
+
+/// Write an initialized Token-2022 token account for a transfer-fee mint
+pub fn set_transfer_fee_token_account(
+    context: &mut ProgramTestContext,
+    address: &Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) {
+    let mut data = vec![0u8; ExtensionType::get_account_len::<TokenAccount>(&[ExtensionType::TransferFeeAmount])];
+    let mut state = StateWithExtensionsMut::<TokenAccount>::unpack_uninitialized(&mut data).unwrap();
+    state.init_extension::<TransferFeeAmount>(true).unwrap();
+    state.base = TokenAccount {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        delegate: COption::None,
+        state: AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    set_account(context, address, data, &spl_token_2022::id());
+}
+
+pub fn set_account(context: &mut ProgramTestContext, address: &Pubkey, data: Vec<u8>, owner: &Pubkey) {
+    let account = Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: *owner,
+        executable: false,
+        rent_epoch: 0,
+    };
+    context.set_account(address, &account.into());
+}
+
+/// Write this program's program data account with `authority` as upgrade authority
+pub fn set_upgrade_authority(context: &mut ProgramTestContext, authority: &Pubkey) {
+    // bincode layout of UpgradeableLoaderState::ProgramData { slot, upgrade_authority_address }
+    let mut data = 3u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(authority.as_ref());
+    let program_data = instruction::find_program_data_address(&dielemma_program::id());
+    set_account(context, &program_data, data, &bpf_loader_upgradeable::id());
+}
+
+/// Give a wallet SOL to pay for rent and fees
+pub fn fund(context: &mut ProgramTestContext, address: &Pubkey, lamports: u64) {
+    let account = Account {
+        lamports,
+        data: vec![],
+        owner: system_program::id(),
+        executable: false,
+        rent_epoch: 0,
+    };
+    context.set_account(address, &account.into());
+}
+
+pub async fn token_balance(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    let account = context.banks_client.get_account(*address).await.unwrap().unwrap();
+    StateWithExtensions::<TokenAccount>::unpack(&account.data).unwrap().base.amount
+}
+
+pub async fn account_data_len(context: &mut ProgramTestContext, address: &Pubkey) -> usize {
+    context.banks_client.get_account(*address).await.unwrap().unwrap().data.len()
+}
+
+pub async fn get_deposit(context: &mut ProgramTestContext, address: &Pubkey) -> DepositAccount {
+    let account = context.banks_client.get_account(*address).await.unwrap().unwrap();
+    DepositAccount::try_from_slice(&account.data).unwrap()
+}
+
+pub async fn lamports(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .map(|account| account.lamports)
+        .unwrap_or(0)
+}
+
+/// Send instructions paid for by the context payer and signed by `signers`
+pub async fn process(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+pub async fn now(context: &mut ProgramTestContext) -> i64 {
+    context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp
+}
+
+/// Move the bank clock to an absolute unix timestamp
+pub async fn set_clock(context: &mut ProgramTestContext, unix_timestamp: i64) {
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    context.set_sysvar(&clock);
+}
+
+pub fn assert_instruction_error(result: Result<(), BanksClientError>, expected: InstructionError) {
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, expected)
+    );
+}
+
+pub fn assert_dielemma_error(result: Result<(), BanksClientError>, expected: DielemmaError) {
+    assert_instruction_error(result, InstructionError::Custom(expected.code()));
+}
+
+/// Like `assert_dielemma_error`, for the instruction at `index` of the transaction
+pub fn assert_dielemma_error_at(result: Result<(), BanksClientError>, index: u8, expected: DielemmaError) {
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(index, InstructionError::Custom(expected.code()))
+    );
+}
+
+/// A depositor and receiver holding accounts for a freshly created mint,
+/// plus a funded DLM balance for proof-of-life
+pub struct TestEnv {
+    pub context: ProgramTestContext,
+    pub admin: Keypair,
+    pub depositor: Keypair,
+    pub receiver: Keypair,
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    pub depositor_token: Pubkey,
+    pub receiver_token: Pubkey,
+    pub dlm_mint: Pubkey,
+    pub depositor_dlm: Pubkey,
+    pub burn_address: Pubkey,
+    pub burn_dlm: Pubkey,
+    /// Seeds of the deposits linked to the depositor's liveness profile
+    pub linked_seeds: Vec<String>,
+}
+
+impl TestEnv {
+    /// Environment with the config initialized by the upgrade authority
+    pub async fn new(token_program: Pubkey) -> Self {
+        let mut env = Self::new_without_config(token_program).await;
+        env.initialize_config().await.unwrap();
+        env
+    }
+
+    pub async fn new_without_config(token_program: Pubkey) -> Self {
+        let mut context = program_test().start_with_context().await;
+        let admin = Keypair::new();
+        let depositor = Keypair::new();
+        let receiver = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let depositor_token = Pubkey::new_unique();
+        let receiver_token = Pubkey::new_unique();
+
+        fund(&mut context, &admin.pubkey(), 10_000_000_000);
+        set_upgrade_authority(&mut context, &admin.pubkey());
+        fund(&mut context, &depositor.pubkey(), 10_000_000_000);
+        fund(&mut context, &receiver.pubkey(), 10_000_000_000);
+        set_mint(&mut context, &mint, 9, &token_program);
+        set_token_account(&mut context, &depositor_token, &mint, &depositor.pubkey(), INITIAL_BALANCE, &token_program);
+        set_token_account(&mut context, &receiver_token, &mint, &receiver.pubkey(), 0, &token_program);
+
+        // Stand-in DLM mint at the official address, with Token-2022 ATAs
+        let dlm_mint = dlm_mint();
+        let token_2022 = spl_token_2022::id();
+        let depositor_dlm = instruction::get_associated_token_address(&depositor.pubkey(), &dlm_mint, &token_2022);
+        let burn_address = instruction::default_burn_address();
+        let burn_dlm = instruction::get_burn_token_address(&burn_address, &dlm_mint);
+        set_mint(&mut context, &dlm_mint, DLM_DECIMALS, &token_2022);
+        set_token_account(&mut context, &depositor_dlm, &dlm_mint, &depositor.pubkey(), 10 * ONE_DLM, &token_2022);
+        set_token_account(&mut context, &burn_dlm, &dlm_mint, &burn_address, 0, &token_2022);
+
+        Self {
+            context,
+            admin,
+            depositor,
+            receiver,
+            mint,
+            token_program,
+            depositor_token,
+            receiver_token,
+            dlm_mint,
+            depositor_dlm,
+            burn_address,
+            burn_dlm,
+            linked_seeds: Vec::new(),
+        }
+    }
+
+    /// Replace the deposit mint with a Token-2022 transfer-fee mint, keeping the
+    /// depositor's and receiver's balances
+    pub async fn use_transfer_fee_mint(&mut self, transfer_fee_basis_points: u16, maximum_fee: u64) {
+        assert_eq!(self.token_program, spl_token_2022::id());
+        let depositor_balance = self.balance(self.depositor_token).await;
+        let receiver_balance = self.balance(self.receiver_token).await;
+        let (mint, depositor, receiver) = (self.mint, self.depositor.pubkey(), self.receiver.pubkey());
+        set_transfer_fee_mint(&mut self.context, &mint, 9, transfer_fee_basis_points, maximum_fee);
+        set_transfer_fee_token_account(&mut self.context, &self.depositor_token, &mint, &depositor, depositor_balance);
+        set_transfer_fee_token_account(&mut self.context, &self.receiver_token, &mint, &receiver, receiver_balance);
+    }
+
+    pub async fn initialize_config(&mut self) -> Result<(), BanksClientError> {
+        let ix = instruction::initialize_config(
+            &dielemma_program::id(),
+            &self.admin.pubkey(),
+            &self.dlm_mint,
+            &self.burn_address,
+            ONE_DLM,
+        );
+        let admin = self.admin.insecure_clone();
+        process(&mut self.context, &[ix], &[&admin]).await
+    }
+
+    pub async fn set_paused(&mut self, paused: bool) -> Result<(), BanksClientError> {
+        let ix = instruction::set_paused(&dielemma_program::id(), &self.admin.pubkey(), paused);
+        let admin = self.admin.insecure_clone();
+        process(&mut self.context, &[ix], &[&admin]).await
+    }
+
+    pub async fn config(&mut self) -> ConfigAccount {
+        let address = instruction::find_config_address(&dielemma_program::id()).0;
+        let account = self.context.banks_client.get_account(address).await.unwrap().unwrap();
+        ConfigAccount::try_from_slice(&account.data).unwrap()
+    }
+
+    pub fn deposit_address(&self, seed: &str) -> Pubkey {
+        instruction::find_deposit_address(&dielemma_program::id(), &self.depositor.pubkey(), seed).0
+    }
+
+    pub fn vault_address(&self, seed: &str) -> Pubkey {
+        instruction::find_vault_address(&dielemma_program::id(), &self.deposit_address(seed)).0
+    }
+
+    pub fn deposit_ix(&self, seed: &str, amount: u64, timeout_seconds: u64) -> Instruction {
+        self.locked_deposit_ix(seed, amount, timeout_seconds, 0)
+    }
+
+    pub fn locked_deposit_ix(&self, seed: &str, amount: u64, timeout_seconds: u64, withdraw_locked_until: i64) -> Instruction {
+        instruction::deposit(
+            &dielemma_program::id(),
+            &self.depositor.pubkey(),
+            &self.depositor_token,
+            &self.mint,
+            &self.token_program,
+            seed,
+            &self.receiver.pubkey(),
+            amount,
+            timeout_seconds,
+            withdraw_locked_until,
+        )
+    }
+
+    pub fn top_up_ix(&self, seed: &str, amount: u64) -> Instruction {
+        instruction::top_up(
+            &dielemma_program::id(),
+            &self.depositor.pubkey(),
+            &self.depositor_token,
+            &self.mint,
+            &self.token_program,
+            seed,
+            amount,
+        )
+    }
+
+    pub fn proof_of_life_ix(&self, seed: &str) -> Instruction {
+        instruction::proof_of_life(
+            &dielemma_program::id(),
+            &self.depositor.pubkey(),
+            seed,
+            &self.dlm_mint,
+            &self.burn_address,
+        )
+    }
+
+    pub fn withdraw_ix(&self, seed: &str) -> Instruction {
+        instruction::withdraw(
+            &dielemma_program::id(),
+            &self.depositor.pubkey(),
+            &self.depositor_token,
+            &self.mint,
+            &self.token_program,
+            seed,
+        )
+    }
+
+    pub fn partial_withdraw_ix(&self, seed: &str, amount: u64) -> Instruction {
+        instruction::partial_withdraw(
+            &dielemma_program::id(),
+            &self.depositor.pubkey(),
+            &self.depositor_token,
+            &self.mint,
+            &self.token_program,
+            seed,
+            amount,
+        )
+    }
+
+    pub fn claim_ix(&self, seed: &str) -> Instruction {
+        self.claim_ix_for(seed, &self.receiver.pubkey(), &self.receiver_token)
+    }
+
+    pub fn claim_ix_for(&self, seed: &str, receiver: &Pubkey, receiver_token: &Pubkey) -> Instruction {
+        instruction::claim(
+            &dielemma_program::id(),
+            receiver,
+            &self.depositor.pubkey(),
+            receiver_token,
+            &self.mint,
+            &self.token_program,
+            seed,
+            self.is_linked(seed),
+        )
+    }
+
+    pub fn is_linked(&self, seed: &str) -> bool {
+        self.linked_seeds.iter().any(|linked| linked == seed)
+    }
+
+    /// A funded wallet with an empty token account for the deposit mint
+    pub fn new_receiver(&mut self) -> (Keypair, Pubkey) {
+        let receiver = Keypair::new();
+        let receiver_token = Pubkey::new_unique();
+        let (mint, token_program) = (self.mint, self.token_program);
+        fund(&mut self.context, &receiver.pubkey(), 1_000_000_000);
+        set_token_account(&mut self.context, &receiver_token, &mint, &receiver.pubkey(), 0, &token_program);
+        (receiver, receiver_token)
+    }
+
+    /// A funded wallet holding 10 DLM in its Token-2022 ATA, to act as a proof-of-life delegate
+    pub fn new_delegate(&mut self) -> Keypair {
+        let delegate = Keypair::new();
+        let dlm_mint = self.dlm_mint;
+        let delegate_dlm = instruction::get_associated_token_address(&delegate.pubkey(), &dlm_mint, &spl_token_2022::id());
+        fund(&mut self.context, &delegate.pubkey(), 1_000_000_000);
+        set_token_account(&mut self.context, &delegate_dlm, &dlm_mint, &delegate.pubkey(), 10 * ONE_DLM, &spl_token_2022::id());
+        delegate
+    }
+
+    pub fn close_ix(&self, seed: &str, authority: &Pubkey) -> Instruction {
+        instruction::close_account(
+            &dielemma_program::id(),
+            authority,
+            &self.depositor.pubkey(),
+            authority,
+            seed,
+        )
+    }
+
+    pub async fn deposit(&mut self, seed: &str, amount: u64, timeout_seconds: u64) -> Result<(), BanksClientError> {
+        let ix = self.deposit_ix(seed, amount, timeout_seconds);
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    pub async fn top_up(&mut self, seed: &str, amount: u64) -> Result<(), BanksClientError> {
+        let ix = self.top_up_ix(seed, amount);
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    pub async fn proof_of_life(&mut self, seed: &str) -> Result<(), BanksClientError> {
+        let ix = self.proof_of_life_ix(seed);
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    pub async fn delegated_proof_of_life(&mut self, seed: &str, delegate: &Keypair) -> Result<(), BanksClientError> {
+        let ix = instruction::delegated_proof_of_life(
+            &dielemma_program::id(),
+            &delegate.pubkey(),
+            &self.depositor.pubkey(),
+            seed,
+            &self.dlm_mint,
+            &self.burn_address,
+        );
+        process(&mut self.context, &[ix], &[delegate]).await
+    }
+
+    pub fn batch_proof_of_life_ix(&self, seeds: &[&str]) -> Instruction {
+        instruction::batch_proof_of_life(
+            &dielemma_program::id(),
+            &self.depositor.pubkey(),
+            seeds,
+            &self.dlm_mint,
+            &self.burn_address,
+        )
+    }
+
+    pub async fn batch_proof_of_life(&mut self, seeds: &[&str]) -> Result<(), BanksClientError> {
+        let ix = self.batch_proof_of_life_ix(seeds);
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    pub fn profile_address(&self) -> Pubkey {
+        instruction::find_profile_address(&dielemma_program::id(), &self.depositor.pubkey()).0
+    }
+
+    pub async fn profile_state(&mut self) -> ProfileAccount {
+        let address = self.profile_address();
+        let account = self.context.banks_client.get_account(address).await.unwrap().unwrap();
+        ProfileAccount::try_from_slice(&account.data).unwrap()
+    }
+
+    pub async fn create_profile(&mut self) -> Result<(), BanksClientError> {
+        let ix = instruction::create_profile(&dielemma_program::id(), &self.depositor.pubkey());
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    /// Link or unlink a deposit, remembering which deposits claims must pass the profile for
+    pub async fn set_profile_link(&mut self, seed: &str, linked: bool) -> Result<(), BanksClientError> {
+        let ix = instruction::set_profile_link(&dielemma_program::id(), &self.depositor.pubkey(), seed, linked);
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await?;
+        self.linked_seeds.retain(|linked_seed| linked_seed != seed);
+        if linked {
+            self.linked_seeds.push(seed.to_string());
+        }
+        Ok(())
+    }
+
+    pub async fn profile_proof_of_life(&mut self) -> Result<(), BanksClientError> {
+        let ix = instruction::profile_proof_of_life(
+            &dielemma_program::id(),
+            &self.depositor.pubkey(),
+            &self.dlm_mint,
+            &self.burn_address,
+        );
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    /// The ed25519 check and `SignedProofOfLife` for a heartbeat signed by `signer`, relayed by `relayer`
+    pub fn signed_proof_of_life_ixs(
+        &self,
+        seed: &str,
+        relayer: &Pubkey,
+        signer: &Keypair,
+        nonce: u64,
+        expires_at: i64,
+    ) -> Vec<Instruction> {
+        let program_id = dielemma_program::id();
+        let message = instruction::heartbeat_message(&program_id, &self.deposit_address(seed), nonce, expires_at);
+        let signature: [u8; 64] = signer.sign_message(&message).as_ref().try_into().unwrap();
+        vec![
+            instruction::ed25519_signature_instruction(&signer.pubkey(), &signature, &message),
+            instruction::signed_proof_of_life(
+                &program_id,
+                relayer,
+                &self.depositor.pubkey(),
+                seed,
+                nonce,
+                expires_at,
+                &self.dlm_mint,
+                &self.burn_address,
+            ),
+        ]
+    }
+
+    pub async fn signed_proof_of_life(
+        &mut self,
+        seed: &str,
+        relayer: &Keypair,
+        nonce: u64,
+        expires_at: i64,
+    ) -> Result<(), BanksClientError> {
+        let depositor = self.depositor.insecure_clone();
+        let ixs = self.signed_proof_of_life_ixs(seed, &relayer.pubkey(), &depositor, nonce, expires_at);
+        process(&mut self.context, &ixs, &[relayer]).await
+    }
+
+    pub async fn add_delegate(&mut self, seed: &str, delegate: &Pubkey, expires_at: i64) -> Result<(), BanksClientError> {
+        let ix = instruction::add_delegate(&dielemma_program::id(), &self.depositor.pubkey(), seed, delegate, expires_at);
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    pub async fn revoke_delegate(&mut self, seed: &str, delegate: &Pubkey) -> Result<(), BanksClientError> {
+        let ix = instruction::revoke_delegate(&dielemma_program::id(), &self.depositor.pubkey(), seed, delegate);
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    pub async fn set_guardians(&mut self, seed: &str, threshold: u8, guardians: &[Pubkey]) -> Result<(), BanksClientError> {
+        let ix = instruction::set_guardians(&dielemma_program::id(), &self.depositor.pubkey(), seed, threshold, guardians);
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    pub async fn attest_death(&mut self, seed: &str, guardian: &Keypair) -> Result<(), BanksClientError> {
+        let ix = instruction::attest_death(
+            &dielemma_program::id(),
+            &guardian.pubkey(),
+            &self.depositor.pubkey(),
+            seed,
+            self.is_linked(seed),
+        );
+        process(&mut self.context, &[ix], &[guardian]).await
+    }
+
+    pub async fn set_vesting(&mut self, seed: &str, vesting_seconds: u64) -> Result<(), BanksClientError> {
+        let ix = instruction::set_vesting(&dielemma_program::id(), &self.depositor.pubkey(), seed, vesting_seconds);
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    pub async fn set_release_tranches(&mut self, seed: &str, tranches: &[(u8, u16)]) -> Result<(), BanksClientError> {
+        let ix = instruction::set_release_tranches(&dielemma_program::id(), &self.depositor.pubkey(), seed, tranches);
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    pub async fn set_release_date(&mut self, seed: &str, release_at: i64, release_at_only: bool) -> Result<(), BanksClientError> {
+        let ix = instruction::set_release_date(&dielemma_program::id(), &self.depositor.pubkey(), seed, release_at, release_at_only);
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    pub async fn set_fallback_receiver(&mut self, seed: &str, fallback_receiver: &Pubkey) -> Result<(), BanksClientError> {
+        let ix = instruction::set_fallback_receiver(&dielemma_program::id(), &self.depositor.pubkey(), seed, fallback_receiver);
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    pub async fn set_fallback_chain(&mut self, seed: &str, fallbacks: &[(Pubkey, u64)]) -> Result<(), BanksClientError> {
+        let ix = instruction::set_fallback_chain(&dielemma_program::id(), &self.depositor.pubkey(), seed, fallbacks);
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    pub async fn renounce(&mut self, seed: &str, receiver: &Keypair) -> Result<(), BanksClientError> {
+        let ix = instruction::renounce(&dielemma_program::id(), &receiver.pubkey(), &self.depositor.pubkey(), seed);
+        process(&mut self.context, &[ix], &[receiver]).await
+    }
+
+    /// Move a deposit to `new_depositor` and act as that depositor from then on,
+    /// with a fresh empty token account and its own DLM ATA
+    pub async fn rotate_depositor(&mut self, seed: &str, new_depositor: &Keypair) -> Result<(), BanksClientError> {
+        let native = self.deposit_state(seed).await.is_native();
+        let ix = instruction::rotate_depositor(
+            &dielemma_program::id(),
+            &self.depositor.pubkey(),
+            &new_depositor.pubkey(),
+            seed,
+            (!native).then_some((&self.mint, &self.token_program)),
+            self.is_linked(seed),
+        );
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor, new_depositor]).await?;
+
+        let new_depositor_token = Pubkey::new_unique();
+        let (mint, token_program) = (self.mint, self.token_program);
+        set_token_account(&mut self.context, &new_depositor_token, &mint, &new_depositor.pubkey(), 0, &token_program);
+        self.depositor = new_depositor.insecure_clone();
+        self.depositor_token = new_depositor_token;
+        self.depositor_dlm =
+            instruction::get_associated_token_address(&new_depositor.pubkey(), &self.dlm_mint, &spl_token_2022::id());
+        self.linked_seeds.retain(|linked_seed| linked_seed != seed);
+        Ok(())
+    }
+
+    /// Write a deposit in the layout used before beneficiaries, as if made at `last_proof_timestamp`,
+    /// with `amount` tokens in its vault
+    pub fn legacy_deposit(&mut self, seed: &str, amount: u64, timeout_seconds: u64, last_proof_timestamp: i64) {
+        let (address, bump) = instruction::find_deposit_address(&dielemma_program::id(), &self.depositor.pubkey(), seed);
+        let mut deposit_seed = [0u8; MAX_DEPOSIT_SEED_LENGTH];
+        deposit_seed[..seed.len()].copy_from_slice(seed.as_bytes());
+        let legacy = LegacyDepositAccount {
+            depositor: self.depositor.pubkey(),
+            receiver: self.receiver.pubkey(),
+            token_mint: self.mint,
+            amount,
+            last_proof_timestamp,
+            timeout_seconds,
+            bump,
+            is_closed: false,
+            deposit_seed_len: seed.len() as u32,
+            deposit_seed,
+        };
+        set_account(&mut self.context, &address, legacy.try_to_vec().unwrap(), &dielemma_program::id());
+
+        let vault = self.vault_address(seed);
+        let (mint, token_program) = (self.mint, self.token_program);
+        set_token_account(&mut self.context, &vault, &mint, &address, amount, &token_program);
+    }
+
+    pub async fn migrate_deposit(&mut self, seed: &str) -> Result<(), BanksClientError> {
+        let ix = instruction::migrate_deposit(
+            &dielemma_program::id(),
+            &self.context.payer.pubkey(),
+            &self.depositor.pubkey(),
+            seed,
+        );
+        process(&mut self.context, &[ix], &[]).await
+    }
+
+    pub async fn withdraw(&mut self, seed: &str) -> Result<(), BanksClientError> {
+        let ix = self.withdraw_ix(seed);
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    pub async fn partial_withdraw(&mut self, seed: &str, amount: u64) -> Result<(), BanksClientError> {
+        let ix = self.partial_withdraw_ix(seed, amount);
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    pub async fn claim(&mut self, seed: &str) -> Result<(), BanksClientError> {
+        let ix = self.claim_ix(seed);
+        let receiver = self.receiver.insecure_clone();
+        process(&mut self.context, &[ix], &[&receiver]).await
+    }
+
+    pub async fn claim_as(&mut self, seed: &str, receiver: &Keypair, receiver_token: &Pubkey) -> Result<(), BanksClientError> {
+        let ix = self.claim_ix_for(seed, &receiver.pubkey(), receiver_token);
+        process(&mut self.context, &[ix], &[receiver]).await
+    }
+
+    pub async fn set_beneficiaries(&mut self, seed: &str, beneficiaries: &[(Pubkey, u16)]) -> Result<(), BanksClientError> {
+        let ix = instruction::set_beneficiaries(&dielemma_program::id(), &self.depositor.pubkey(), seed, beneficiaries);
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    pub async fn update_receiver(
+        &mut self,
+        seed: &str,
+        old_receiver: &Pubkey,
+        new_receiver: &Pubkey,
+        reset_timer: bool,
+    ) -> Result<(), BanksClientError> {
+        let ix = instruction::update_receiver(
+            &dielemma_program::id(),
+            &self.depositor.pubkey(),
+            seed,
+            old_receiver,
+            new_receiver,
+            reset_timer,
+        );
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    pub async fn update_timeout(&mut self, seed: &str, timeout_seconds: u64) -> Result<(), BanksClientError> {
+        let ix = instruction::update_timeout(
+            &dielemma_program::id(),
+            &self.depositor.pubkey(),
+            seed,
+            timeout_seconds,
+            self.is_linked(seed),
+        );
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    pub async fn deposit_sol(&mut self, seed: &str, amount: u64, timeout_seconds: u64) -> Result<(), BanksClientError> {
+        let ix = instruction::deposit_sol(
+            &dielemma_program::id(),
+            &self.depositor.pubkey(),
+            seed,
+            &self.receiver.pubkey(),
+            amount,
+            timeout_seconds,
+            0,
+        );
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    pub async fn withdraw_sol(&mut self, seed: &str) -> Result<(), BanksClientError> {
+        let ix = instruction::withdraw_sol(&dielemma_program::id(), &self.depositor.pubkey(), seed);
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    /// Claim a native SOL deposit, or unwrap a wSOL deposit when `wsol_token_program` is set
+    pub async fn claim_sol(&mut self, seed: &str, wsol_token_program: Option<&Pubkey>) -> Result<(), BanksClientError> {
+        let ix = instruction::claim_sol(
+            &dielemma_program::id(),
+            &self.receiver.pubkey(),
+            &self.depositor.pubkey(),
+            wsol_token_program,
+            seed,
+            self.is_linked(seed),
+        );
+        let receiver = self.receiver.insecure_clone();
+        process(&mut self.context, &[ix], &[&receiver]).await
+    }
+
+    pub async fn initiate_claim(&mut self, seed: &str) -> Result<(), BanksClientError> {
+        let receiver = self.receiver.insecure_clone();
+        self.initiate_claim_as(seed, &receiver).await
+    }
+
+    pub async fn initiate_claim_as(&mut self, seed: &str, receiver: &Keypair) -> Result<(), BanksClientError> {
+        let ix = instruction::initiate_claim(
+            &dielemma_program::id(),
+            &receiver.pubkey(),
+            &self.depositor.pubkey(),
+            seed,
+            self.is_linked(seed),
+        );
+        process(&mut self.context, &[ix], &[receiver]).await
+    }
+
+    pub async fn cancel_claim(&mut self, seed: &str) -> Result<(), BanksClientError> {
+        let ix = instruction::cancel_claim(&dielemma_program::id(), &self.depositor.pubkey(), seed);
+        let depositor = self.depositor.insecure_clone();
+        process(&mut self.context, &[ix], &[&depositor]).await
+    }
+
+    pub async fn set_claim_challenge_period(&mut self, challenge_seconds: u64) -> Result<(), BanksClientError> {
+        let ix = instruction::set_claim_challenge_period(&dielemma_program::id(), &self.admin.pubkey(), challenge_seconds);
+        let admin = self.admin.insecure_clone();
+        process(&mut self.context, &[ix], &[&admin]).await
+    }
+
+    /// Initiate a claim as the receiver and move the clock to the end of its challenge period
+    pub async fn wait_out_claim(&mut self, seed: &str) {
+        let receiver = self.receiver.insecure_clone();
+        self.wait_out_claim_as(seed, &receiver).await;
+    }
+
+    /// Initiate a claim as `receiver` and move the clock to the end of its challenge period
+    pub async fn wait_out_claim_as(&mut self, seed: &str, receiver: &Keypair) {
+        self.initiate_claim_as(seed, receiver).await.unwrap();
+        let deposit = self.deposit_state(seed).await;
+        set_clock(&mut self.context, deposit.claim_finalizable_at).await;
+    }
+
+    pub async fn deposit_state(&mut self, seed: &str) -> DepositAccount {
+        let address = self.deposit_address(seed);
+        get_deposit(&mut self.context, &address).await
+    }
+
+    /// Overwrite a deposit's state, for states no instruction sequence can reach
+    pub fn set_deposit_state(&mut self, seed: &str, deposit: &DepositAccount) {
+        let address = self.deposit_address(seed);
+        set_account(&mut self.context, &address, deposit.try_to_vec().unwrap(), &dielemma_program::id());
+    }
+
+    pub async fn balance(&mut self, address: Pubkey) -> u64 {
+        token_balance(&mut self.context, &address).await
+    }
+
+    pub async fn lamports(&mut self, address: Pubkey) -> u64 {
+        lamports(&mut self.context, &address).await
+    }
+
+    /// Move the clock to `offset` seconds after the deposit's last proof-of-life
+    pub async fn warp_after_last_proof(&mut self, seed: &str, offset: i64) {
+        let deposit = self.deposit_state(seed).await;
+        set_clock(&mut self.context, deposit.last_proof_timestamp + offset).await;
+    }
+}
@@ -0,0 +1,225 @@
+//! Config account tests: initialization, pause switch, DLM settings and admin handover
+
+mod common;
+
+use common::*;
+use dielemma_program::{instruction, DielemmaError, DEFAULT_CLAIM_CHALLENGE_SECONDS};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::{
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+};
+
+const SEED: &str = "test-seed";
+const AMOUNT: u64 = 500_000;
+
+#[tokio::test]
+async fn test_initialize_config() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let config = env.config().await;
+    assert_eq!(config.admin, env.admin.pubkey());
+    assert_eq!(config.pending_admin, Pubkey::default());
+    assert_eq!(config.dlm_mint, env.dlm_mint);
+    assert_eq!(config.burn_address, env.burn_address);
+    assert_eq!(config.proof_of_life_cost, ONE_DLM);
+    assert_eq!(config.claim_challenge_seconds, DEFAULT_CLAIM_CHALLENGE_SECONDS);
+    assert!(!config.paused);
+
+    // A second initialization cannot take over the config
+    assert_instruction_error(
+        env.initialize_config().await,
+        InstructionError::AccountAlreadyInitialized,
+    );
+}
+
+#[tokio::test]
+async fn test_initialize_config_requires_upgrade_authority() {
+    let mut env = TestEnv::new_without_config(spl_token::id()).await;
+    let program_id = dielemma_program::id();
+
+    let intruder = Keypair::new();
+    fund(&mut env.context, &intruder.pubkey(), 10_000_000_000);
+    let ix = instruction::initialize_config(&program_id, &intruder.pubkey(), &env.dlm_mint, &env.burn_address, ONE_DLM);
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&intruder]).await,
+        DielemmaError::NotAdmin,
+    );
+
+    // Program data account must be the one derived from the program id
+    let mut ix = instruction::initialize_config(&program_id, &env.admin.pubkey(), &env.dlm_mint, &env.burn_address, ONE_DLM);
+    ix.accounts[2].pubkey = Pubkey::new_unique();
+    let admin = env.admin.insecure_clone();
+    assert_instruction_error(
+        process(&mut env.context, &[ix], &[&admin]).await,
+        InstructionError::InvalidAccountData,
+    );
+
+    let ix = instruction::initialize_config(&program_id, &env.admin.pubkey(), &env.dlm_mint, &env.burn_address, 0);
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&admin]).await,
+        DielemmaError::InvalidAmount,
+    );
+
+    env.initialize_config().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_instructions_require_initialized_config() {
+    let mut env = TestEnv::new_without_config(spl_token::id()).await;
+    assert_dielemma_error(env.deposit(SEED, AMOUNT, DAY).await, DielemmaError::InvalidConfigAccount);
+}
+
+#[tokio::test]
+async fn test_instructions_reject_wrong_config_account() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let mut ix = env.deposit_ix(SEED, AMOUNT, DAY);
+    ix.accounts[8].pubkey = Pubkey::new_unique();
+    let depositor = env.depositor.insecure_clone();
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        DielemmaError::InvalidConfigAccount,
+    );
+}
+
+#[tokio::test]
+async fn test_pause_blocks_deposit_lifecycle() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit("withdraw", AMOUNT, DAY).await.unwrap();
+    env.deposit("claim", AMOUNT, DAY).await.unwrap();
+
+    env.set_paused(true).await.unwrap();
+    assert!(env.config().await.paused);
+    assert_dielemma_error(env.deposit(SEED, AMOUNT, DAY).await, DielemmaError::ProgramPaused);
+    assert_dielemma_error(env.proof_of_life("withdraw").await, DielemmaError::ProgramPaused);
+    assert_dielemma_error(env.withdraw("withdraw").await, DielemmaError::ProgramPaused);
+    env.warp_after_last_proof("claim", DAY as i64 + 1).await;
+    assert_dielemma_error(env.claim("claim").await, DielemmaError::ProgramPaused);
+
+    env.set_paused(false).await.unwrap();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.withdraw("withdraw").await.unwrap();
+    env.wait_out_claim("claim").await;
+    env.claim("claim").await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT);
+}
+
+#[tokio::test]
+async fn test_admin_instructions_require_admin() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let program_id = dielemma_program::id();
+    let intruder = Keypair::new();
+
+    let ix = instruction::set_paused(&program_id, &intruder.pubkey(), true);
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&intruder]).await,
+        DielemmaError::NotAdmin,
+    );
+
+    let ix = instruction::update_config(&program_id, &intruder.pubkey(), &env.dlm_mint, &intruder.pubkey(), 1);
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&intruder]).await,
+        DielemmaError::NotAdmin,
+    );
+
+    let ix = instruction::propose_admin(&program_id, &intruder.pubkey(), &intruder.pubkey());
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&intruder]).await,
+        DielemmaError::NotAdmin,
+    );
+
+    // The admin account must sign
+    let mut ix = instruction::set_paused(&program_id, &env.admin.pubkey(), true);
+    ix.accounts[0].is_signer = false;
+    assert_instruction_error(
+        process(&mut env.context, &[ix], &[]).await,
+        InstructionError::MissingRequiredSignature,
+    );
+    assert!(!env.config().await.paused);
+}
+
+#[tokio::test]
+async fn test_update_config_changes_proof_of_life_burn() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    // New DLM mint with different decimals, burned to a new address
+    let token_2022 = spl_token_2022::id();
+    let new_mint = Pubkey::new_unique();
+    let new_burn_address = Pubkey::new_unique();
+    let new_depositor_dlm = instruction::get_associated_token_address(&env.depositor.pubkey(), &new_mint, &token_2022);
+    let new_burn_dlm = instruction::get_burn_token_address(&new_burn_address, &new_mint);
+    set_mint(&mut env.context, &new_mint, 9, &token_2022);
+    set_token_account(&mut env.context, &new_depositor_dlm, &new_mint, &env.depositor.pubkey(), 10_000_000_000, &token_2022);
+    set_token_account(&mut env.context, &new_burn_dlm, &new_mint, &new_burn_address, 0, &token_2022);
+
+    let ix = instruction::update_config(
+        &dielemma_program::id(),
+        &env.admin.pubkey(),
+        &new_mint,
+        &new_burn_address,
+        2_000_000_000,
+    );
+    let admin = env.admin.insecure_clone();
+    process(&mut env.context, &[ix], &[&admin]).await.unwrap();
+    let config = env.config().await;
+    assert_eq!(config.dlm_mint, new_mint);
+    assert_eq!(config.burn_address, new_burn_address);
+    assert_eq!(config.proof_of_life_cost, 2_000_000_000);
+
+    // The old mint is no longer accepted
+    assert_dielemma_error(env.proof_of_life(SEED).await, DielemmaError::InvalidDlmMint);
+
+    let ix = instruction::proof_of_life(
+        &dielemma_program::id(),
+        &env.depositor.pubkey(),
+        SEED,
+        &new_mint,
+        &new_burn_address,
+    );
+    let depositor = env.depositor.insecure_clone();
+    process(&mut env.context, &[ix], &[&depositor]).await.unwrap();
+    assert_eq!(env.balance(new_depositor_dlm).await, 8_000_000_000);
+    assert_eq!(env.balance(new_burn_dlm).await, 2_000_000_000);
+    assert_eq!(env.balance(env.depositor_dlm).await, 10 * ONE_DLM);
+}
+
+#[tokio::test]
+async fn test_admin_handover() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let program_id = dielemma_program::id();
+    let new_admin = Keypair::new();
+    let intruder = Keypair::new();
+
+    // Nothing to accept before a proposal
+    let ix = instruction::accept_admin(&program_id, &new_admin.pubkey());
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&new_admin]).await,
+        DielemmaError::NotPendingAdmin,
+    );
+
+    let ix = instruction::propose_admin(&program_id, &env.admin.pubkey(), &new_admin.pubkey());
+    let admin = env.admin.insecure_clone();
+    process(&mut env.context, &[ix], &[&admin]).await.unwrap();
+    let config = env.config().await;
+    assert_eq!(config.admin, env.admin.pubkey());
+    assert_eq!(config.pending_admin, new_admin.pubkey());
+
+    let ix = instruction::accept_admin(&program_id, &intruder.pubkey());
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&intruder]).await,
+        DielemmaError::NotPendingAdmin,
+    );
+
+    let ix = instruction::accept_admin(&program_id, &new_admin.pubkey());
+    process(&mut env.context, &[ix], &[&new_admin]).await.unwrap();
+    let config = env.config().await;
+    assert_eq!(config.admin, new_admin.pubkey());
+    assert_eq!(config.pending_admin, Pubkey::default());
+
+    // The previous admin has lost its rights, the new one has them
+    assert_dielemma_error(env.set_paused(true).await, DielemmaError::NotAdmin);
+    let ix = instruction::set_paused(&program_id, &new_admin.pubkey(), true);
+    process(&mut env.context, &[ix], &[&new_admin]).await.unwrap();
+    assert!(env.config().await.paused);
+}
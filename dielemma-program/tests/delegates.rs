@@ -0,0 +1,138 @@
+//! Delegated proof-of-life keys: AddDelegate, RevokeDelegate and ProofOfLife signed by a delegate
+
+mod common;
+
+use common::*;
+use dielemma_program::{instruction, DielemmaError, MAX_DELEGATES};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+
+const SEED: &str = "test-seed";
+const AMOUNT: u64 = 500_000;
+
+#[tokio::test]
+async fn test_delegate_proves_life() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let delegate = env.new_delegate();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    // Unregistered keys cannot prove life
+    assert_dielemma_error(
+        env.delegated_proof_of_life(SEED, &delegate).await,
+        DielemmaError::NotDepositor,
+    );
+
+    env.add_delegate(SEED, &delegate.pubkey(), 0).await.unwrap();
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.delegate_count, 1);
+    assert_eq!(deposit.delegates[0].key, delegate.pubkey());
+
+    env.warp_after_last_proof(SEED, DAY as i64 - 1).await;
+    let expected_timestamp = now(&mut env.context).await;
+    env.delegated_proof_of_life(SEED, &delegate).await.unwrap();
+
+    assert_eq!(env.deposit_state(SEED).await.last_proof_timestamp, expected_timestamp);
+    // The delegate pays the burn, not the depositor
+    let delegate_dlm = instruction::get_associated_token_address(&delegate.pubkey(), &env.dlm_mint, &spl_token_2022::id());
+    assert_eq!(env.balance(delegate_dlm).await, 9 * ONE_DLM);
+    assert_eq!(env.balance(env.depositor_dlm).await, 10 * ONE_DLM);
+    assert_eq!(env.balance(env.burn_dlm).await, ONE_DLM);
+}
+
+#[tokio::test]
+async fn test_delegate_cannot_move_funds() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let delegate = env.new_delegate();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.add_delegate(SEED, &delegate.pubkey(), 0).await.unwrap();
+
+    let delegate_token = Pubkey::new_unique();
+    let (mint, token_program) = (env.mint, env.token_program);
+    set_token_account(&mut env.context, &delegate_token, &mint, &delegate.pubkey(), 0, &token_program);
+
+    let mut ix = env.withdraw_ix(SEED);
+    ix.accounts[0].pubkey = delegate.pubkey();
+    ix.accounts[2].pubkey = delegate_token;
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&delegate]).await,
+        DielemmaError::InvalidDepositAccount,
+    );
+
+    // Nor manage other delegates
+    let mut ix = instruction::add_delegate(&dielemma_program::id(), &delegate.pubkey(), SEED, &Pubkey::new_unique(), 0);
+    ix.accounts[1].pubkey = env.deposit_address(SEED);
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&delegate]).await,
+        DielemmaError::InvalidDepositAccount,
+    );
+    assert_eq!(env.balance(env.vault_address(SEED)).await, AMOUNT);
+}
+
+#[tokio::test]
+async fn test_delegate_expiry_and_revocation() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let expiring = env.new_delegate();
+    let revoked = env.new_delegate();
+    let kept = env.new_delegate();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let expires_at = now(&mut env.context).await + 3_600;
+
+    env.add_delegate(SEED, &expiring.pubkey(), expires_at).await.unwrap();
+    env.add_delegate(SEED, &revoked.pubkey(), 0).await.unwrap();
+    env.add_delegate(SEED, &kept.pubkey(), 0).await.unwrap();
+
+    env.delegated_proof_of_life(SEED, &expiring).await.unwrap();
+    set_clock(&mut env.context, expires_at).await;
+    assert_dielemma_error(
+        env.delegated_proof_of_life(SEED, &expiring).await,
+        DielemmaError::DelegateExpired,
+    );
+
+    // Re-adding a delegate updates its expiry
+    env.add_delegate(SEED, &expiring.pubkey(), 0).await.unwrap();
+    env.delegated_proof_of_life(SEED, &expiring).await.unwrap();
+
+    env.revoke_delegate(SEED, &revoked.pubkey()).await.unwrap();
+    assert_dielemma_error(
+        env.delegated_proof_of_life(SEED, &revoked).await,
+        DielemmaError::NotDepositor,
+    );
+    assert_dielemma_error(
+        env.revoke_delegate(SEED, &revoked.pubkey()).await,
+        DielemmaError::InvalidDelegate,
+    );
+
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.delegate_count, 2);
+    assert!(deposit.delegate_index(&kept.pubkey()).is_some());
+    env.delegated_proof_of_life(SEED, &kept).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_add_delegate_rejects_invalid_requests() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let depositor = env.depositor.pubkey();
+
+    assert_dielemma_error(env.add_delegate(SEED, &depositor, 0).await, DielemmaError::InvalidDelegate);
+    let past = now(&mut env.context).await;
+    assert_dielemma_error(
+        env.add_delegate(SEED, &Pubkey::new_unique(), past).await,
+        DielemmaError::InvalidDelegate,
+    );
+
+    for _ in 0..MAX_DELEGATES {
+        env.add_delegate(SEED, &Pubkey::new_unique(), 0).await.unwrap();
+    }
+    assert_dielemma_error(
+        env.add_delegate(SEED, &Pubkey::new_unique(), 0).await,
+        DielemmaError::InvalidDelegate,
+    );
+
+    env.withdraw(SEED).await.unwrap();
+    assert_dielemma_error(
+        env.add_delegate(SEED, &Pubkey::new_unique(), 0).await,
+        DielemmaError::AlreadyClosed,
+    );
+}
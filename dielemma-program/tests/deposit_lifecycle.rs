@@ -0,0 +1,491 @@
+//! Deposit → ProofOfLife → Withdraw/Claim → CloseAccount lifecycle tests
+
+mod common;
+
+use common::*;
+use dielemma_program::{instruction, Beneficiary, DielemmaError, MAX_DEPOSIT_SEED_LENGTH, TOTAL_SHARE_BPS};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::{
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+    sysvar,
+};
+
+const SEED: &str = "test-seed";
+const AMOUNT: u64 = 500_000;
+
+#[tokio::test]
+async fn test_deposit_creates_deposit_and_vault() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    let deposit_address = env.deposit_address(SEED);
+    let deposit = get_deposit(&mut env.context, &deposit_address).await;
+    assert_eq!(deposit.depositor, env.depositor.pubkey());
+    assert_eq!(
+        deposit.beneficiaries(),
+        &[Beneficiary {
+            receiver: env.receiver.pubkey(),
+            share_bps: TOTAL_SHARE_BPS,
+            claimed: false,
+            claimed_amount: 0,
+        }]
+    );
+    assert_eq!(deposit.token_mint, env.mint);
+    assert_eq!(deposit.amount, AMOUNT);
+    assert_eq!(deposit.timeout_seconds, DAY);
+    assert!(!deposit.is_closed);
+    assert_eq!(&deposit.deposit_seed[..deposit.deposit_seed_len as usize], SEED.as_bytes());
+
+    let vault = env.vault_address(SEED);
+    assert_eq!(token_balance(&mut env.context, &vault).await, AMOUNT);
+    assert_eq!(
+        env.balance(env.depositor_token).await,
+        INITIAL_BALANCE - AMOUNT
+    );
+}
+
+#[tokio::test]
+async fn test_deposit_rejects_invalid_amount() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    assert_dielemma_error(env.deposit(SEED, 0, DAY).await, DielemmaError::InvalidAmount);
+    assert_dielemma_error(
+        env.deposit(SEED, 100_000_000_000_000_001, DAY).await,
+        DielemmaError::InvalidAmount,
+    );
+}
+
+#[tokio::test]
+async fn test_deposit_rejects_invalid_timeout() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    assert_dielemma_error(env.deposit(SEED, AMOUNT, 59).await, DielemmaError::InvalidTimeout);
+    assert_dielemma_error(
+        env.deposit(SEED, AMOUNT, 315_360_001).await,
+        DielemmaError::InvalidTimeout,
+    );
+    env.deposit(SEED, AMOUNT, 60).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_deposit_rejects_long_seed() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let seed = "s".repeat(MAX_DEPOSIT_SEED_LENGTH + 1);
+    let mut ix = env.deposit_ix(SEED, AMOUNT, DAY);
+    // Splice the oversized seed into the packed data, keeping the PDA from SEED
+    let mut data = ix.data[..4].to_vec();
+    data.extend_from_slice(&(seed.len() as u32).to_le_bytes());
+    data.extend_from_slice(seed.as_bytes());
+    data.extend_from_slice(&ix.data[8 + SEED.len()..]);
+    ix.data = data;
+
+    let depositor = env.depositor.insecure_clone();
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        DielemmaError::InvalidDepositSeed,
+    );
+}
+
+#[tokio::test]
+async fn test_deposit_rejects_missing_signature() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let mut ix = env.deposit_ix(SEED, AMOUNT, DAY);
+    ix.accounts[0].is_signer = false;
+    assert_instruction_error(
+        process(&mut env.context, &[ix], &[]).await,
+        InstructionError::MissingRequiredSignature,
+    );
+}
+
+#[tokio::test]
+async fn test_deposit_rejects_wrong_programs_and_sysvars() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let depositor = env.depositor.insecure_clone();
+
+    let mut ix = env.deposit_ix(SEED, AMOUNT, DAY);
+    ix.accounts[5].pubkey = Pubkey::new_unique();
+    assert_instruction_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        InstructionError::IncorrectProgramId,
+    );
+
+    let mut ix = env.deposit_ix(SEED, AMOUNT, DAY);
+    ix.accounts[6].pubkey = spl_token::id();
+    assert_instruction_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        InstructionError::IncorrectProgramId,
+    );
+
+    let mut ix = env.deposit_ix(SEED, AMOUNT, DAY);
+    ix.accounts[7].pubkey = sysvar::clock::id();
+    assert_instruction_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        InstructionError::InvalidAccountData,
+    );
+}
+
+#[tokio::test]
+async fn test_deposit_rejects_foreign_token_account() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let foreign = Pubkey::new_unique();
+    let (mint, token_program) = (env.mint, env.token_program);
+    set_token_account(&mut env.context, &foreign, &mint, &Pubkey::new_unique(), AMOUNT, &token_program);
+
+    let mut ix = env.deposit_ix(SEED, AMOUNT, DAY);
+    ix.accounts[2].pubkey = foreign;
+    let depositor = env.depositor.insecure_clone();
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        DielemmaError::InvalidTokenAccountOwner,
+    );
+}
+
+#[tokio::test]
+async fn test_deposit_rejects_wrong_pdas() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let depositor = env.depositor.insecure_clone();
+
+    let mut ix = env.deposit_ix(SEED, AMOUNT, DAY);
+    ix.accounts[1].pubkey = env.deposit_address("other-seed");
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        DielemmaError::InvalidDepositAccount,
+    );
+
+    let mut ix = env.deposit_ix(SEED, AMOUNT, DAY);
+    ix.accounts[3].pubkey = env.vault_address("other-seed");
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        DielemmaError::InvalidVaultAccount,
+    );
+}
+
+#[tokio::test]
+async fn test_deposit_rejects_existing_seed() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    assert_instruction_error(
+        env.deposit(SEED, AMOUNT, DAY).await,
+        InstructionError::AccountAlreadyInitialized,
+    );
+}
+
+#[tokio::test]
+async fn test_proof_of_life_burns_dlm_and_resets_timer() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.warp_after_last_proof(SEED, DAY as i64 - 1).await;
+    let expected_timestamp = now(&mut env.context).await;
+
+    env.proof_of_life(SEED).await.unwrap();
+
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.last_proof_timestamp, expected_timestamp);
+    assert_eq!(env.balance(env.depositor_dlm).await, 9 * ONE_DLM);
+    assert_eq!(env.balance(env.burn_dlm).await, ONE_DLM);
+
+    // The timer restarted, so the receiver has to wait a full timeout again
+    env.warp_after_last_proof(SEED, DAY as i64 - 1).await;
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::NotExpired);
+}
+
+#[tokio::test]
+async fn test_proof_of_life_rejects_wrong_accounts() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let depositor = env.depositor.insecure_clone();
+
+    let mut ix = env.proof_of_life_ix(SEED);
+    ix.accounts[5].pubkey = spl_token::id();
+    assert_instruction_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        InstructionError::IncorrectProgramId,
+    );
+
+    let fake_mint = Pubkey::new_unique();
+    set_mint(&mut env.context, &fake_mint, DLM_DECIMALS, &spl_token_2022::id());
+    let mut ix = env.proof_of_life_ix(SEED);
+    ix.accounts[4].pubkey = fake_mint;
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        DielemmaError::InvalidDlmMint,
+    );
+
+    let mut ix = env.proof_of_life_ix(SEED);
+    ix.accounts[1].pubkey = env.deposit_address("other-seed");
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        DielemmaError::InvalidDepositAccount,
+    );
+
+    let other_dlm = Pubkey::new_unique();
+    let dlm_mint = env.dlm_mint;
+    set_token_account(&mut env.context, &other_dlm, &dlm_mint, &depositor.pubkey(), ONE_DLM, &spl_token_2022::id());
+    let mut ix = env.proof_of_life_ix(SEED);
+    ix.accounts[2].pubkey = other_dlm;
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        DielemmaError::InvalidDlmTokenAccount,
+    );
+
+    let mut ix = env.proof_of_life_ix(SEED);
+    ix.accounts[3].pubkey = other_dlm;
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        DielemmaError::InvalidBurnTokenAccount,
+    );
+
+    let mut ix = env.proof_of_life_ix(SEED);
+    ix.accounts[0].is_signer = false;
+    assert_instruction_error(
+        process(&mut env.context, &[ix], &[]).await,
+        InstructionError::MissingRequiredSignature,
+    );
+}
+
+#[tokio::test]
+async fn test_proof_of_life_requires_dlm_balance() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let (depositor_dlm, dlm_mint, depositor) = (env.depositor_dlm, env.dlm_mint, env.depositor.pubkey());
+    set_token_account(&mut env.context, &depositor_dlm, &dlm_mint, &depositor, ONE_DLM - 1, &spl_token_2022::id());
+
+    // Token-2022 InsufficientFunds
+    assert_instruction_error(env.proof_of_life(SEED).await, InstructionError::Custom(1));
+}
+
+#[tokio::test]
+async fn test_proof_of_life_rejects_closed_deposit() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.withdraw(SEED).await.unwrap();
+    assert_dielemma_error(env.proof_of_life(SEED).await, DielemmaError::AlreadyClosed);
+}
+
+#[tokio::test]
+async fn test_withdraw_returns_tokens() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.withdraw(SEED).await.unwrap();
+
+    let deposit = env.deposit_state(SEED).await;
+    assert!(deposit.is_closed);
+    assert_eq!(env.balance(env.depositor_token).await, INITIAL_BALANCE);
+    assert_eq!(env.balance(env.vault_address(SEED)).await, 0);
+
+    assert_dielemma_error(env.withdraw(SEED).await, DielemmaError::AlreadyClosed);
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::AlreadyClosed);
+}
+
+#[tokio::test]
+async fn test_withdraw_allowed_after_expiry() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.warp_after_last_proof(SEED, 10 * DAY as i64).await;
+    env.withdraw(SEED).await.unwrap();
+    assert_eq!(env.balance(env.depositor_token).await, INITIAL_BALANCE);
+}
+
+#[tokio::test]
+async fn test_withdraw_rejects_invalid_requests() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let depositor = env.depositor.insecure_clone();
+
+    let mut ix = env.withdraw_ix(SEED);
+    ix.accounts[0].is_signer = false;
+    assert_instruction_error(
+        process(&mut env.context, &[ix], &[]).await,
+        InstructionError::MissingRequiredSignature,
+    );
+
+    let mut ix = env.withdraw_ix(SEED);
+    ix.accounts[1].pubkey = env.deposit_address("other-seed");
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        DielemmaError::InvalidDepositAccount,
+    );
+
+    let mut ix = env.withdraw_ix(SEED);
+    ix.accounts[2].pubkey = env.receiver_token;
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        DielemmaError::InvalidTokenAccountOwner,
+    );
+
+    let other_mint = Pubkey::new_unique();
+    let other_account = Pubkey::new_unique();
+    let token_program = env.token_program;
+    set_mint(&mut env.context, &other_mint, 9, &token_program);
+    set_token_account(&mut env.context, &other_account, &other_mint, &depositor.pubkey(), 0, &token_program);
+    let mut ix = env.withdraw_ix(SEED);
+    ix.accounts[2].pubkey = other_account;
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        DielemmaError::MintMismatch,
+    );
+
+    // A third party cannot withdraw somebody else's deposit: the PDA is derived from the signer
+    let attacker = Keypair::new();
+    fund(&mut env.context, &attacker.pubkey(), 1_000_000_000);
+    let attacker_token = Pubkey::new_unique();
+    let mint = env.mint;
+    set_token_account(&mut env.context, &attacker_token, &mint, &attacker.pubkey(), 0, &token_program);
+    let mut ix = instruction::withdraw(&dielemma_program::id(), &attacker.pubkey(), &attacker_token, &env.mint, &token_program, SEED);
+    ix.accounts[1].pubkey = env.deposit_address(SEED);
+    ix.accounts[3].pubkey = env.vault_address(SEED);
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&attacker]).await,
+        DielemmaError::InvalidDepositAccount,
+    );
+}
+
+#[tokio::test]
+async fn test_tokens_sent_to_vault_are_reconciled() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    // Tokens sent straight to the vault become part of the deposit when it pays out
+    let vault = env.vault_address(SEED);
+    let depositor = env.depositor.insecure_clone();
+    let ix = spl_token::instruction::transfer(&spl_token::id(), &env.depositor_token, &vault, &depositor.pubkey(), &[], 1_000)
+        .unwrap();
+    process(&mut env.context, &[ix], &[&depositor]).await.unwrap();
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT + 1_000);
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.amount, AMOUNT + 1_000);
+    assert_eq!(deposit.claimed_amount, AMOUNT + 1_000);
+}
+
+#[tokio::test]
+async fn test_claim_expiry_boundary() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    env.warp_after_last_proof(SEED, DAY as i64 - 1).await;
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::NotExpired);
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+
+    let deposit = env.deposit_state(SEED).await;
+    assert!(deposit.is_closed);
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT);
+    assert_eq!(env.balance(env.vault_address(SEED)).await, 0);
+
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::AlreadyClosed);
+    assert_dielemma_error(env.withdraw(SEED).await, DielemmaError::AlreadyClosed);
+}
+
+#[tokio::test]
+async fn test_claim_rejects_invalid_requests() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    let receiver = env.receiver.insecure_clone();
+
+    let mut ix = env.claim_ix(SEED);
+    ix.accounts[0].is_signer = false;
+    assert_instruction_error(
+        process(&mut env.context, &[ix], &[]).await,
+        InstructionError::MissingRequiredSignature,
+    );
+
+    // The depositor is not the receiver
+    let depositor = env.depositor.insecure_clone();
+    let mut ix = env.claim_ix(SEED);
+    ix.accounts[0].pubkey = depositor.pubkey();
+    ix.accounts[2].pubkey = env.depositor_token;
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        DielemmaError::NotReceiver,
+    );
+
+    let mut ix = env.claim_ix(SEED);
+    ix.accounts[2].pubkey = env.depositor_token;
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&receiver]).await,
+        DielemmaError::InvalidTokenAccountOwner,
+    );
+
+    let other_mint = Pubkey::new_unique();
+    let other_account = Pubkey::new_unique();
+    let token_program = env.token_program;
+    set_mint(&mut env.context, &other_mint, 9, &token_program);
+    set_token_account(&mut env.context, &other_account, &other_mint, &receiver.pubkey(), 0, &token_program);
+    let mut ix = env.claim_ix(SEED);
+    ix.accounts[2].pubkey = other_account;
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&receiver]).await,
+        DielemmaError::MintMismatch,
+    );
+
+    // Another deposit of the same depositor cannot be claimed through this seed
+    env.deposit("other-seed", AMOUNT, DAY).await.unwrap();
+    let mut ix = env.claim_ix(SEED);
+    ix.accounts[1].pubkey = env.deposit_address("other-seed");
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&receiver]).await,
+        DielemmaError::InvalidDepositAccount,
+    );
+}
+
+#[tokio::test]
+async fn test_close_account_after_withdraw() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let deposit_address = env.deposit_address(SEED);
+    let depositor = env.depositor.insecure_clone();
+
+    // Active deposits cannot be closed
+    let ix = env.close_ix(SEED, &depositor.pubkey());
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        DielemmaError::DepositStillActive,
+    );
+
+    env.withdraw(SEED).await.unwrap();
+
+    let stranger = Keypair::new();
+    fund(&mut env.context, &stranger.pubkey(), 1_000_000_000);
+    let ix = env.close_ix(SEED, &stranger.pubkey());
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&stranger]).await,
+        DielemmaError::Unauthorized,
+    );
+
+    let mut ix = env.close_ix(SEED, &depositor.pubkey());
+    ix.accounts[0].is_signer = false;
+    assert_instruction_error(
+        process(&mut env.context, &[ix], &[]).await,
+        InstructionError::MissingRequiredSignature,
+    );
+
+    let rent = lamports(&mut env.context, &deposit_address).await;
+    let before = lamports(&mut env.context, &depositor.pubkey()).await;
+    let ix = env.close_ix(SEED, &depositor.pubkey());
+    process(&mut env.context, &[ix], &[&depositor]).await.unwrap();
+
+    assert_eq!(lamports(&mut env.context, &deposit_address).await, 0);
+    assert_eq!(lamports(&mut env.context, &depositor.pubkey()).await, before + rent);
+}
+
+#[tokio::test]
+async fn test_close_account_by_receiver_after_claim() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+
+    let receiver = env.receiver.insecure_clone();
+    let ix = env.close_ix(SEED, &receiver.pubkey());
+    process(&mut env.context, &[ix], &[&receiver]).await.unwrap();
+    assert_eq!(env.lamports(env.deposit_address(SEED)).await, 0);
+}
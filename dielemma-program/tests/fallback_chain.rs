@@ -0,0 +1,162 @@
+//! Fallback receiver chains: SetFallbackChain and claim windows opening in turn
+
+mod common;
+
+use common::*;
+use dielemma_program::{DielemmaError, MAX_FALLBACK_RECEIVERS, MAX_TIMEOUT_SECONDS};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+
+const SEED: &str = "test-seed";
+const AMOUNT: u64 = 500_000;
+const FIRST_GRACE: u64 = 10 * DAY;
+const LAST_GRACE: u64 = 20 * DAY;
+
+#[tokio::test]
+async fn test_fallback_claims_after_primary_window() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let (fallback, fallback_token) = env.new_receiver();
+    let charity = Pubkey::new_unique();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.set_fallback_chain(SEED, &[(fallback.pubkey(), FIRST_GRACE), (charity, LAST_GRACE)])
+        .await
+        .unwrap();
+
+    env.warp_after_last_proof(SEED, (DAY + FIRST_GRACE) as i64 - 1).await;
+    assert_dielemma_error(env.initiate_claim_as(SEED, &fallback).await, DielemmaError::OutsideClaimWindow);
+
+    env.warp_after_last_proof(SEED, (DAY + FIRST_GRACE) as i64).await;
+    env.wait_out_claim_as(SEED, &fallback).await;
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::OutsideClaimWindow);
+
+    env.claim_as(SEED, &fallback, &fallback_token).await.unwrap();
+    assert_eq!(env.balance(fallback_token).await, AMOUNT);
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.beneficiaries()[0].receiver, fallback.pubkey());
+    assert_eq!(deposit.beneficiaries().len(), 1);
+    assert_eq!(deposit.fallback_chain()[0].receiver, charity);
+    assert_eq!(deposit.fallback_chain().len(), 1);
+    // The primary receiver's claim right passed to the fallback
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::NotReceiver);
+}
+
+#[tokio::test]
+async fn test_claim_windows_close_in_turn() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let (fallback, _) = env.new_receiver();
+    let (charity, charity_token) = env.new_receiver();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.set_fallback_chain(SEED, &[(fallback.pubkey(), FIRST_GRACE), (charity.pubkey(), LAST_GRACE)])
+        .await
+        .unwrap();
+
+    env.warp_after_last_proof(SEED, (DAY + FIRST_GRACE) as i64).await;
+    assert_dielemma_error(env.initiate_claim(SEED).await, DielemmaError::OutsideClaimWindow);
+    assert_dielemma_error(env.initiate_claim_as(SEED, &charity).await, DielemmaError::OutsideClaimWindow);
+
+    env.warp_after_last_proof(SEED, (DAY + LAST_GRACE) as i64).await;
+    assert_dielemma_error(env.initiate_claim_as(SEED, &fallback).await, DielemmaError::OutsideClaimWindow);
+    env.wait_out_claim_as(SEED, &charity).await;
+    env.claim_as(SEED, &charity, &charity_token).await.unwrap();
+    assert_eq!(env.balance(charity_token).await, AMOUNT);
+}
+
+#[tokio::test]
+async fn test_claim_initiated_in_window_survives_it() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let (fallback, _) = env.new_receiver();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.set_fallback_chain(SEED, &[(fallback.pubkey(), DAY)]).await.unwrap();
+
+    // The challenge period outlasts the primary receiver's window
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    assert_dielemma_error(env.initiate_claim_as(SEED, &fallback).await, DielemmaError::OutsideClaimWindow);
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT);
+}
+
+#[tokio::test]
+async fn test_unfinalized_claim_lapses_to_fallbacks() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let (fallback, _) = env.new_receiver();
+    let (charity, charity_token) = env.new_receiver();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.set_fallback_chain(SEED, &[(fallback.pubkey(), FIRST_GRACE), (charity.pubkey(), LAST_GRACE)])
+        .await
+        .unwrap();
+
+    // The receiver initiates a claim and never finalizes it
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.initiate_claim(SEED).await.unwrap();
+    let finalizable_at = env.deposit_state(SEED).await.claim_finalizable_at;
+
+    set_clock(&mut env.context, finalizable_at + FIRST_GRACE as i64 - 1).await;
+    assert_dielemma_error(env.initiate_claim_as(SEED, &fallback).await, DielemmaError::OutsideClaimWindow);
+
+    // The first fallback lets its own claim lapse too, and the chain moves on
+    set_clock(&mut env.context, finalizable_at + FIRST_GRACE as i64).await;
+    env.initiate_claim_as(SEED, &fallback).await.unwrap();
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::OutsideClaimWindow);
+    let finalizable_at = env.deposit_state(SEED).await.claim_finalizable_at;
+
+    set_clock(&mut env.context, finalizable_at + LAST_GRACE as i64).await;
+    env.wait_out_claim_as(SEED, &charity).await;
+    env.claim_as(SEED, &charity, &charity_token).await.unwrap();
+    assert_eq!(env.balance(charity_token).await, AMOUNT);
+}
+
+#[tokio::test]
+async fn test_voided_fallback_claim_keeps_beneficiaries() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let (fallback, _) = env.new_receiver();
+    let charity = Pubkey::new_unique();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.set_fallback_chain(SEED, &[(fallback.pubkey(), FIRST_GRACE), (charity, LAST_GRACE)])
+        .await
+        .unwrap();
+    let before = env.deposit_state(SEED).await;
+
+    // Initiating a claim does not hand the deposit over, so a returning depositor voids it cleanly
+    env.warp_after_last_proof(SEED, (DAY + FIRST_GRACE) as i64).await;
+    env.initiate_claim_as(SEED, &fallback).await.unwrap();
+    env.proof_of_life(SEED).await.unwrap();
+
+    let deposit = env.deposit_state(SEED).await;
+    assert!(!deposit.has_pending_claim());
+    assert_eq!(deposit.beneficiaries(), before.beneficiaries());
+    assert_eq!(deposit.fallback_chain(), before.fallback_chain());
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT);
+}
+
+#[tokio::test]
+async fn test_set_fallback_chain_validation() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let too_many: Vec<_> = (1..=MAX_FALLBACK_RECEIVERS as u64 + 1).map(|i| (Pubkey::new_unique(), i * DAY)).collect();
+
+    for fallbacks in [
+        vec![(a, 0)],
+        vec![(a, DAY), (b, DAY)],
+        vec![(a, 2 * DAY), (b, DAY)],
+        vec![(a, DAY), (a, 2 * DAY)],
+        vec![(a, MAX_TIMEOUT_SECONDS + 1)],
+        vec![(env.depositor.pubkey(), DAY)],
+        vec![(env.receiver.pubkey(), DAY)],
+        too_many,
+    ] {
+        assert_dielemma_error(
+            env.set_fallback_chain(SEED, &fallbacks).await,
+            DielemmaError::InvalidFallbackChain,
+        );
+    }
+
+    env.set_fallback_chain(SEED, &[(a, DAY), (b, 2 * DAY)]).await.unwrap();
+    assert_eq!(env.deposit_state(SEED).await.fallback_chain().len(), 2);
+}
@@ -0,0 +1,112 @@
+//! Guardian quorums: SetGuardians and AttestDeath making a deposit claimable early
+
+mod common;
+
+use common::*;
+use dielemma_program::{DielemmaError, GUARDIAN_GRACE_SECONDS, MAX_GUARDIANS};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+const SEED: &str = "test-seed";
+const AMOUNT: u64 = 500_000;
+const TIMEOUT: u64 = 30 * DAY;
+
+async fn env_with_guardians() -> (TestEnv, Vec<Keypair>) {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, TIMEOUT).await.unwrap();
+    let guardians: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    let keys: Vec<Pubkey> = guardians.iter().map(|g| g.pubkey()).collect();
+    env.set_guardians(SEED, 2, &keys).await.unwrap();
+    (env, guardians)
+}
+
+#[tokio::test]
+async fn test_guardian_quorum_accelerates_claim() {
+    let (mut env, guardians) = env_with_guardians().await;
+
+    env.attest_death(SEED, &guardians[0]).await.unwrap();
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.attestation_count(), 1);
+    assert_eq!(deposit.death_confirmed_at, 0);
+    assert_dielemma_error(env.initiate_claim(SEED).await, DielemmaError::NotExpired);
+
+    let confirmed_at = now(&mut env.context).await;
+    env.attest_death(SEED, &guardians[2]).await.unwrap();
+    assert_eq!(env.deposit_state(SEED).await.death_confirmed_at, confirmed_at);
+
+    // The depositor still has the grace period to prove life
+    assert_dielemma_error(env.initiate_claim(SEED).await, DielemmaError::NotExpired);
+
+    set_clock(&mut env.context, confirmed_at + GUARDIAN_GRACE_SECONDS as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT);
+}
+
+#[tokio::test]
+async fn test_proof_of_life_invalidates_attestations() {
+    let (mut env, guardians) = env_with_guardians().await;
+    env.attest_death(SEED, &guardians[0]).await.unwrap();
+    env.attest_death(SEED, &guardians[1]).await.unwrap();
+    let confirmed_at = env.deposit_state(SEED).await.death_confirmed_at;
+
+    set_clock(&mut env.context, confirmed_at + GUARDIAN_GRACE_SECONDS as i64 - 1).await;
+    env.proof_of_life(SEED).await.unwrap();
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.death_attestations, 0);
+    assert_eq!(deposit.death_confirmed_at, 0);
+
+    set_clock(&mut env.context, confirmed_at + GUARDIAN_GRACE_SECONDS as i64).await;
+    assert_dielemma_error(env.initiate_claim(SEED).await, DielemmaError::NotExpired);
+
+    // Guardians can attest again from scratch
+    env.attest_death(SEED, &guardians[0]).await.unwrap();
+    assert_eq!(env.deposit_state(SEED).await.attestation_count(), 1);
+}
+
+#[tokio::test]
+async fn test_attestation_requires_guardian() {
+    let (mut env, guardians) = env_with_guardians().await;
+
+    let outsider = Keypair::new();
+    assert_dielemma_error(env.attest_death(SEED, &outsider).await, DielemmaError::NotGuardian);
+
+    env.attest_death(SEED, &guardians[0]).await.unwrap();
+    assert_dielemma_error(env.attest_death(SEED, &guardians[0]).await, DielemmaError::AlreadyAttested);
+
+    // Replacing the guardians resets attestations; an empty set removes them
+    env.set_guardians(SEED, 0, &[]).await.unwrap();
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.guardian_count, 0);
+    assert_eq!(deposit.death_attestations, 0);
+    assert_dielemma_error(env.attest_death(SEED, &guardians[1]).await, DielemmaError::NotGuardian);
+}
+
+#[tokio::test]
+async fn test_set_guardians_validation() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, TIMEOUT).await.unwrap();
+    let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let depositor = env.depositor.pubkey();
+    let too_many: Vec<Pubkey> = (0..=MAX_GUARDIANS).map(|_| Pubkey::new_unique()).collect();
+
+    for (threshold, guardians) in [
+        (0, vec![a, b]),
+        (3, vec![a, b]),
+        (1, vec![]),
+        (1, vec![a, a]),
+        (1, vec![a, depositor]),
+        (1, too_many),
+    ] {
+        assert_dielemma_error(
+            env.set_guardians(SEED, threshold, &guardians).await,
+            DielemmaError::InvalidGuardians,
+        );
+    }
+
+    env.set_guardians(SEED, 2, &[a, b]).await.unwrap();
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.guardian_threshold, 2);
+    assert_eq!(&deposit.guardians[..2], &[a, b]);
+}
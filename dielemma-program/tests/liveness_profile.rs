@@ -0,0 +1,131 @@
+//! Depositor liveness profiles: CreateProfile, SetProfileLink and ProfileProofOfLife
+
+mod common;
+
+use common::*;
+use dielemma_program::{instruction, DielemmaError, GUARDIAN_GRACE_SECONDS};
+use solana_program_test::tokio;
+use solana_sdk::{
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+};
+
+const LINKED: &str = "linked";
+const UNLINKED: &str = "unlinked";
+const AMOUNT: u64 = 500_000;
+
+#[tokio::test]
+async fn test_profile_proof_keeps_linked_deposits_alive() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.create_profile().await.unwrap();
+    env.deposit(LINKED, AMOUNT, DAY).await.unwrap();
+    env.deposit(UNLINKED, AMOUNT, DAY).await.unwrap();
+    env.set_profile_link(LINKED, true).await.unwrap();
+    assert!(env.deposit_state(LINKED).await.profile_linked);
+
+    env.warp_after_last_proof(LINKED, DAY as i64 - 1).await;
+    let proved_at = now(&mut env.context).await;
+    env.profile_proof_of_life().await.unwrap();
+    assert_eq!(env.profile_state().await.last_proof_timestamp, proved_at);
+    assert_eq!(env.balance(env.depositor_dlm).await, 9 * ONE_DLM);
+    assert_eq!(env.balance(env.burn_dlm).await, ONE_DLM);
+
+    // Only the linked deposit benefits from the profile's proof of life
+    env.warp_after_last_proof(UNLINKED, DAY as i64 + 100).await;
+    env.initiate_claim(UNLINKED).await.unwrap();
+    assert_dielemma_error(env.initiate_claim(LINKED).await, DielemmaError::NotExpired);
+
+    set_clock(&mut env.context, proved_at + DAY as i64).await;
+    env.wait_out_claim(LINKED).await;
+    env.claim(LINKED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT);
+}
+
+#[tokio::test]
+async fn test_profile_proof_voids_pending_claim() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.create_profile().await.unwrap();
+    env.deposit(LINKED, AMOUNT, DAY).await.unwrap();
+    env.set_profile_link(LINKED, true).await.unwrap();
+
+    env.warp_after_last_proof(LINKED, DAY as i64).await;
+    env.wait_out_claim(LINKED).await;
+    env.profile_proof_of_life().await.unwrap();
+
+    assert_dielemma_error(env.claim(LINKED).await, DielemmaError::NotExpired);
+    assert_eq!(env.balance(env.receiver_token).await, 0);
+}
+
+#[tokio::test]
+async fn test_profile_proof_keeps_later_attestations() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.create_profile().await.unwrap();
+    env.deposit(LINKED, AMOUNT, 30 * DAY).await.unwrap();
+    env.set_profile_link(LINKED, true).await.unwrap();
+    let guardian = Keypair::new();
+    env.set_guardians(LINKED, 1, &[guardian.pubkey()]).await.unwrap();
+
+    // The guardian attests after the profile proof, so the claim sync must not void it
+    env.warp_after_last_proof(LINKED, DAY as i64 / 2).await;
+    env.profile_proof_of_life().await.unwrap();
+    let confirmed_at = now(&mut env.context).await;
+    env.attest_death(LINKED, &guardian).await.unwrap();
+    assert_eq!(env.deposit_state(LINKED).await.death_confirmed_at, confirmed_at);
+
+    set_clock(&mut env.context, confirmed_at + GUARDIAN_GRACE_SECONDS as i64).await;
+    env.wait_out_claim(LINKED).await;
+    env.claim(LINKED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT);
+}
+
+#[tokio::test]
+async fn test_unlinking_keeps_profile_liveness() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.create_profile().await.unwrap();
+    env.deposit(LINKED, AMOUNT, DAY).await.unwrap();
+    env.set_profile_link(LINKED, true).await.unwrap();
+
+    env.warp_after_last_proof(LINKED, 1_000).await;
+    let proved_at = now(&mut env.context).await;
+    env.profile_proof_of_life().await.unwrap();
+    env.set_profile_link(LINKED, false).await.unwrap();
+
+    let deposit = env.deposit_state(LINKED).await;
+    assert!(!deposit.profile_linked);
+    assert_eq!(deposit.last_proof_timestamp, proved_at);
+
+    // Further profile proofs no longer count for the deposit
+    env.warp_after_last_proof(LINKED, DAY as i64 - 1).await;
+    env.profile_proof_of_life().await.unwrap();
+    env.warp_after_last_proof(LINKED, DAY as i64).await;
+    env.initiate_claim(LINKED).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_profile_account_validation() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(LINKED, AMOUNT, DAY).await.unwrap();
+
+    assert_dielemma_error(env.set_profile_link(LINKED, true).await, DielemmaError::InvalidProfileAccount);
+    assert_dielemma_error(env.profile_proof_of_life().await, DielemmaError::InvalidProfileAccount);
+
+    env.create_profile().await.unwrap();
+    assert_instruction_error(env.create_profile().await, InstructionError::AccountAlreadyInitialized);
+    env.set_profile_link(LINKED, true).await.unwrap();
+    env.warp_after_last_proof(LINKED, DAY as i64).await;
+
+    // Claims on a linked deposit must pass the depositor's profile
+    let receiver = env.receiver.insecure_clone();
+    let ix = instruction::initiate_claim(&dielemma_program::id(), &receiver.pubkey(), &env.depositor.pubkey(), LINKED, false);
+    assert_instruction_error(
+        process(&mut env.context, &[ix], &[&receiver]).await,
+        InstructionError::NotEnoughAccountKeys,
+    );
+
+    let mut ix = instruction::initiate_claim(&dielemma_program::id(), &receiver.pubkey(), &env.depositor.pubkey(), LINKED, true);
+    ix.accounts[3].pubkey = instruction::find_config_address(&dielemma_program::id()).0;
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&receiver]).await,
+        DielemmaError::InvalidProfileAccount,
+    );
+}
@@ -0,0 +1,109 @@
+//! Layout migration: MigrateDeposit converting deposits written before beneficiaries
+
+mod common;
+
+use common::*;
+use dielemma_program::{
+    Beneficiary, DielemmaError, DEPOSIT_ACCOUNT_SIZE, DEPOSIT_LAYOUT_VERSION, LEGACY_DEPOSIT_ACCOUNT_SIZE,
+    TOTAL_SHARE_BPS,
+};
+use solana_program::rent::Rent;
+use solana_program_test::tokio;
+use solana_sdk::{instruction::InstructionError, signature::Signer};
+
+const SEED: &str = "test-seed";
+const AMOUNT: u64 = 500_000;
+
+#[tokio::test]
+async fn test_migration_keeps_legacy_terms() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let proved_at = now(&mut env.context).await - 100;
+    env.legacy_deposit(SEED, AMOUNT, DAY, proved_at);
+    let address = env.deposit_address(SEED);
+    assert_eq!(account_data_len(&mut env.context, &address).await, LEGACY_DEPOSIT_ACCOUNT_SIZE);
+
+    // Anyone can migrate; the transaction fee payer covers the extra rent
+    env.migrate_deposit(SEED).await.unwrap();
+    assert_eq!(account_data_len(&mut env.context, &address).await, DEPOSIT_ACCOUNT_SIZE);
+    assert_eq!(env.lamports(address).await, Rent::default().minimum_balance(DEPOSIT_ACCOUNT_SIZE));
+
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.layout_version, DEPOSIT_LAYOUT_VERSION);
+    assert_eq!(deposit.depositor, env.depositor.pubkey());
+    assert_eq!(
+        deposit.beneficiaries(),
+        &[Beneficiary {
+            receiver: env.receiver.pubkey(),
+            share_bps: TOTAL_SHARE_BPS,
+            claimed: false,
+            claimed_amount: 0,
+        }]
+    );
+    assert_eq!(deposit.token_mint, env.mint);
+    assert_eq!(deposit.amount, AMOUNT);
+    assert_eq!(deposit.last_proof_timestamp, proved_at);
+    assert_eq!(deposit.timeout_seconds, DAY);
+    assert_eq!(deposit.withdraw_locked_until, 0);
+    assert!(!deposit.is_closed);
+    assert_eq!(&deposit.deposit_seed[..deposit.deposit_seed_len as usize], SEED.as_bytes());
+}
+
+#[tokio::test]
+async fn test_migrated_deposit_can_be_claimed() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let proved_at = now(&mut env.context).await;
+    env.legacy_deposit(SEED, AMOUNT, DAY, proved_at);
+    env.migrate_deposit(SEED).await.unwrap();
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT);
+    assert!(env.deposit_state(SEED).await.is_closed);
+}
+
+#[tokio::test]
+async fn test_migrated_deposit_can_be_withdrawn() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let proved_at = now(&mut env.context).await;
+    env.legacy_deposit(SEED, AMOUNT, DAY, proved_at);
+    env.migrate_deposit(SEED).await.unwrap();
+
+    env.proof_of_life(SEED).await.unwrap();
+    env.withdraw(SEED).await.unwrap();
+    assert_eq!(env.balance(env.depositor_token).await, INITIAL_BALANCE + AMOUNT);
+}
+
+#[tokio::test]
+async fn test_migration_rejects_current_layout() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let before = env.deposit_state(SEED).await;
+
+    assert_instruction_error(env.migrate_deposit(SEED).await, InstructionError::InvalidAccountData);
+    assert_eq!(env.deposit_state(SEED).await, before);
+
+    // A legacy deposit can only be migrated once
+    let proved_at = now(&mut env.context).await;
+    env.legacy_deposit("legacy", AMOUNT, DAY, proved_at);
+    env.migrate_deposit("legacy").await.unwrap();
+    assert_instruction_error(env.migrate_deposit("legacy").await, InstructionError::InvalidAccountData);
+}
+
+#[tokio::test]
+async fn test_unmigrated_deposit_is_rejected() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let proved_at = now(&mut env.context).await;
+    env.legacy_deposit(SEED, AMOUNT, DAY, proved_at);
+
+    assert_dielemma_error(env.proof_of_life(SEED).await, DielemmaError::OutdatedDepositLayout);
+    assert_dielemma_error(env.withdraw(SEED).await, DielemmaError::OutdatedDepositLayout);
+    assert_dielemma_error(env.initiate_claim(SEED).await, DielemmaError::OutdatedDepositLayout);
+
+    // A layout version this program does not know is rejected too
+    env.migrate_deposit(SEED).await.unwrap();
+    let mut deposit = env.deposit_state(SEED).await;
+    deposit.layout_version = DEPOSIT_LAYOUT_VERSION + 1;
+    env.set_deposit_state(SEED, &deposit);
+    assert_dielemma_error(env.proof_of_life(SEED).await, DielemmaError::OutdatedDepositLayout);
+}
@@ -0,0 +1,209 @@
+//! Native SOL deposits and unwrapping of wSOL deposits on claim
+
+mod common;
+
+use common::*;
+use dielemma_program::{DielemmaError, DEPOSIT_ACCOUNT_SIZE, NATIVE_SOL_MINT};
+use solana_program::{program_option::COption, program_pack::Pack, pubkey::Pubkey, rent::Rent};
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::{account::Account, instruction::InstructionError, signature::Signer};
+use spl_token::state::{Account as TokenAccount, AccountState};
+
+const SEED: &str = "test-seed";
+const LAMPORTS: u64 = 2_000_000_000;
+
+/// Rent held by a deposit account until CloseAccount
+fn deposit_rent() -> u64 {
+    Rent::default().minimum_balance(DEPOSIT_ACCOUNT_SIZE)
+}
+
+#[tokio::test]
+async fn test_deposit_sol_and_withdraw() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let depositor = env.depositor.pubkey();
+    let before = env.lamports(depositor).await;
+
+    env.deposit_sol(SEED, LAMPORTS, DAY).await.unwrap();
+    let deposit = env.deposit_state(SEED).await;
+    assert!(deposit.is_native());
+    assert_eq!(deposit.token_mint, NATIVE_SOL_MINT);
+    assert_eq!(deposit.amount, LAMPORTS);
+    assert_eq!(env.lamports(env.deposit_address(SEED)).await, deposit_rent() + LAMPORTS);
+    assert_eq!(env.lamports(depositor).await, before - deposit_rent() - LAMPORTS);
+
+    // Proof-of-life works the same for SOL deposits
+    env.proof_of_life(SEED).await.unwrap();
+
+    env.withdraw_sol(SEED).await.unwrap();
+    assert!(env.deposit_state(SEED).await.is_closed);
+    assert_eq!(env.lamports(env.deposit_address(SEED)).await, deposit_rent());
+    assert_eq!(env.lamports(depositor).await, before - deposit_rent());
+    assert_dielemma_error(env.withdraw_sol(SEED).await, DielemmaError::AlreadyClosed);
+    assert_dielemma_error(env.claim_sol(SEED, None).await, DielemmaError::AlreadyClosed);
+
+    let ix = env.close_ix(SEED, &depositor);
+    let signer = env.depositor.insecure_clone();
+    process(&mut env.context, &[ix], &[&signer]).await.unwrap();
+    assert_eq!(env.lamports(depositor).await, before);
+}
+
+#[tokio::test]
+async fn test_claim_sol_after_expiry() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit_sol(SEED, LAMPORTS, DAY).await.unwrap();
+    let receiver = env.receiver.pubkey();
+    let before = env.lamports(receiver).await;
+
+    env.warp_after_last_proof(SEED, DAY as i64 - 1).await;
+    assert_dielemma_error(env.claim_sol(SEED, None).await, DielemmaError::NotExpired);
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim_sol(SEED, None).await.unwrap();
+    assert_eq!(env.lamports(receiver).await, before + LAMPORTS);
+    assert_eq!(env.lamports(env.deposit_address(SEED)).await, deposit_rent());
+    assert_dielemma_error(env.claim_sol(SEED, None).await, DielemmaError::AlreadyClosed);
+}
+
+#[tokio::test]
+async fn test_deposit_sol_rejects_invalid_terms() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    assert_dielemma_error(env.deposit_sol(SEED, 0, DAY).await, DielemmaError::InvalidAmount);
+    assert_dielemma_error(env.deposit_sol(SEED, LAMPORTS, 59).await, DielemmaError::InvalidTimeout);
+
+    env.set_paused(true).await.unwrap();
+    assert_dielemma_error(env.deposit_sol(SEED, LAMPORTS, DAY).await, DielemmaError::ProgramPaused);
+}
+
+#[tokio::test]
+async fn test_sol_and_token_instructions_do_not_mix() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit("tokens", 500_000, DAY).await.unwrap();
+    env.deposit_sol(SEED, LAMPORTS, DAY).await.unwrap();
+
+    assert_dielemma_error(env.withdraw_sol("tokens").await, DielemmaError::NotNativeDeposit);
+    env.warp_after_last_proof("tokens", DAY as i64).await;
+    env.wait_out_claim("tokens").await;
+    assert_dielemma_error(env.claim_sol("tokens", None).await, DielemmaError::NotNativeDeposit);
+
+    // The SOL deposit has no vault to withdraw from
+    assert_dielemma_error(env.withdraw(SEED).await, DielemmaError::MintMismatch);
+}
+
+#[tokio::test]
+async fn test_claim_sol_rejects_wrong_signer() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit_sol(SEED, LAMPORTS, DAY).await.unwrap();
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+
+    let mut ix = dielemma_program::instruction::claim_sol(
+        &dielemma_program::id(),
+        &env.receiver.pubkey(),
+        &env.depositor.pubkey(),
+        None,
+        SEED,
+        false,
+    );
+    ix.accounts[0].is_signer = false;
+    assert_instruction_error(
+        process(&mut env.context, &[ix], &[]).await,
+        InstructionError::MissingRequiredSignature,
+    );
+
+    let mut ix = dielemma_program::instruction::claim_sol(
+        &dielemma_program::id(),
+        &env.depositor.pubkey(),
+        &env.depositor.pubkey(),
+        None,
+        SEED,
+        false,
+    );
+    ix.accounts[0].is_writable = true;
+    let depositor = env.depositor.insecure_clone();
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        DielemmaError::NotReceiver,
+    );
+}
+
+/// Write a wSOL token account holding `amount` wrapped lamports
+fn set_wsol_account(context: &mut ProgramTestContext, address: &Pubkey, owner: &Pubkey, amount: u64) {
+    let rent = Rent::default().minimum_balance(TokenAccount::LEN);
+    let mut data = vec![0u8; TokenAccount::LEN];
+    TokenAccount::pack(
+        TokenAccount {
+            mint: spl_token::native_mint::id(),
+            owner: *owner,
+            amount,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::Some(rent),
+            delegated_amount: 0,
+            close_authority: COption::None,
+        },
+        &mut data,
+    )
+    .unwrap();
+    let account = Account {
+        lamports: rent + amount,
+        data,
+        owner: spl_token::id(),
+        executable: false,
+        rent_epoch: 0,
+    };
+    context.set_account(address, &account.into());
+}
+
+#[tokio::test]
+async fn test_claim_sol_unwraps_wsol_deposit() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.mint = spl_token::native_mint::id();
+    let (mint, depositor_token, depositor) = (env.mint, env.depositor_token, env.depositor.pubkey());
+    set_mint(&mut env.context, &mint, 9, &spl_token::id());
+    set_wsol_account(&mut env.context, &depositor_token, &depositor, LAMPORTS);
+
+    env.deposit(SEED, LAMPORTS, DAY).await.unwrap();
+    let vault = env.vault_address(SEED);
+    let vault_lamports = env.lamports(vault).await;
+    assert_eq!(env.balance(vault).await, LAMPORTS);
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    let receiver = env.receiver.pubkey();
+    let before = env.lamports(receiver).await;
+    env.claim_sol(SEED, Some(&spl_token::id())).await.unwrap();
+
+    // The vault is closed: its wrapped lamports go to the receiver and its rent stays with the deposit
+    let vault_rent = vault_lamports - LAMPORTS;
+    assert_eq!(env.lamports(vault).await, 0);
+    assert_eq!(env.lamports(receiver).await, before + LAMPORTS);
+    assert!(env.deposit_state(SEED).await.is_closed);
+    let deposit_address = env.deposit_address(SEED);
+    assert_eq!(env.lamports(deposit_address).await, deposit_rent() + vault_rent);
+
+    // Closing the deposit refunds both rents to the depositor who paid them
+    let before = env.lamports(depositor).await;
+    let ix = env.close_ix(SEED, &depositor);
+    let signer = env.depositor.insecure_clone();
+    process(&mut env.context, &[ix], &[&signer]).await.unwrap();
+    assert_eq!(env.lamports(depositor).await, before + deposit_rent() + vault_rent);
+}
+
+#[tokio::test]
+async fn test_top_up_rejects_sol_deposit() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit_sol(SEED, LAMPORTS, DAY).await.unwrap();
+
+    assert_dielemma_error(env.top_up(SEED, 1).await, DielemmaError::NotNativeDeposit);
+    assert_eq!(env.deposit_state(SEED).await.amount, LAMPORTS);
+}
+
+#[tokio::test]
+async fn test_partial_withdraw_rejects_sol_deposit() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit_sol(SEED, LAMPORTS, DAY).await.unwrap();
+
+    assert_dielemma_error(env.partial_withdraw(SEED, 1).await, DielemmaError::NotNativeDeposit);
+    assert_eq!(env.deposit_state(SEED).await.amount, LAMPORTS);
+}
@@ -0,0 +1,80 @@
+//! PartialWithdraw: taking part of a deposit back while it stays active
+
+mod common;
+
+use common::*;
+use dielemma_program::DielemmaError;
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+
+const SEED: &str = "test-seed";
+const AMOUNT: u64 = 500_000;
+
+#[tokio::test]
+async fn test_partial_withdraw_keeps_deposit_active() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let before = env.deposit_state(SEED).await;
+
+    env.partial_withdraw(SEED, 200_000).await.unwrap();
+
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.amount, 300_000);
+    assert!(!deposit.is_closed);
+    assert_eq!(deposit.beneficiaries(), before.beneficiaries());
+    assert_eq!(deposit.timeout_seconds, before.timeout_seconds);
+    assert_eq!(deposit.last_proof_timestamp, before.last_proof_timestamp);
+    assert_eq!(env.balance(env.vault_address(SEED)).await, 300_000);
+    assert_eq!(env.balance(env.depositor_token).await, INITIAL_BALANCE - 300_000);
+
+    // The receiver still gets what is left once proof-of-life expires
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, 300_000);
+}
+
+#[tokio::test]
+async fn test_partial_withdraw_then_withdraw_rest() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    env.partial_withdraw(SEED, 100_000).await.unwrap();
+    env.partial_withdraw(SEED, 100_000).await.unwrap();
+    env.withdraw(SEED).await.unwrap();
+
+    assert_eq!(env.balance(env.depositor_token).await, INITIAL_BALANCE);
+    assert_eq!(env.balance(env.vault_address(SEED)).await, 0);
+}
+
+#[tokio::test]
+async fn test_partial_withdraw_rejects_invalid_requests() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let (second, second_token) = env.new_receiver();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    assert_dielemma_error(env.partial_withdraw(SEED, 0).await, DielemmaError::InvalidAmount);
+    assert_dielemma_error(env.partial_withdraw(SEED, AMOUNT).await, DielemmaError::InvalidAmount);
+
+    // Only the depositor can withdraw
+    let receiver = env.receiver.insecure_clone();
+    let mut ix = env.partial_withdraw_ix(SEED, 1);
+    ix.accounts[0].pubkey = receiver.pubkey();
+    ix.accounts[2].pubkey = env.receiver_token;
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&receiver]).await,
+        DielemmaError::InvalidDepositAccount,
+    );
+
+    // Not once a share has been paid out
+    env.set_beneficiaries(SEED, &[(env.receiver.pubkey(), 5_000), (second.pubkey(), 5_000)])
+        .await
+        .unwrap();
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim_as(SEED, &second, &second_token).await.unwrap();
+    assert_dielemma_error(env.partial_withdraw(SEED, 1).await, DielemmaError::ShareAlreadyClaimed);
+
+    env.withdraw(SEED).await.unwrap();
+    assert_dielemma_error(env.partial_withdraw(SEED, 1).await, DielemmaError::AlreadyClosed);
+}
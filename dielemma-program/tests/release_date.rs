@@ -0,0 +1,68 @@
+//! Time-capsule release dates: SetReleaseDate unlocking claims regardless of liveness
+
+mod common;
+
+use common::*;
+use dielemma_program::DielemmaError;
+use solana_program_test::tokio;
+
+const SEED: &str = "test-seed";
+const AMOUNT: u64 = 500_000;
+const TIMEOUT: u64 = 30 * DAY;
+
+#[tokio::test]
+async fn test_release_date_unlocks_claim_despite_proof_of_life() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, TIMEOUT).await.unwrap();
+    let release_at = now(&mut env.context).await + 10 * DAY as i64;
+    env.set_release_date(SEED, release_at, true).await.unwrap();
+
+    set_clock(&mut env.context, release_at - 1).await;
+    assert_dielemma_error(env.initiate_claim(SEED).await, DielemmaError::NotExpired);
+    env.proof_of_life(SEED).await.unwrap();
+
+    set_clock(&mut env.context, release_at).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT);
+}
+
+#[tokio::test]
+async fn test_release_date_only_ignores_expiry() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, TIMEOUT).await.unwrap();
+    let release_at = now(&mut env.context).await + 2 * TIMEOUT as i64;
+    env.set_release_date(SEED, release_at, true).await.unwrap();
+
+    env.warp_after_last_proof(SEED, TIMEOUT as i64).await;
+    assert_dielemma_error(env.initiate_claim(SEED).await, DielemmaError::NotExpired);
+
+    // Without `release_at_only`, whichever condition comes first unlocks the claim
+    env.set_release_date(SEED, release_at, false).await.unwrap();
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT);
+}
+
+#[tokio::test]
+async fn test_set_release_date_validation() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, TIMEOUT).await.unwrap();
+    let now = now(&mut env.context).await;
+
+    assert_dielemma_error(env.set_release_date(SEED, now, false).await, DielemmaError::InvalidReleaseDate);
+    assert_dielemma_error(env.set_release_date(SEED, 0, true).await, DielemmaError::InvalidReleaseDate);
+
+    env.set_release_date(SEED, now + DAY as i64, false).await.unwrap();
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.release_at, now + DAY as i64);
+    assert!(!deposit.release_at_only);
+
+    // The release terms are frozen once the receiver has started claiming
+    env.set_vesting(SEED, TIMEOUT).await.unwrap();
+    set_clock(&mut env.context, now + DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert!(!env.deposit_state(SEED).await.is_closed);
+    assert_dielemma_error(env.set_release_date(SEED, 0, false).await, DielemmaError::ShareAlreadyClaimed);
+}
@@ -0,0 +1,91 @@
+//! Release tranches: SetReleaseTranches unlocking shares at multiples of the timeout
+
+mod common;
+
+use common::*;
+use dielemma_program::{DielemmaError, ReleaseTranche, MAX_RELEASE_TRANCHES};
+use solana_program_test::tokio;
+
+const SEED: &str = "test-seed";
+const AMOUNT: u64 = 1_000_000;
+const TIMEOUT: u64 = 30 * DAY;
+const TRANCHES: &[(u8, u16)] = &[(1, 2_500), (2, 10_000)];
+
+#[tokio::test]
+async fn test_tranches_unlock_with_time_since_last_proof() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, TIMEOUT).await.unwrap();
+    env.set_release_tranches(SEED, TRANCHES).await.unwrap();
+
+    env.warp_after_last_proof(SEED, TIMEOUT as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT / 4);
+    assert!(!env.deposit_state(SEED).await.is_closed);
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::NothingUnlocked);
+
+    env.warp_after_last_proof(SEED, 2 * TIMEOUT as i64 - 1).await;
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::NothingUnlocked);
+
+    env.warp_after_last_proof(SEED, 2 * TIMEOUT as i64).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT);
+
+    let deposit = env.deposit_state(SEED).await;
+    assert!(deposit.is_closed);
+    assert!(deposit.beneficiaries()[0].claimed);
+}
+
+#[tokio::test]
+async fn test_proof_of_life_stops_unlocking() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, TIMEOUT).await.unwrap();
+    env.set_release_tranches(SEED, TRANCHES).await.unwrap();
+    let first_proof = env.deposit_state(SEED).await.last_proof_timestamp;
+
+    env.warp_after_last_proof(SEED, TIMEOUT as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+
+    // The returning depositor keeps everything that had not unlocked yet
+    env.proof_of_life(SEED).await.unwrap();
+    set_clock(&mut env.context, first_proof + 2 * TIMEOUT as i64).await;
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::NotExpired);
+    env.withdraw(SEED).await.unwrap();
+    assert_eq!(env.balance(env.depositor_token).await, INITIAL_BALANCE - AMOUNT / 4);
+}
+
+#[tokio::test]
+async fn test_set_release_tranches_validation() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, TIMEOUT).await.unwrap();
+
+    for tranches in [
+        vec![(0, 10_000)],
+        vec![(1, 5_000)],
+        vec![(2, 5_000), (1, 10_000)],
+        vec![(1, 5_000), (2, 5_000)],
+        vec![(1, 10_001)],
+        (1..=MAX_RELEASE_TRANCHES as u8 + 1).map(|i| (i, i as u16 * 2_000)).collect(),
+    ] {
+        assert_dielemma_error(
+            env.set_release_tranches(SEED, &tranches).await,
+            DielemmaError::InvalidTranches,
+        );
+    }
+
+    env.set_release_tranches(SEED, TRANCHES).await.unwrap();
+    assert_eq!(
+        env.deposit_state(SEED).await.tranches(),
+        &[
+            ReleaseTranche { timeout_multiple: 1, unlocked_bps: 2_500 },
+            ReleaseTranche { timeout_multiple: 2, unlocked_bps: 10_000 },
+        ]
+    );
+
+    // The schedule is frozen once the receiver has started claiming
+    env.warp_after_last_proof(SEED, TIMEOUT as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert_dielemma_error(env.set_release_tranches(SEED, &[]).await, DielemmaError::ShareAlreadyClaimed);
+}
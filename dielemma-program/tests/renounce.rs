@@ -0,0 +1,149 @@
+//! Receivers renouncing a deposit: Renounce and SetFallbackReceiver
+
+mod common;
+
+use common::*;
+use dielemma_program::{DielemmaError, WITHDRAW_LOCKED_FOREVER};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+const SEED: &str = "test-seed";
+const AMOUNT: u64 = 1_000_000;
+
+#[tokio::test]
+async fn test_renounced_deposit_becomes_withdraw_only() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    let receiver = env.receiver.insecure_clone();
+    env.renounce(SEED, &receiver).await.unwrap();
+    assert!(env.deposit_state(SEED).await.beneficiaries().is_empty());
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    assert_dielemma_error(env.initiate_claim(SEED).await, DielemmaError::NotReceiver);
+    env.withdraw(SEED).await.unwrap();
+    assert_eq!(env.balance(env.depositor_token).await, INITIAL_BALANCE);
+}
+
+#[tokio::test]
+async fn test_last_renounce_lifts_withdraw_lock() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let ix = env.locked_deposit_ix(SEED, AMOUNT, DAY, WITHDRAW_LOCKED_FOREVER);
+    let depositor = env.depositor.insecure_clone();
+    process(&mut env.context, &[ix], &[&depositor]).await.unwrap();
+
+    // An irrevocable deposit nobody can claim any more goes back to the depositor
+    let receiver = env.receiver.insecure_clone();
+    env.renounce(SEED, &receiver).await.unwrap();
+    assert_eq!(env.deposit_state(SEED).await.withdraw_locked_until, 0);
+    env.withdraw(SEED).await.unwrap();
+    assert_eq!(env.balance(env.depositor_token).await, INITIAL_BALANCE);
+}
+
+#[tokio::test]
+async fn test_locked_renounced_share_goes_to_unpaid_receivers() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let (second, second_token) = env.new_receiver();
+    let (third, third_token) = env.new_receiver();
+    let fourth = Keypair::new();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.set_beneficiaries(
+        SEED,
+        &[
+            (env.receiver.pubkey(), 4_000),
+            (second.pubkey(), 3_000),
+            (third.pubkey(), 2_000),
+            (fourth.pubkey(), 1_000),
+        ],
+    )
+    .await
+    .unwrap();
+
+    // Beneficiaries cannot be set once a deposit is locked, so lock it afterwards
+    let mut deposit = env.deposit_state(SEED).await;
+    deposit.withdraw_locked_until = WITHDRAW_LOCKED_FOREVER;
+    env.set_deposit_state(SEED, &deposit);
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, 400_000);
+
+    // The depositor cannot take the renounced share back, so the receivers still owed theirs split it
+    env.renounce(SEED, &fourth).await.unwrap();
+    let shares: Vec<_> = env.deposit_state(SEED).await.beneficiaries().iter().map(|b| b.share_bps).collect();
+    assert_eq!(shares, [4_000, 3_600, 2_400]);
+
+    env.claim_as(SEED, &second, &second_token).await.unwrap();
+    env.claim_as(SEED, &third, &third_token).await.unwrap();
+    assert_eq!(env.balance(second_token).await, 360_000);
+    assert_eq!(env.balance(third_token).await, 240_000);
+    assert_eq!(env.balance(env.vault_address(SEED)).await, 0);
+    assert!(env.deposit_state(SEED).await.is_closed);
+}
+
+#[tokio::test]
+async fn test_renounced_share_passes_to_fallback_receiver() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let (fallback, fallback_token) = env.new_receiver();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.set_fallback_receiver(SEED, &fallback.pubkey()).await.unwrap();
+
+    let receiver = env.receiver.insecure_clone();
+    env.renounce(SEED, &receiver).await.unwrap();
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.beneficiaries()[0].receiver, fallback.pubkey());
+    assert_eq!(deposit.fallback_receiver, Pubkey::default());
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim_as(SEED, &fallback).await;
+    env.claim_as(SEED, &fallback, &fallback_token).await.unwrap();
+    assert_eq!(env.balance(fallback_token).await, AMOUNT);
+    assert!(env.deposit_state(SEED).await.is_closed);
+}
+
+#[tokio::test]
+async fn test_renounced_share_is_left_to_depositor() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let second = Keypair::new();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.set_beneficiaries(SEED, &[(env.receiver.pubkey(), 4_000), (second.pubkey(), 6_000)])
+        .await
+        .unwrap();
+    env.renounce(SEED, &second).await.unwrap();
+
+    // The remaining receiver only gets their own share, not the renounced one
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, 400_000);
+    assert!(!env.deposit_state(SEED).await.is_closed);
+
+    env.withdraw(SEED).await.unwrap();
+    assert_eq!(env.balance(env.depositor_token).await, INITIAL_BALANCE - 400_000);
+}
+
+#[tokio::test]
+async fn test_renounce_rejects_invalid_requests() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let depositor = env.depositor.pubkey();
+    let receiver = env.receiver.pubkey();
+
+    assert_dielemma_error(env.renounce(SEED, &Keypair::new()).await, DielemmaError::NotReceiver);
+    for fallback in [depositor, receiver] {
+        assert_dielemma_error(
+            env.set_fallback_receiver(SEED, &fallback).await,
+            DielemmaError::InvalidBeneficiaries,
+        );
+    }
+
+    // A claim initiated by the renouncing receiver is voided
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.initiate_claim(SEED).await.unwrap();
+    let receiver = env.receiver.insecure_clone();
+    env.renounce(SEED, &receiver).await.unwrap();
+    assert!(!env.deposit_state(SEED).await.has_pending_claim());
+    assert_dielemma_error(env.renounce(SEED, &receiver).await, DielemmaError::NotReceiver);
+}
@@ -0,0 +1,162 @@
+//! Depositor key rotation: RotateDepositor moving a deposit under a new depositor key
+
+mod common;
+
+use common::*;
+use dielemma_program::{DepositAccount, DielemmaError, DEPOSIT_ACCOUNT_SIZE};
+use solana_program::{program_pack::Pack, rent::Rent};
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+use spl_token::state::Account as TokenAccount;
+
+const SEED: &str = "test-seed";
+const AMOUNT: u64 = 500_000;
+const LAMPORTS: u64 = 2_000_000_000;
+
+#[tokio::test]
+async fn test_rotation_moves_deposit_and_keeps_history() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.set_release_tranches(SEED, &[(1, 10_000)]).await.unwrap();
+    env.warp_after_last_proof(SEED, DAY as i64 / 2).await;
+
+    let before = env.deposit_state(SEED).await;
+    let (old_deposit, old_vault) = (env.deposit_address(SEED), env.vault_address(SEED));
+    let old_depositor = env.depositor.pubkey();
+    let old_lamports = env.lamports(old_depositor).await;
+
+    // A funded wallet with its own DLM, so it can keep proving life
+    let new_depositor = env.new_delegate();
+    env.rotate_depositor(SEED, &new_depositor).await.unwrap();
+
+    let after = env.deposit_state(SEED).await;
+    assert_eq!(after.depositor, new_depositor.pubkey());
+    assert_eq!(DepositAccount { depositor: old_depositor, bump: before.bump, ..after }, before);
+    assert_eq!(env.balance(env.vault_address(SEED)).await, AMOUNT);
+
+    // The old accounts are closed and their rent refunded to the old key
+    assert_eq!(env.lamports(old_deposit).await, 0);
+    assert_eq!(env.lamports(old_vault).await, 0);
+    let rent = Rent::default();
+    assert_eq!(
+        env.lamports(old_depositor).await,
+        old_lamports + rent.minimum_balance(DEPOSIT_ACCOUNT_SIZE) + rent.minimum_balance(TokenAccount::LEN)
+    );
+
+    // Rotating is not a proof of life: the deposit still expires on schedule
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT);
+}
+
+#[tokio::test]
+async fn test_new_depositor_controls_deposit() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let new_depositor = env.new_delegate();
+    env.rotate_depositor(SEED, &new_depositor).await.unwrap();
+
+    env.proof_of_life(SEED).await.unwrap();
+    env.partial_withdraw(SEED, 1).await.unwrap();
+    env.withdraw(SEED).await.unwrap();
+    assert_eq!(env.balance(env.depositor_token).await, AMOUNT);
+    assert!(env.deposit_state(SEED).await.is_closed);
+}
+
+#[tokio::test]
+async fn test_rotate_sol_deposit() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit_sol(SEED, LAMPORTS, DAY).await.unwrap();
+    let old_depositor = env.depositor.pubkey();
+    let old_lamports = env.lamports(old_depositor).await;
+
+    let new_depositor = env.new_delegate();
+    env.rotate_depositor(SEED, &new_depositor).await.unwrap();
+    let deposit_rent = Rent::default().minimum_balance(DEPOSIT_ACCOUNT_SIZE);
+    assert_eq!(env.lamports(env.deposit_address(SEED)).await, deposit_rent + LAMPORTS);
+    assert_eq!(env.lamports(old_depositor).await, old_lamports + deposit_rent);
+
+    let new_lamports = env.lamports(new_depositor.pubkey()).await;
+    env.withdraw_sol(SEED).await.unwrap();
+    assert_eq!(env.lamports(new_depositor.pubkey()).await, new_lamports + LAMPORTS);
+}
+
+#[tokio::test]
+async fn test_rotate_linked_deposit_keeps_profile_proof() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.create_profile().await.unwrap();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.set_profile_link(SEED, true).await.unwrap();
+
+    env.warp_after_last_proof(SEED, DAY as i64 - 1).await;
+    let proved_at = now(&mut env.context).await;
+    env.profile_proof_of_life().await.unwrap();
+
+    // The profile belongs to the old key, so the deposit takes its proof along and unlinks
+    let new_depositor = env.new_delegate();
+    env.rotate_depositor(SEED, &new_depositor).await.unwrap();
+    let deposit = env.deposit_state(SEED).await;
+    assert!(!deposit.profile_linked);
+    assert_eq!(deposit.last_proof_timestamp, proved_at);
+}
+
+#[tokio::test]
+async fn test_rotate_transfer_fee_deposit() {
+    let mut env = TestEnv::new(spl_token_2022::id()).await;
+    env.use_transfer_fee_mint(100, AMOUNT).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let net = env.deposit_state(SEED).await.amount;
+    let old_vault = env.vault_address(SEED);
+
+    // The fee withheld on the old vault is harvested so it can be closed
+    let new_depositor = env.new_delegate();
+    env.rotate_depositor(SEED, &new_depositor).await.unwrap();
+    assert_eq!(env.lamports(old_vault).await, 0);
+
+    let moved = net - net / 100;
+    assert_eq!(env.deposit_state(SEED).await.amount, moved);
+    assert_eq!(env.balance(env.vault_address(SEED)).await, moved);
+}
+
+#[tokio::test]
+async fn test_rotate_rejects_invalid_new_depositor() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let fallback = env.new_delegate();
+    env.set_fallback_chain(SEED, &[(fallback.pubkey(), DAY)]).await.unwrap();
+
+    let depositor = env.depositor.insecure_clone();
+    let receiver = env.receiver.insecure_clone();
+    for new_depositor in [depositor, receiver, fallback] {
+        assert_dielemma_error(
+            env.rotate_depositor(SEED, &new_depositor).await,
+            DielemmaError::InvalidNewDepositor,
+        );
+    }
+    assert_eq!(env.deposit_state(SEED).await.depositor, env.depositor.pubkey());
+}
+
+#[tokio::test]
+async fn test_rotate_rejects_guardian() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let guardian = env.new_delegate();
+    env.set_guardians(SEED, 1, &[guardian.pubkey()]).await.unwrap();
+
+    // A guardian could otherwise attest death on a deposit they control
+    assert_dielemma_error(env.rotate_depositor(SEED, &guardian).await, DielemmaError::InvalidNewDepositor);
+    assert_eq!(env.deposit_state(SEED).await.depositor, env.depositor.pubkey());
+}
+
+#[tokio::test]
+async fn test_rotate_rejects_delegate() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let delegate = env.new_delegate();
+    let expires_at = now(&mut env.context).await + DAY as i64;
+    env.add_delegate(SEED, &delegate.pubkey(), expires_at).await.unwrap();
+
+    assert_dielemma_error(env.rotate_depositor(SEED, &delegate).await, DielemmaError::InvalidNewDepositor);
+    assert_eq!(env.deposit_state(SEED).await.depositor, env.depositor.pubkey());
+}
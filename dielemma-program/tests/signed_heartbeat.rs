@@ -0,0 +1,140 @@
+//! SignedProofOfLife: heartbeats signed off-chain by the depositor and submitted by a relayer
+
+mod common;
+
+use common::*;
+use dielemma_program::{instruction, DielemmaError};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+const SEED: &str = "test-seed";
+const AMOUNT: u64 = 500_000;
+
+#[tokio::test]
+async fn test_relayed_heartbeat_resets_timer() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let relayer = env.new_delegate();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    env.warp_after_last_proof(SEED, DAY as i64 - 1).await;
+    let expected_timestamp = now(&mut env.context).await;
+    env.initiate_claim(SEED).await.unwrap_err();
+    env.signed_proof_of_life(SEED, &relayer, 1, expected_timestamp + 60).await.unwrap();
+
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.last_proof_timestamp, expected_timestamp);
+    assert_eq!(deposit.heartbeat_nonce, 1);
+    // The relayer pays the burn; the depositor's keys and balances are untouched
+    let relayer_dlm = instruction::get_associated_token_address(&relayer.pubkey(), &env.dlm_mint, &spl_token_2022::id());
+    assert_eq!(env.balance(relayer_dlm).await, 9 * ONE_DLM);
+    assert_eq!(env.balance(env.depositor_dlm).await, 10 * ONE_DLM);
+
+    // Nonces may skip ahead but never go back
+    let expires_at = expected_timestamp + 60;
+    env.signed_proof_of_life(SEED, &relayer, 5, expires_at).await.unwrap();
+    assert_eq!(env.deposit_state(SEED).await.heartbeat_nonce, 5);
+}
+
+#[tokio::test]
+async fn test_heartbeat_cannot_be_replayed() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let relayer = env.new_delegate();
+    let other_relayer = env.new_delegate();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let expires_at = now(&mut env.context).await + 60;
+
+    env.signed_proof_of_life(SEED, &relayer, 3, expires_at).await.unwrap();
+    assert_dielemma_error_at(
+        env.signed_proof_of_life(SEED, &other_relayer, 3, expires_at).await,
+        1,
+        DielemmaError::HeartbeatNonceUsed,
+    );
+    assert_dielemma_error_at(
+        env.signed_proof_of_life(SEED, &other_relayer, 2, expires_at).await,
+        1,
+        DielemmaError::HeartbeatNonceUsed,
+    );
+}
+
+#[tokio::test]
+async fn test_expired_heartbeat_is_rejected() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let relayer = env.new_delegate();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let expires_at = now(&mut env.context).await + 60;
+
+    set_clock(&mut env.context, expires_at + 1).await;
+    assert_dielemma_error_at(
+        env.signed_proof_of_life(SEED, &relayer, 1, expires_at).await,
+        1,
+        DielemmaError::HeartbeatExpired,
+    );
+}
+
+#[tokio::test]
+async fn test_heartbeat_requires_depositor_signature() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let relayer = env.new_delegate();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let expires_at = now(&mut env.context).await + 60;
+
+    // Signed by someone else
+    let impostor = Keypair::new();
+    let ixs = env.signed_proof_of_life_ixs(SEED, &relayer.pubkey(), &impostor, 1, expires_at);
+    assert_dielemma_error_at(
+        process(&mut env.context, &ixs, &[&relayer]).await,
+        1,
+        DielemmaError::InvalidHeartbeat,
+    );
+
+    // A valid signature over a different nonce
+    let depositor = env.depositor.insecure_clone();
+    let mut ixs = env.signed_proof_of_life_ixs(SEED, &relayer.pubkey(), &depositor, 1, expires_at);
+    ixs[1] = env.signed_proof_of_life_ixs(SEED, &relayer.pubkey(), &depositor, 2, expires_at).remove(1);
+    assert_dielemma_error_at(
+        process(&mut env.context, &ixs, &[&relayer]).await,
+        1,
+        DielemmaError::InvalidHeartbeat,
+    );
+
+    // A valid signature over the same heartbeat for another program
+    let other_program = Pubkey::new_unique();
+    let message = instruction::heartbeat_message(&other_program, &env.deposit_address(SEED), 1, expires_at);
+    let signature: [u8; 64] = depositor.sign_message(&message).as_ref().try_into().unwrap();
+    let mut ixs = env.signed_proof_of_life_ixs(SEED, &relayer.pubkey(), &depositor, 1, expires_at);
+    ixs[0] = instruction::ed25519_signature_instruction(&depositor.pubkey(), &signature, &message);
+    assert_dielemma_error_at(
+        process(&mut env.context, &ixs, &[&relayer]).await,
+        1,
+        DielemmaError::InvalidHeartbeat,
+    );
+
+    // No ed25519 instruction at all
+    let ixs = env.signed_proof_of_life_ixs(SEED, &relayer.pubkey(), &depositor, 1, expires_at);
+    assert_dielemma_error(
+        process(&mut env.context, &ixs[1..], &[&relayer]).await,
+        DielemmaError::InvalidHeartbeat,
+    );
+
+    // A forged signature fails the ed25519 program before the heartbeat is processed
+    let mut ixs = env.signed_proof_of_life_ixs(SEED, &relayer.pubkey(), &depositor, 1, expires_at);
+    ixs[0].data[48] ^= 1;
+    assert!(process(&mut env.context, &ixs, &[&relayer]).await.is_err());
+    assert_eq!(env.deposit_state(SEED).await.heartbeat_nonce, 0);
+}
+
+#[tokio::test]
+async fn test_heartbeat_rejected_for_closed_deposit() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let relayer = env.new_delegate();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.withdraw(SEED).await.unwrap();
+    let expires_at = now(&mut env.context).await + 60;
+
+    assert_dielemma_error_at(
+        env.signed_proof_of_life(SEED, &relayer, 1, expires_at).await,
+        1,
+        DielemmaError::AlreadyClosed,
+    );
+}
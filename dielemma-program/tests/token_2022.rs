@@ -0,0 +1,167 @@
+//! Deposits of Token-2022 mints, with and without mint extensions such as transfer fees
+
+mod common;
+
+use common::*;
+use dielemma_program::DielemmaError;
+use solana_program_test::tokio;
+use solana_program::pubkey::Pubkey;
+use spl_token_2022::{
+    extension::{
+        transfer_fee::{instruction::harvest_withheld_tokens_to_mint, TransferFeeAmount, TransferFeeConfig},
+        BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+    },
+    state::{Account as TokenAccount, Mint},
+};
+
+const SEED: &str = "test-seed";
+const AMOUNT: u64 = 500_000;
+
+#[tokio::test]
+async fn test_token_2022_deposit_and_withdraw() {
+    let mut env = TestEnv::new(spl_token_2022::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    let vault = env.vault_address(SEED);
+    let vault_account = env.context.banks_client.get_account(vault).await.unwrap().unwrap();
+    assert_eq!(vault_account.owner, spl_token_2022::id());
+    assert_eq!(env.balance(vault).await, AMOUNT);
+    assert_eq!(env.deposit_state(SEED).await.amount, AMOUNT);
+
+    env.withdraw(SEED).await.unwrap();
+    assert_eq!(env.balance(env.depositor_token).await, INITIAL_BALANCE);
+    assert_eq!(env.balance(vault).await, 0);
+}
+
+#[tokio::test]
+async fn test_token_2022_claim_after_expiry() {
+    let mut env = TestEnv::new(spl_token_2022::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.proof_of_life(SEED).await.unwrap();
+
+    env.warp_after_last_proof(SEED, DAY as i64 - 1).await;
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::NotExpired);
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT);
+    assert!(env.deposit_state(SEED).await.is_closed);
+}
+
+#[tokio::test]
+async fn test_vault_sized_for_mint_extensions() {
+    let mut env = TestEnv::new(spl_token_2022::id()).await;
+    env.use_transfer_fee_mint(0, 0).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    // Accounts of a transfer-fee mint must carry the withheld amount extension
+    let vault = env.vault_address(SEED);
+    assert_eq!(
+        account_data_len(&mut env.context, &vault).await,
+        ExtensionType::get_account_len::<TokenAccount>(&[ExtensionType::TransferFeeAmount])
+    );
+    assert_eq!(env.balance(vault).await, AMOUNT);
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT);
+}
+
+#[tokio::test]
+async fn test_token_2022_rejects_mismatched_mint_account() {
+    let mut env = TestEnv::new(spl_token_2022::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let depositor = env.depositor.insecure_clone();
+
+    let other_mint = Pubkey::new_unique();
+    set_mint(&mut env.context, &other_mint, 9, &spl_token_2022::id());
+    let mut ix = env.withdraw_ix(SEED);
+    ix.accounts[6].pubkey = other_mint;
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        DielemmaError::MintMismatch,
+    );
+
+    // The deposit mint must match the depositor's token account
+    let mut ix = env.deposit_ix("other-seed", AMOUNT, DAY);
+    ix.accounts[4].pubkey = other_mint;
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        DielemmaError::MintMismatch,
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_fee_deposit_records_net_amount() {
+    let mut env = TestEnv::new(spl_token_2022::id()).await;
+    // 1% fee, capped well above the amounts used here
+    env.use_transfer_fee_mint(100, AMOUNT).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    let net = AMOUNT - AMOUNT / 100;
+    assert_eq!(env.deposit_state(SEED).await.amount, net);
+    assert_eq!(env.balance(env.vault_address(SEED)).await, net);
+    assert_eq!(env.balance(env.depositor_token).await, INITIAL_BALANCE - AMOUNT);
+
+    // Withdrawing pays the fee again on the way out
+    env.withdraw(SEED).await.unwrap();
+    assert_eq!(env.balance(env.vault_address(SEED)).await, 0);
+    assert_eq!(
+        env.balance(env.depositor_token).await,
+        INITIAL_BALANCE - AMOUNT + net - net / 100
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_fee_harvest_keeps_vault_balance() {
+    let mut env = TestEnv::new(spl_token_2022::id()).await;
+    env.use_transfer_fee_mint(100, AMOUNT).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let net = env.deposit_state(SEED).await.amount;
+
+    // Anyone may sweep the fee withheld on the vault to the mint
+    let vault = env.vault_address(SEED);
+    let harvest_ix = harvest_withheld_tokens_to_mint(&spl_token_2022::id(), &env.mint, &[&vault]).unwrap();
+    process(&mut env.context, &[harvest_ix], &[]).await.unwrap();
+    assert_eq!(env.balance(vault).await, net);
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, net - net / 100);
+    assert_eq!(env.balance(vault).await, 0);
+}
+
+/// Transfer fees withheld on a token account and on a mint
+async fn withheld_amounts(env: &mut TestEnv, account: Pubkey) -> (u64, u64) {
+    let account = env.context.banks_client.get_account(account).await.unwrap().unwrap();
+    let account = StateWithExtensions::<TokenAccount>::unpack(&account.data).unwrap();
+    let mint = env.context.banks_client.get_account(env.mint).await.unwrap().unwrap();
+    let mint = StateWithExtensions::<Mint>::unpack(&mint.data).unwrap();
+    (
+        account.get_extension::<TransferFeeAmount>().unwrap().withheld_amount.into(),
+        mint.get_extension::<TransferFeeConfig>().unwrap().withheld_amount.into(),
+    )
+}
+
+#[tokio::test]
+async fn test_emptying_vault_harvests_withheld_fees() {
+    let mut env = TestEnv::new(spl_token_2022::id()).await;
+    env.use_transfer_fee_mint(100, AMOUNT).await;
+    env.deposit("withdrawn", AMOUNT, DAY).await.unwrap();
+    env.deposit("claimed", AMOUNT, DAY).await.unwrap();
+    let fee = AMOUNT / 100;
+    let vault = env.vault_address("withdrawn");
+    assert_eq!(withheld_amounts(&mut env, vault).await, (fee, 0));
+
+    env.withdraw("withdrawn").await.unwrap();
+    assert_eq!(withheld_amounts(&mut env, vault).await, (0, fee));
+
+    let vault = env.vault_address("claimed");
+    env.warp_after_last_proof("claimed", DAY as i64).await;
+    env.wait_out_claim("claimed").await;
+    env.claim("claimed").await.unwrap();
+    assert_eq!(withheld_amounts(&mut env, vault).await, (0, 2 * fee));
+}
@@ -0,0 +1,98 @@
+//! TopUp: adding tokens to an existing deposit
+
+mod common;
+
+use common::*;
+use dielemma_program::{DielemmaError, MAX_DEPOSIT_AMOUNT};
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+
+const SEED: &str = "test-seed";
+const AMOUNT: u64 = 500_000;
+
+#[tokio::test]
+async fn test_top_up_adds_to_deposit() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let last_proof_timestamp = env.deposit_state(SEED).await.last_proof_timestamp;
+
+    env.top_up(SEED, 200_000).await.unwrap();
+    env.top_up(SEED, 300_000).await.unwrap();
+
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.amount, 1_000_000);
+    assert_eq!(deposit.last_proof_timestamp, last_proof_timestamp);
+    assert_eq!(env.balance(env.vault_address(SEED)).await, 1_000_000);
+    assert_eq!(env.balance(env.depositor_token).await, INITIAL_BALANCE - 1_000_000);
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, 1_000_000);
+}
+
+#[tokio::test]
+async fn test_top_up_with_transfer_fee_records_net_amount() {
+    let mut env = TestEnv::new(spl_token_2022::id()).await;
+    // 1% fee, capped well above the amounts used here
+    env.use_transfer_fee_mint(100, AMOUNT).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    env.top_up(SEED, AMOUNT).await.unwrap();
+    let net = AMOUNT - AMOUNT / 100;
+    assert_eq!(env.deposit_state(SEED).await.amount, 2 * net);
+    assert_eq!(env.balance(env.vault_address(SEED)).await, 2 * net);
+}
+
+#[tokio::test]
+async fn test_top_up_respects_max_amount() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    assert_dielemma_error(env.top_up(SEED, 0).await, DielemmaError::InvalidAmount);
+    assert_dielemma_error(
+        env.top_up(SEED, MAX_DEPOSIT_AMOUNT - AMOUNT + 1).await,
+        DielemmaError::InvalidAmount,
+    );
+    assert_dielemma_error(env.top_up(SEED, u64::MAX).await, DielemmaError::InvalidAmount);
+    assert_eq!(env.deposit_state(SEED).await.amount, AMOUNT);
+}
+
+#[tokio::test]
+async fn test_top_up_rejects_invalid_requests() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let (second, second_token) = env.new_receiver();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.deposit("other-seed", AMOUNT, DAY).await.unwrap();
+
+    // Tokens must land in this deposit's own vault
+    let mut ix = env.top_up_ix(SEED, AMOUNT);
+    ix.accounts[3].pubkey = env.vault_address("other-seed");
+    let depositor = env.depositor.insecure_clone();
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&depositor]).await,
+        DielemmaError::InvalidVaultAccount,
+    );
+
+    // Only the depositor can top up
+    let receiver = env.receiver.insecure_clone();
+    let mut ix = env.top_up_ix(SEED, AMOUNT);
+    ix.accounts[0].pubkey = receiver.pubkey();
+    ix.accounts[2].pubkey = env.receiver_token;
+    assert_dielemma_error(
+        process(&mut env.context, &[ix], &[&receiver]).await,
+        DielemmaError::InvalidDepositAccount,
+    );
+
+    // No top-ups once a share has been paid out
+    env.set_beneficiaries(SEED, &[(env.receiver.pubkey(), 5_000), (second.pubkey(), 5_000)])
+        .await
+        .unwrap();
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim_as(SEED, &second, &second_token).await.unwrap();
+    assert_dielemma_error(env.top_up(SEED, AMOUNT).await, DielemmaError::ShareAlreadyClaimed);
+
+    env.withdraw(SEED).await.unwrap();
+    assert_dielemma_error(env.top_up(SEED, AMOUNT).await, DielemmaError::AlreadyClosed);
+}
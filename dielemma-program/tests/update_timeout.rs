@@ -0,0 +1,117 @@
+//! UpdateTimeout: immediate increases and delayed reductions
+
+mod common;
+
+use common::*;
+use dielemma_program::{DielemmaError, MAX_TIMEOUT_SECONDS, MIN_TIMEOUT_SECONDS};
+use solana_program_test::tokio;
+
+const SEED: &str = "test-seed";
+const AMOUNT: u64 = 500_000;
+const HOUR: u64 = 3_600;
+
+#[tokio::test]
+async fn test_increase_applies_immediately() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    env.update_timeout(SEED, 2 * DAY).await.unwrap();
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.timeout_seconds, 2 * DAY);
+    assert_eq!(deposit.pending_timeout_seconds, 0);
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::NotExpired);
+
+    env.warp_after_last_proof(SEED, 2 * DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_reduction_waits_for_current_timeout() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    let scheduled_at = env.deposit_state(SEED).await.last_proof_timestamp;
+
+    env.update_timeout(SEED, HOUR).await.unwrap();
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.timeout_seconds, DAY);
+    assert_eq!(deposit.pending_timeout_seconds, HOUR);
+    assert_eq!(deposit.pending_timeout_effective_at, scheduled_at + DAY as i64);
+
+    // The shorter timeout does not apply before the old window has passed
+    env.warp_after_last_proof(SEED, HOUR as i64).await;
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::NotExpired);
+
+    // Once it is due, it measures from the latest proof of life
+    set_clock(&mut env.context, scheduled_at + DAY as i64 - 100).await;
+    env.proof_of_life(SEED).await.unwrap();
+    env.warp_after_last_proof(SEED, HOUR as i64 - 1).await;
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::NotExpired);
+    env.warp_after_last_proof(SEED, HOUR as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_increase_cancels_pending_reduction() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    env.update_timeout(SEED, HOUR).await.unwrap();
+    env.update_timeout(SEED, DAY).await.unwrap();
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.timeout_seconds, DAY);
+    assert_eq!(deposit.pending_timeout_seconds, 0);
+    assert_eq!(deposit.pending_timeout_effective_at, 0);
+}
+
+#[tokio::test]
+async fn test_increase_rejected_once_expired() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    // A longer timeout would otherwise revive the deposit without burning DLM
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    assert_dielemma_error(env.update_timeout(SEED, 2 * DAY).await, DielemmaError::DepositExpired);
+    env.proof_of_life(SEED).await.unwrap();
+    env.update_timeout(SEED, 2 * DAY).await.unwrap();
+    assert_eq!(env.deposit_state(SEED).await.timeout_seconds, 2 * DAY);
+}
+
+#[tokio::test]
+async fn test_profile_proof_keeps_timeout_updatable() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.create_profile().await.unwrap();
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.set_profile_link(SEED, true).await.unwrap();
+
+    env.warp_after_last_proof(SEED, DAY as i64 - 1).await;
+    env.profile_proof_of_life().await.unwrap();
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.update_timeout(SEED, 2 * DAY).await.unwrap();
+    assert_eq!(env.deposit_state(SEED).await.timeout_seconds, 2 * DAY);
+}
+
+#[tokio::test]
+async fn test_update_timeout_rejects_invalid_requests() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    assert_dielemma_error(
+        env.update_timeout(SEED, MIN_TIMEOUT_SECONDS - 1).await,
+        DielemmaError::InvalidTimeout,
+    );
+    assert_dielemma_error(
+        env.update_timeout(SEED, MAX_TIMEOUT_SECONDS + 1).await,
+        DielemmaError::InvalidTimeout,
+    );
+
+    env.set_paused(true).await.unwrap();
+    assert_dielemma_error(env.update_timeout(SEED, 2 * DAY).await, DielemmaError::ProgramPaused);
+    env.set_paused(false).await.unwrap();
+
+    env.withdraw(SEED).await.unwrap();
+    assert_dielemma_error(env.update_timeout(SEED, 2 * DAY).await, DielemmaError::AlreadyClosed);
+}
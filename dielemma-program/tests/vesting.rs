@@ -0,0 +1,137 @@
+//! Vesting payouts: SetVesting and repeated claims streaming shares out after expiry
+
+mod common;
+
+use common::*;
+use dielemma_program::{DielemmaError, MAX_VESTING_SECONDS};
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+
+const SEED: &str = "test-seed";
+const AMOUNT: u64 = 1_000_000;
+const VESTING: u64 = 10 * DAY;
+
+#[tokio::test]
+async fn test_vested_amount_streams_to_receiver() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.set_vesting(SEED, VESTING).await.unwrap();
+    let expired_at = env.deposit_state(SEED).await.last_proof_timestamp + DAY as i64;
+
+    // Vesting runs from expiry, so the challenge period already counts towards it
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    let claimed_at = now(&mut env.context).await;
+    env.claim(SEED).await.unwrap();
+    let first = AMOUNT * (claimed_at - expired_at) as u64 / VESTING;
+    assert_eq!(env.balance(env.receiver_token).await, first);
+
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.vesting_started_at, expired_at);
+    assert_eq!(deposit.beneficiaries()[0].claimed_amount, first);
+    assert!(!deposit.beneficiaries()[0].claimed);
+    assert!(!deposit.is_closed);
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::NothingVested);
+
+    set_clock(&mut env.context, expired_at + VESTING as i64 / 2).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT / 2);
+
+    // The deposit closes once the whole share has vested and been paid out
+    set_clock(&mut env.context, expired_at + VESTING as i64).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT);
+    assert_eq!(env.balance(env.vault_address(SEED)).await, 0);
+
+    let deposit = env.deposit_state(SEED).await;
+    assert!(deposit.is_closed);
+    assert_eq!(deposit.claimed_amount, AMOUNT);
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::AlreadyClosed);
+}
+
+#[tokio::test]
+async fn test_proof_of_life_stops_vesting_claims() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.set_vesting(SEED, VESTING).await.unwrap();
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    let claimed = env.balance(env.receiver_token).await;
+
+    // A returning depositor keeps the unvested remainder
+    env.proof_of_life(SEED).await.unwrap();
+    assert_dielemma_error(env.claim(SEED).await, DielemmaError::NotExpired);
+    env.withdraw(SEED).await.unwrap();
+    assert_eq!(env.balance(env.depositor_token).await, INITIAL_BALANCE - claimed);
+}
+
+#[tokio::test]
+async fn test_proof_of_life_restarts_vesting() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+    env.set_vesting(SEED, VESTING).await.unwrap();
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    let claimed = env.balance(env.receiver_token).await;
+
+    // The payout stays counted against the share and the schedule starts over
+    env.proof_of_life(SEED).await.unwrap();
+    let deposit = env.deposit_state(SEED).await;
+    assert_eq!(deposit.amount, AMOUNT);
+    assert_eq!(deposit.claimed_amount, claimed);
+    assert_eq!(deposit.beneficiaries()[0].claimed_amount, claimed);
+    assert_eq!(deposit.vesting_started_at, 0);
+    assert_dielemma_error(env.set_vesting(SEED, 2 * VESTING).await, DielemmaError::ShareAlreadyClaimed);
+
+    let expired_at = deposit.last_proof_timestamp + DAY as i64;
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    set_clock(&mut env.context, expired_at + VESTING as i64 / 2).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.deposit_state(SEED).await.vesting_started_at, expired_at);
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT / 2);
+}
+
+#[tokio::test]
+async fn test_sol_deposit_vests() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit_sol(SEED, 1_000_000_000, DAY).await.unwrap();
+    env.set_vesting(SEED, VESTING).await.unwrap();
+    let expired_at = env.deposit_state(SEED).await.last_proof_timestamp + DAY as i64;
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    set_clock(&mut env.context, expired_at + VESTING as i64 / 2).await;
+    let before = env.lamports(env.receiver.pubkey()).await;
+    env.claim_sol(SEED, None).await.unwrap();
+    assert_eq!(env.lamports(env.receiver.pubkey()).await, before + 500_000_000);
+    assert!(!env.deposit_state(SEED).await.is_closed);
+
+    set_clock(&mut env.context, expired_at + VESTING as i64).await;
+    env.claim_sol(SEED, None).await.unwrap();
+    assert_eq!(env.lamports(env.receiver.pubkey()).await, before + 1_000_000_000);
+    assert!(env.deposit_state(SEED).await.is_closed);
+}
+
+#[tokio::test]
+async fn test_set_vesting_validation() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    env.deposit(SEED, AMOUNT, DAY).await.unwrap();
+
+    assert_dielemma_error(
+        env.set_vesting(SEED, MAX_VESTING_SECONDS + 1).await,
+        DielemmaError::InvalidVesting,
+    );
+    env.set_vesting(SEED, VESTING).await.unwrap();
+    assert_eq!(env.deposit_state(SEED).await.vesting_seconds, VESTING);
+
+    // The schedule is frozen once the receiver has started claiming
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert_dielemma_error(env.set_vesting(SEED, 0).await, DielemmaError::ShareAlreadyClaimed);
+}
@@ -0,0 +1,205 @@
+//! Withdrawal locks: deposits the depositor cannot withdraw before a date, or ever
+
+mod common;
+
+use common::*;
+use dielemma_program::{instruction, DielemmaError, WITHDRAW_LOCKED_FOREVER};
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+
+const SEED: &str = "test-seed";
+const AMOUNT: u64 = 500_000;
+
+#[tokio::test]
+async fn test_withdraw_locked_until_date() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let locked_until = now(&mut env.context).await + 30 * DAY as i64;
+    let ix = env.locked_deposit_ix(SEED, AMOUNT, DAY, locked_until);
+    let depositor = env.depositor.insecure_clone();
+    process(&mut env.context, &[ix], &[&depositor]).await.unwrap();
+    assert_eq!(env.deposit_state(SEED).await.withdraw_locked_until, locked_until);
+
+    assert_dielemma_error(env.withdraw(SEED).await, DielemmaError::WithdrawLocked);
+    assert_dielemma_error(env.partial_withdraw(SEED, 1).await, DielemmaError::WithdrawLocked);
+
+    // Proof of life keeps working while locked
+    env.proof_of_life(SEED).await.unwrap();
+
+    set_clock(&mut env.context, locked_until).await;
+    env.partial_withdraw(SEED, 1).await.unwrap();
+    env.withdraw(SEED).await.unwrap();
+    assert_eq!(env.balance(env.depositor_token).await, INITIAL_BALANCE);
+}
+
+#[tokio::test]
+async fn test_irrevocable_deposit_can_still_be_claimed() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let ix = env.locked_deposit_ix(SEED, AMOUNT, DAY, WITHDRAW_LOCKED_FOREVER);
+    let depositor = env.depositor.insecure_clone();
+    process(&mut env.context, &[ix], &[&depositor]).await.unwrap();
+
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    assert_dielemma_error(env.withdraw(SEED).await, DielemmaError::WithdrawLocked);
+
+    env.wait_out_claim(SEED).await;
+    env.claim(SEED).await.unwrap();
+    assert_eq!(env.balance(env.receiver_token).await, AMOUNT);
+}
+
+#[tokio::test]
+async fn test_sol_withdraw_lock() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let locked_until = now(&mut env.context).await + DAY as i64;
+    let ix = instruction::deposit_sol(
+        &dielemma_program::id(),
+        &env.depositor.pubkey(),
+        SEED,
+        &env.receiver.pubkey(),
+        1_000_000_000,
+        DAY,
+        locked_until,
+    );
+    let depositor = env.depositor.insecure_clone();
+    process(&mut env.context, &[ix], &[&depositor]).await.unwrap();
+
+    assert_dielemma_error(env.withdraw_sol(SEED).await, DielemmaError::WithdrawLocked);
+    set_clock(&mut env.context, locked_until).await;
+    env.withdraw_sol(SEED).await.unwrap();
+    assert!(env.deposit_state(SEED).await.is_closed);
+}
+
+/// Make an irrevocable deposit for the lock tests below
+async fn irrevocable_deposit(env: &mut TestEnv) {
+    let ix = env.locked_deposit_ix(SEED, AMOUNT, DAY, WITHDRAW_LOCKED_FOREVER);
+    let depositor = env.depositor.insecure_clone();
+    process(&mut env.context, &[ix], &[&depositor]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_locked_deposit_rejects_set_beneficiaries() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    irrevocable_deposit(&mut env).await;
+
+    // Otherwise the depositor could name themselves receiver and claim the deposit back
+    let depositor = env.depositor.pubkey();
+    assert_dielemma_error(env.set_beneficiaries(SEED, &[(depositor, 10_000)]).await, DielemmaError::WithdrawLocked);
+    assert_eq!(env.deposit_state(SEED).await.beneficiaries()[0].receiver, env.receiver.pubkey());
+}
+
+#[tokio::test]
+async fn test_locked_deposit_rejects_update_receiver() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    irrevocable_deposit(&mut env).await;
+
+    let (receiver, depositor) = (env.receiver.pubkey(), env.depositor.pubkey());
+    assert_dielemma_error(
+        env.update_receiver(SEED, &receiver, &depositor, false).await,
+        DielemmaError::WithdrawLocked,
+    );
+}
+
+#[tokio::test]
+async fn test_locked_deposit_rejects_update_timeout() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    irrevocable_deposit(&mut env).await;
+
+    assert_dielemma_error(env.update_timeout(SEED, 2 * DAY).await, DielemmaError::WithdrawLocked);
+    assert_eq!(env.deposit_state(SEED).await.timeout_seconds, DAY);
+}
+
+#[tokio::test]
+async fn test_locked_deposit_rejects_set_vesting() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    irrevocable_deposit(&mut env).await;
+
+    assert_dielemma_error(env.set_vesting(SEED, 365 * DAY).await, DielemmaError::WithdrawLocked);
+}
+
+#[tokio::test]
+async fn test_locked_deposit_rejects_set_release_tranches() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    irrevocable_deposit(&mut env).await;
+
+    assert_dielemma_error(env.set_release_tranches(SEED, &[(1, 10_000)]).await, DielemmaError::WithdrawLocked);
+}
+
+#[tokio::test]
+async fn test_locked_deposit_rejects_set_release_date() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    irrevocable_deposit(&mut env).await;
+
+    let release_at = now(&mut env.context).await + 365 * DAY as i64;
+    assert_dielemma_error(env.set_release_date(SEED, release_at, true).await, DielemmaError::WithdrawLocked);
+}
+
+#[tokio::test]
+async fn test_locked_deposit_rejects_set_fallback_receiver() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    irrevocable_deposit(&mut env).await;
+
+    let depositor = env.depositor.pubkey();
+    assert_dielemma_error(env.set_fallback_receiver(SEED, &depositor).await, DielemmaError::WithdrawLocked);
+}
+
+#[tokio::test]
+async fn test_locked_deposit_rejects_set_fallback_chain() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    irrevocable_deposit(&mut env).await;
+
+    let depositor = env.depositor.pubkey();
+    assert_dielemma_error(env.set_fallback_chain(SEED, &[(depositor, DAY)]).await, DielemmaError::WithdrawLocked);
+}
+
+#[tokio::test]
+async fn test_locked_deposit_rejects_cancel_claim() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    irrevocable_deposit(&mut env).await;
+    env.warp_after_last_proof(SEED, DAY as i64).await;
+    env.initiate_claim(SEED).await.unwrap();
+
+    // Only a proof of life, burning DLM and restarting the timer, can void the claim
+    assert_dielemma_error(env.cancel_claim(SEED).await, DielemmaError::WithdrawLocked);
+    assert!(env.deposit_state(SEED).await.has_pending_claim());
+    env.proof_of_life(SEED).await.unwrap();
+    assert!(!env.deposit_state(SEED).await.has_pending_claim());
+}
+
+#[tokio::test]
+async fn test_settings_unlock_with_withdrawal() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+    let locked_until = now(&mut env.context).await + DAY as i64;
+    let ix = env.locked_deposit_ix(SEED, AMOUNT, 2 * DAY, locked_until);
+    let depositor = env.depositor.insecure_clone();
+    process(&mut env.context, &[ix], &[&depositor]).await.unwrap();
+
+    assert_dielemma_error(env.update_timeout(SEED, 3 * DAY).await, DielemmaError::WithdrawLocked);
+    set_clock(&mut env.context, locked_until).await;
+    env.update_timeout(SEED, 3 * DAY).await.unwrap();
+    assert_eq!(env.deposit_state(SEED).await.timeout_seconds, 3 * DAY);
+}
+
+#[tokio::test]
+async fn test_deposit_without_lock_field_is_unlocked() {
+    let mut env = TestEnv::new(spl_token::id()).await;
+
+    // Clients written before withdrawal locks leave the trailing field out
+    let mut ix = env.deposit_ix(SEED, AMOUNT, DAY);
+    ix.data.truncate(ix.data.len() - 8);
+    let mut sol_ix = instruction::deposit_sol(
+        &dielemma_program::id(),
+        &env.depositor.pubkey(),
+        "sol-seed",
+        &env.receiver.pubkey(),
+        1_000_000_000,
+        DAY,
+        0,
+    );
+    sol_ix.data.truncate(sol_ix.data.len() - 8);
+    let depositor = env.depositor.insecure_clone();
+    process(&mut env.context, &[ix, sol_ix], &[&depositor]).await.unwrap();
+
+    assert_eq!(env.deposit_state(SEED).await.withdraw_locked_until, 0);
+    assert_eq!(env.deposit_state("sol-seed").await.withdraw_locked_until, 0);
+    env.withdraw(SEED).await.unwrap();
+    env.withdraw_sol("sol-seed").await.unwrap();
+}